@@ -9,7 +9,7 @@ use std::io::ErrorKind;
 use std::convert::TryFrom;
 
 use tracing::debug;
-use fluvio::{PartitionConsumer, Offset, ConsumerConfig};
+use fluvio::{PartitionConsumer, Offset, ConsumerConfig, StreamEnd};
 use futures_lite::StreamExt;
 
 use crate::error::CliError;
@@ -75,44 +75,29 @@ where
         if let Some(max_bytes) = opt.max_bytes {
             config = config.with_max_bytes(max_bytes);
         }
+        // without continuous processing, stream ends at records which exist now
+        if opt.disable_continuous {
+            config = config.with_end(StreamEnd::HighWatermark);
+        }
         config
     };
 
-    if opt.disable_continuous {
-        let response = consumer
-            .fetch_with_config(initial_offset, fetch_config)
-            .await?;
+    let mut log_stream = consumer
+        ._stream_batches_with_config(initial_offset, fetch_config)
+        .await?;
 
+    while let Some(Ok(response)) = log_stream.next().await {
+        let partition = response.partition;
         debug!(
-            "got a single response: LSO: {} batches: {}",
-            response.log_start_offset,
-            response.records.batches.len(),
+            "got response: LSO: {} batchs: {}",
+            partition.log_start_offset,
+            partition.records.batches.len(),
         );
 
-        process_fetch_topic_response(out.clone(), response, &opt).await?;
-    } else {
-        let mut log_stream = consumer
-            ._stream_batches_with_config(initial_offset, fetch_config)
-            .await?;
-
-        while let Some(Ok(response)) = log_stream.next().await {
-            let partition = response.partition;
-            debug!(
-                "got response: LSO: {} batchs: {}",
-                partition.log_start_offset,
-                partition.records.batches.len(),
-            );
-
-            process_fetch_topic_response(out.clone(), partition, &opt).await?;
-
-            if opt.disable_continuous {
-                debug!("finishing fetch loop");
-                break;
-            }
-        }
-
-        debug!("fetch loop exited");
+        process_fetch_topic_response(out.clone(), partition, &opt).await?;
     }
 
+    debug!("fetch loop exited");
+
     Ok(())
 }
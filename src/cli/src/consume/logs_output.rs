@@ -93,7 +93,7 @@ pub fn partition_to_json_records(
 
     // convert all batches to json records
     for batch in &partition.records.batches {
-        for record in &batch.records {
+        for record in batch.records() {
             if let Some(batch_record) = record.get_value().inner_value_ref() {
                 match serde_json::from_slice(&batch_record) {
                     Ok(value) => json_records.push(value),
@@ -142,7 +142,7 @@ pub fn print_text_records<O>(
         }
 
         for batch in &r_partition.records.batches {
-            for record in &batch.records {
+            for record in batch.records() {
                 if record.get_value().inner_value_ref().is_some() {
                     if record.get_value().is_binary() {
                         if !suppress {
@@ -184,7 +184,7 @@ pub fn print_binary_records<O>(
         }
 
         for batch in &r_partition.records.batches {
-            for record in &batch.records {
+            for record in batch.records() {
                 if let Some(batch_record) = record.get_value().inner_value_ref() {
                     t_println!(out, "{}", hex_dump_separator());
                     t_println!(out, "{}", bytes_to_hex_dump(&batch_record));
@@ -217,7 +217,7 @@ pub fn print_dynamic_records<O>(
         }
 
         for batch in &r_partition.records.batches {
-            for record in &batch.records {
+            for record in batch.records() {
                 if let Some(batch_record) = record.get_value().inner_value_ref() {
                     // TODO: this should be refactored
                     if let Some(bytes) = record.get_value().inner_value_ref() {
@@ -255,7 +255,7 @@ pub fn print_raw_records<O>(
         }
 
         for batch in &r_partition.records.batches {
-            for record in &batch.records {
+            for record in batch.records() {
                 if let Some(value) = record.get_value().inner_value_ref() {
                     let str_value = std::str::from_utf8(value).unwrap();
                     t_println!(out, "{}", str_value);
@@ -4,7 +4,7 @@ use std::sync::Arc;
 use tracing::debug;
 use structopt::StructOpt;
 
-use fluvio::Fluvio;
+use fluvio::{Fluvio, ProducerConfig, Compression};
 
 use crate::Result;
 use crate::Terminal;
@@ -15,6 +15,7 @@ pub struct ProduceLogConfig {
     pub topic: String,
     pub partition: i32,
     pub continuous: bool,
+    pub compression: Compression,
 }
 
 #[derive(Debug)]
@@ -64,12 +65,19 @@ pub struct ProduceLogOpt {
         conflicts_with = "record-per-line"
     )]
     record_file: Vec<PathBuf>,
+
+    /// Compress records with codec: none, gzip, snappy, lz4 or zstd
+    #[structopt(long = "compression", value_name = "codec", default_value = "none")]
+    compression: Compression,
 }
 
 impl ProduceLogOpt {
     pub async fn process<O: Terminal>(self, out: Arc<O>, fluvio: &Fluvio) -> Result<()> {
         let (cfg, file_records) = self.validate()?;
-        let producer = fluvio.topic_producer(&cfg.topic).await?;
+        let config = ProducerConfig::default().with_compression(cfg.compression);
+        let producer = fluvio
+            .topic_producer_with_config(&cfg.topic, config)
+            .await?;
 
         debug!("got producer");
         if let Some(records) = file_records {
@@ -95,6 +103,7 @@ impl ProduceLogOpt {
             topic: self.topic,
             partition: self.partition,
             continuous: self.continuous,
+            compression: self.compression,
         };
 
         Ok((produce_log_cfg, file_records))
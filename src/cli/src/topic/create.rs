@@ -77,6 +77,21 @@ pub struct CreateTopicOpt {
     )]
     replica_assignment: Option<PathBuf>,
 
+    /// Topic configuration in key=value form, can be repeated
+    ///
+    /// Supported keys: retention.seconds, retention.bytes, segment.bytes,
+    /// max.message.bytes, compression.type, cleanup.policy,
+    /// message.timestamp.type, flush.messages, flush.ms, flush.before.commit
+    /// and delete.retention.seconds
+    #[structopt(
+        short = "c",
+        long = "config",
+        value_name = "key=value",
+        parse(try_from_str = parse_key_val),
+        number_of_values = 1
+    )]
+    config: Vec<(String, String)>,
+
     /// Validates configuration, does not provision
     #[structopt(short = "d", long)]
     dry_run: bool,
@@ -98,6 +113,7 @@ impl CreateTopicOpt {
     /// Validate cli options. Generate target-server and create-topic configuration.
     fn validate(self) -> Result<(String, TopicSpec)> {
         use fluvio::metadata::topic::PartitionMaps;
+        use fluvio::metadata::topic::TopicConfig;
         use fluvio::metadata::topic::TopicReplicaParam;
         use load::PartitionLoad;
 
@@ -114,18 +130,39 @@ impl CreateTopicOpt {
                 })?,
             )
         } else {
-            TopicSpec::Computed(TopicReplicaParam {
-                partitions: self.partitions,
-                replication_factor: self.replication as i32,
-                ignore_rack_assignment: self.ignore_rack_assigment,
-            })
+            TopicSpec::Computed(TopicReplicaParam::new(
+                self.partitions,
+                self.replication as i32,
+                self.ignore_rack_assigment,
+            ))
         };
 
+        let config = self
+            .config
+            .into_iter()
+            .fold(TopicConfig::default(), |config, (key, value)| {
+                config.with(key, value)
+            });
+        config.validate()?;
+
+        let topic = topic.with_config(config);
+
         // return server separately from config
         Ok((self.topic, topic))
     }
 }
 
+/// Parse a single key=value pair
+fn parse_key_val(s: &str) -> std::result::Result<(String, String), IoError> {
+    let pos = s.find('=').ok_or_else(|| {
+        IoError::new(
+            ErrorKind::InvalidInput,
+            format!("invalid key=value: no `=` found in `{}`", s),
+        )
+    })?;
+    Ok((s[..pos].to_owned(), s[pos + 1..].to_owned()))
+}
+
 /// module to load partitions maps from file
 mod load {
 
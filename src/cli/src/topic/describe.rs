@@ -140,6 +140,9 @@ mod display {
                     */
                 }
             }
+            if !spec.config().is_empty() {
+                key_values.push(("Config".to_owned(), Some(spec.config().to_string())));
+            }
 
             key_values.push((
                 "Status".to_owned(),
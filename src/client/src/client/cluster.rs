@@ -18,7 +18,10 @@ cfg_if::cfg_if! {
 use crate::config::ConfigFile;
 use crate::admin::FluvioAdmin;
 use crate::TopicProducer;
+use crate::ProducerConfig;
 use crate::PartitionConsumer;
+use crate::GroupConsumer;
+use crate::TopicConsumer;
 use crate::FluvioError;
 use crate::FluvioConfig;
 use crate::spu::SpuPool;
@@ -116,10 +119,43 @@ impl Fluvio {
     pub async fn topic_producer<S: Into<String>>(
         &self,
         topic: S,
+    ) -> Result<TopicProducer, FluvioError> {
+        self.topic_producer_with_config(topic, ProducerConfig::default())
+            .await
+    }
+
+    /// Creates a new `TopicProducer` for the given topic name using a specific configuration
+    ///
+    /// Most of the time, you shouldn't need to use a custom [`ProducerConfig`].
+    /// A custom configuration lets the producer collect events in batches
+    /// before sending them, which increases throughput when sending many
+    /// small events.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use fluvio::{Fluvio, FluvioError, ProducerConfig};
+    /// # async fn do_produce_batches(fluvio: &Fluvio) -> Result<(), FluvioError> {
+    /// let config = ProducerConfig::default().with_linger(Duration::from_millis(10));
+    /// let producer = fluvio.topic_producer_with_config("my-topic", config).await?;
+    /// for i in 0..1000 {
+    ///     producer.queue_record(format!("event {}", i), 0).await?;
+    /// }
+    /// producer.flush().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ProducerConfig`]: struct.ProducerConfig.html
+    pub async fn topic_producer_with_config<S: Into<String>>(
+        &self,
+        topic: S,
+        config: ProducerConfig,
     ) -> Result<TopicProducer, FluvioError> {
         let topic = topic.into();
         debug!(topic = &*topic, "Creating producer");
-        Ok(TopicProducer::new(topic, self.spu_pool()?))
+        Ok(TopicProducer::new(topic, self.spu_pool()?, config))
     }
 
     /// Creates a new `PartitionConsumer` for the given topic and partition
@@ -153,6 +189,53 @@ impl Fluvio {
         Ok(PartitionConsumer::new(topic, partition, self.spu_pool()?))
     }
 
+    /// Creates a new `TopicConsumer` which consumes all partitions of a topic
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use fluvio::{Fluvio, Offset, FluvioError};
+    /// # async fn do_create_topic_consumer(fluvio: &Fluvio) -> Result<(), FluvioError> {
+    /// let consumer = fluvio.topic_consumer("my-topic").await?;
+    /// let stream = consumer.stream(Offset::beginning()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn topic_consumer<S: Into<String>>(
+        &self,
+        topic: S,
+    ) -> Result<TopicConsumer, FluvioError> {
+        let topic = topic.into();
+        debug!(topic = &*topic, "Creating topic consumer");
+        Ok(TopicConsumer::new(topic, self.spu_pool()?))
+    }
+
+    /// Creates a new `GroupConsumer` which consumes a topic as member of a consumer group
+    ///
+    /// Partitions of the topic are shared by all members of the group, and
+    /// consumption resumes from offsets committed by the group.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use fluvio::{Fluvio, Offset, FluvioError};
+    /// # async fn do_create_group_consumer(fluvio: &Fluvio) -> Result<(), FluvioError> {
+    /// let consumer = fluvio.group_consumer("my-group", "my-topic").await?;
+    /// let stream = consumer.stream(Offset::beginning()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn group_consumer<G: Into<String>, S: Into<String>>(
+        &self,
+        group: G,
+        topic: S,
+    ) -> Result<GroupConsumer, FluvioError> {
+        let group = group.into();
+        let topic = topic.into();
+        debug!(group = &*group, topic = &*topic, "Creating group consumer");
+        Ok(GroupConsumer::new(group, topic, self.spu_pool()?))
+    }
+
     /// Provides an interface for managing a Fluvio cluster
     ///
     /// # Example
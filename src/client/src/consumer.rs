@@ -1,10 +1,18 @@
 use std::sync::Arc;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::time::Duration;
 
 use futures_util::stream::Stream;
+use futures_util::stream::unfold;
 use tracing::debug;
 
 use fluvio_spu_schema::server::stream_fetch::{DefaultStreamFetchRequest, DefaultStreamFetchResponse};
+use fluvio_spu_schema::server::stream_fetch::bound_records;
+use fluvio_spu_schema::server::record_filter::{RecordFilter, filter_records};
+use fluvio_spu_schema::server::smart_stream::{SmartStreamKind, SmartStreamPayload};
+use fluvio_spu_schema::server::stream_credit::{StreamFetchFlowControl, StreamFetchCreditRequest};
 use dataplane::Isolation;
+use dataplane::Timestamp;
 use dataplane::ReplicaKey;
 use dataplane::fetch::DefaultFetchRequest;
 use dataplane::fetch::FetchPartition;
@@ -12,6 +20,9 @@ use dataplane::fetch::FetchableTopic;
 use dataplane::fetch::FetchablePartitionResponse;
 use dataplane::record::RecordSet;
 use dataplane::record::DefaultRecord;
+use dataplane::record::RecordHeader;
+use fluvio_types::SpuId;
+use fluvio_socket::AsyncResponse;
 use crate::FluvioError;
 use crate::offset::Offset;
 use crate::client::SerialFrame;
@@ -77,7 +88,7 @@ impl PartitionConsumer {
     /// # async fn do_fetch(consumer: &PartitionConsumer) -> Result<(), FluvioError> {
     /// let response = consumer.fetch(Offset::beginning()).await?;
     /// for batch in response.records.batches {
-    ///     for record in batch.records {
+    ///     for record in batch.into_records() {
     ///         if let Some(record) = record.value.inner_value() {
     ///             let string = String::from_utf8(record)
     ///                 .expect("record should be a string");
@@ -124,7 +135,7 @@ impl PartitionConsumer {
     ///
     /// let response = consumer.fetch_with_config(Offset::beginning(), fetch_config).await?;
     /// for batch in response.records.batches {
-    ///     for record in batch.records {
+    ///     for record in batch.into_records() {
     ///         if let Some(record) = record.value.inner_value() {
     ///             let string = String::from_utf8(record)
     ///                 .expect("record should be a string");
@@ -181,7 +192,11 @@ impl PartitionConsumer {
 
         debug!("received fetch logs for {}", &replica);
 
-        if let Some(partition_response) = response.find_partition(&self.topic, self.partition) {
+        if let Some(mut partition_response) = response.find_partition(&self.topic, self.partition) {
+            if partition_response.error_code.is_error() {
+                return Err(partition_response.error_code.into());
+            }
+            filter_records(&option.filters, &mut partition_response.records);
             debug!(
                 "found partition response with: {} batches: {} bytes",
                 partition_response.records.batches.len(),
@@ -254,6 +269,14 @@ impl PartitionConsumer {
     /// stream using an [`Offset`] and a [`ConsumerConfig`], and periodically
     /// receive events, either individually or in batches.
     ///
+    /// The stream follows the leader of the partition. If the connection to the
+    /// leader is lost or leadership moves to another SPU, the stream reconnects
+    /// and resumes after the last delivered record. An error is yielded and the
+    /// stream ends only if the leader can't be reached after several attempts.
+    ///
+    /// The stream never ends on its own, unless an end is set in the config
+    /// with [`with_end`].
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -280,6 +303,7 @@ impl PartitionConsumer {
     ///
     /// [`Offset`]: struct.Offset.html
     /// [`ConsumerConfig`]: struct.ConsumerConfig.html
+    /// [`with_end`]: struct.ConsumerConfig.html#method.with_end
     pub async fn stream_with_config(
         &self,
         offset: Offset,
@@ -288,8 +312,9 @@ impl PartitionConsumer {
         use futures_util::future::{Either, err};
         use futures_util::stream::{StreamExt, once, iter};
 
+        let partition = self.partition;
         let stream = self._stream_batches_with_config(offset, config).await?;
-        let flattened = stream.flat_map(|batch_result| {
+        let flattened = stream.flat_map(move |batch_result| {
             let batch = match batch_result {
                 Ok(batch) => batch,
                 Err(e) => return Either::Right(once(err(e))),
@@ -300,18 +325,17 @@ impl PartitionConsumer {
                 .records
                 .batches
                 .into_iter()
-                .flat_map(|batch| {
+                .flat_map(move |mut batch| {
                     let base_offset = batch.base_offset;
-                    batch
-                        .records
-                        .into_iter()
-                        .enumerate()
-                        .map(move |(relative, record)| {
-                            Ok(Record {
-                                offset: base_offset + relative as i64,
-                                record,
-                            })
+                    let records = std::mem::take(batch.records_mut());
+                    records.into_iter().map(move |record| {
+                        Ok(Record {
+                            partition,
+                            offset: base_offset + record.get_offset_delta(),
+                            timestamp: batch.get_record_timestamp(&record),
+                            record,
                         })
+                    })
                 });
             Either::Left(iter(records))
         });
@@ -340,23 +364,400 @@ impl PartitionConsumer {
         let offset = offset
             .to_absolute(&mut serial_socket, &self.topic, self.partition)
             .await?;
+        let (end_offset, max_records) = match config.end {
+            Some(StreamEnd::Offset(end)) => (Some(end), None),
+            Some(StreamEnd::HighWatermark) => {
+                let end = Offset::end()
+                    .to_absolute(&mut serial_socket, &self.topic, self.partition)
+                    .await?;
+                (Some(end), None)
+            }
+            Some(StreamEnd::Records(count)) => (None, Some(count)),
+            None => (None, None),
+        };
         drop(serial_socket);
 
-        let stream_request = DefaultStreamFetchRequest {
-            topic: self.topic.to_owned(),
-            partition: self.partition,
-            fetch_offset: offset,
-            isolation: config.isolation,
+        let credit = config.credit();
+        let mut stream = PartitionStream {
+            pool: self.pool.clone(),
+            replica,
             max_bytes: config.max_bytes,
+            isolation: config.isolation,
+            filters: config.filters,
+            smart_stream: config.smart_stream,
+            position: StreamPosition {
+                next_offset: offset,
+                end_offset,
+                remaining_records: max_records,
+                end_of_stream: false,
+            },
+            queue_size: config.queue_size.max(credit.unwrap_or(0) as usize),
+            credit,
+            stream_id: 0,
+            pending_credit: 0,
+            leader: None,
+            stream: None,
+            attempts: 0,
+        };
+        if !stream.end_reached() {
+            stream.connect().await?;
+        }
+
+        // stream ends after error which can't be recovered by reconnecting
+        // or when its end is reached
+        Ok(Box::pin(unfold(Some(stream), |stream| async move {
+            let mut stream = stream?;
+            if stream.end_reached() {
+                debug!("stream fetch replica: {} reached end", stream.replica);
+                return None;
+            }
+            match stream.next_response().await {
+                Ok(response) => Some((Ok(response), Some(stream))),
+                Err(err) => Some((Err(err), None)),
+            }
+        })))
+    }
+}
+
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(100);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(5);
+const MAX_RECONNECT_ATTEMPTS: u32 = 10;
+
+/// ids of flow controlled streams, unique in this process
+static NEXT_STREAM_ID: AtomicI32 = AtomicI32::new(1);
+
+/// Stream fetch which follows leader of the partition
+///
+/// Keeps track of offset of the next record to be delivered. Whenever connection
+/// to leader is lost, leader reports an error or leader of partition changes,
+/// stream fetch is issued again to current leader starting at that offset.
+/// Records which were already delivered are dropped from responses.
+///
+/// With flow control, SPU sends responses only while stream has credit.
+/// Credit of delivered responses is granted back once half of it has been used.
+struct PartitionStream {
+    pool: Arc<SpuPool>,
+    replica: ReplicaKey,
+    max_bytes: i32,
+    isolation: Isolation,
+    filters: Vec<RecordFilter>,
+    smart_stream: Option<SmartStreamPayload>,
+    position: StreamPosition,
+    queue_size: usize,
+    /// responses SPU can send ahead of consumer, without flow control if none
+    credit: Option<i32>,
+    stream_id: i32,
+    /// credit of delivered responses not yet granted back
+    pending_credit: i32,
+    leader: Option<SpuId>,
+    stream: Option<AsyncResponse<DefaultStreamFetchRequest>>,
+    /// reconnect attempts since last response
+    attempts: u32,
+}
+
+impl PartitionStream {
+    /// issue stream fetch to current leader from next offset
+    async fn connect(&mut self) -> Result<(), FluvioError> {
+        let partition = self
+            .pool
+            .metadata()
+            .partitions()
+            .lookup_by_key(&self.replica)
+            .await?;
+        let leader = partition.spec.leader;
+
+        let flow_control = self.credit.map(|credit| {
+            self.stream_id = NEXT_STREAM_ID.fetch_add(1, Ordering::SeqCst);
+            self.pending_credit = 0;
+            StreamFetchFlowControl {
+                stream_id: self.stream_id,
+                credit,
+            }
+        });
+        let stream_request = DefaultStreamFetchRequest {
+            topic: self.replica.topic.clone(),
+            partition: self.replica.partition,
+            fetch_offset: self.position.next_offset,
+            isolation: self.isolation.clone(),
+            max_bytes: self.max_bytes,
+            filters: self.filters.clone(),
+            smart_stream: self.smart_stream.clone(),
+            end_offset: self.position.end_offset,
+            max_records: self.position.remaining_records,
+            flow_control,
             ..Default::default()
         };
+        debug!(
+            "stream fetch replica: {} from leader: {}, offset: {}",
+            self.replica, leader, self.position.next_offset
+        );
+        match self
+            .pool
+            .create_stream(&self.replica, stream_request, self.queue_size)
+            .await
+        {
+            Ok(stream) => {
+                self.leader = Some(leader);
+                self.stream = Some(stream);
+                Ok(())
+            }
+            Err(err) => {
+                self.pool.remove_spu_socket(leader).await;
+                Err(err)
+            }
+        }
+    }
+
+    /// reconnect with exponential backoff, gives up after max attempts
+    async fn reconnect(&mut self) -> Result<(), FluvioError> {
+        use fluvio_future::timer::sleep;
 
+        loop {
+            let delay = RECONNECT_BASE_DELAY
+                .checked_mul(1 << self.attempts.min(16))
+                .map_or(RECONNECT_MAX_DELAY, |delay| delay.min(RECONNECT_MAX_DELAY));
+            sleep(delay).await;
+            self.attempts += 1;
+
+            match self.connect().await {
+                Ok(()) => return Ok(()),
+                Err(err) => self.check_attempts(err)?,
+            }
+        }
+    }
+
+    fn check_attempts(&self, err: FluvioError) -> Result<(), FluvioError> {
+        if self.attempts >= MAX_RECONNECT_ATTEMPTS {
+            debug!(
+                "giving up stream fetch replica: {} after {} attempts",
+                self.replica, self.attempts
+            );
+            Err(err)
+        } else {
+            debug!(
+                "stream fetch replica: {} failed: {}, reconnecting",
+                self.replica, err
+            );
+            Ok(())
+        }
+    }
+
+    /// drop stream to current leader, connection is removed from pool when
+    /// it can't be used anymore
+    async fn disconnect(&mut self, remove_connection: bool) {
+        self.stream = None;
+        if let Some(leader) = self.leader.take() {
+            if remove_connection {
+                self.pool.remove_spu_socket(leader).await;
+            }
+        }
+    }
+
+    /// grant credit of delivered responses back to SPU, once half of credit is used
+    async fn grant_credit(&mut self) {
+        let (credit, leader) = match (self.credit, self.leader) {
+            (Some(credit), Some(leader)) => (credit, leader),
+            _ => return,
+        };
+        if self.pending_credit < (credit + 1) / 2 {
+            return;
+        }
+
+        let request = StreamFetchCreditRequest {
+            stream_id: self.stream_id,
+            credit: self.pending_credit,
+        };
+        let result = match self.pool.create_serial_socket_to_spu(leader).await {
+            Ok(mut socket) => socket.send_receive(request).await.map_err(|err| err.into()),
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(response) if response.error_code.is_error() => {
+                debug!(
+                    "stream: {} of replica: {} not found, credit is not granted: {}",
+                    self.stream_id,
+                    self.replica,
+                    response.error_code.to_sentence()
+                );
+            }
+            Ok(_) => self.pending_credit = 0,
+            // credit stays pending, stream reconnects if connection is lost
+            Err(err) => debug!(
+                "granting credit to stream of replica: {} failed: {}",
+                self.replica, err
+            ),
+        }
+    }
+
+    fn end_reached(&self) -> bool {
+        self.position.end_reached()
+    }
+
+    async fn leader_changed(&self) -> bool {
+        match self
+            .pool
+            .metadata()
+            .partitions()
+            .try_lookup_by_key(&self.replica)
+            .await
+        {
+            Some(partition) => Some(partition.spec.leader) != self.leader,
+            None => false,
+        }
+    }
+
+    /// wait for next response with records not yet delivered
+    async fn next_response(&mut self) -> Result<DefaultStreamFetchResponse, FluvioError> {
+        use tokio::select;
         use futures_util::StreamExt;
-        let stream = self.pool.create_stream(&replica, stream_request).await?;
-        Ok(stream.map(|item| item.map_err(|e| e.into())))
+
+        enum StreamEvent<T> {
+            Response(Option<T>),
+            MetadataChanged,
+        }
+
+        self.grant_credit().await;
+
+        loop {
+            let stream = match self.stream.as_mut() {
+                Some(stream) => stream,
+                None => {
+                    self.reconnect().await?;
+                    continue;
+                }
+            };
+
+            let metadata_listener = self.pool.metadata().partitions().listen();
+            let event = select! {
+                response = stream.next() => StreamEvent::Response(response),
+                _ = metadata_listener => StreamEvent::MetadataChanged,
+            };
+
+            match event {
+                StreamEvent::Response(Some(Ok(mut response))) => {
+                    let error_code = response.partition.error_code;
+                    if error_code.is_error() {
+                        self.disconnect(false).await;
+                        if !error_code.is_retriable() {
+                            return Err(error_code.into());
+                        }
+                        self.check_attempts(error_code.into())?;
+                        continue;
+                    }
+                    self.attempts = 0;
+                    if self.credit.is_some() {
+                        self.pending_credit += 1;
+                    }
+                    self.position.advance(&mut response);
+                    update_accumulator(&mut self.smart_stream, &response.partition.records);
+                    return Ok(response);
+                }
+                StreamEvent::Response(Some(Err(err))) => {
+                    self.disconnect(true).await;
+                    self.check_attempts(err.into())?;
+                }
+                StreamEvent::Response(None) => {
+                    debug!("stream fetch replica: {} terminated", self.replica);
+                    self.disconnect(true).await;
+                }
+                StreamEvent::MetadataChanged => {
+                    if self.leader_changed().await {
+                        debug!("leader of replica: {} has changed", self.replica);
+                        self.disconnect(false).await;
+                    }
+                }
+            }
+        }
     }
 }
 
+/// Position of stream in partition and bounds where it ends
+#[derive(Debug)]
+struct StreamPosition {
+    next_offset: i64,
+    /// offset where stream ends
+    end_offset: Option<i64>,
+    /// records to be delivered before stream ends
+    remaining_records: Option<u32>,
+    /// SPU reported that bound of stream is reached
+    end_of_stream: bool,
+}
+
+impl StreamPosition {
+    fn end_reached(&self) -> bool {
+        self.end_of_stream
+            || matches!(self.end_offset, Some(end) if self.next_offset >= end)
+            || self.remaining_records == Some(0)
+    }
+
+    /// drop records of response which were already delivered or are after end,
+    /// and move to offset after records of response
+    fn advance(&mut self, response: &mut DefaultStreamFetchResponse) {
+        let records = &mut response.partition.records;
+        if let Some(offset) = skip_delivered(records, self.next_offset) {
+            self.next_offset = offset;
+        }
+        // file slices sent by SPU may contain records after end
+        let count = bound_records(records, self.end_offset, self.remaining_records);
+        if let Some(remaining) = self.remaining_records.as_mut() {
+            *remaining -= count;
+        }
+        if let Some(batch) = records.batches.last() {
+            self.next_offset = self.next_offset.min(batch.get_last_offset() + 1);
+        }
+        // records up to where SPU continues were scanned, even if all were filtered out
+        if self.remaining_records != Some(0) {
+            self.next_offset = self.next_offset.max(response.next_offset);
+        }
+        if response.end_of_stream {
+            self.end_of_stream = true;
+        }
+    }
+}
+
+/// accumulator of aggregate is value of its last delivered record,
+/// so stream issued again after reconnect continues from it
+fn update_accumulator(smart_stream: &mut Option<SmartStreamPayload>, records: &RecordSet) {
+    let smart_stream = match smart_stream {
+        Some(smart_stream) if smart_stream.kind == SmartStreamKind::Aggregate => smart_stream,
+        _ => return,
+    };
+    let last_value = records
+        .batches
+        .iter()
+        .rev()
+        .find_map(|batch| batch.records().last())
+        .map(|record| record.value.inner_value_ref().clone().unwrap_or_default());
+    if let Some(value) = last_value {
+        smart_stream.accumulator = value;
+    }
+}
+
+/// drop records before `next_offset` which were already delivered,
+/// returns offset after last record
+fn skip_delivered(records: &mut RecordSet, next_offset: i64) -> Option<i64> {
+    records
+        .batches
+        .retain(|batch| batch.get_last_offset() >= next_offset);
+    for batch in records.batches.iter_mut() {
+        let delivered = next_offset - batch.get_base_offset();
+        if delivered > 0 {
+            // records may be sparse if they were filtered
+            batch.retain_records(|record| record.get_offset_delta() >= delivered);
+            for record in batch.records_mut().iter_mut() {
+                let offset_delta = record.get_offset_delta() - delivered;
+                record.preamble.set_offset_delta(offset_delta);
+            }
+            batch.set_base_offset(next_offset);
+            batch.set_offset_delta(batch.get_header().last_offset_delta - delivered as i32);
+        }
+    }
+    records
+        .batches
+        .last()
+        .map(|batch| batch.get_last_offset() + 1)
+}
+
 /// compute total bytes in record set
 fn bytes_count(records: &RecordSet) -> usize {
     records
@@ -364,7 +765,7 @@ fn bytes_count(records: &RecordSet) -> usize {
         .iter()
         .map(|batch| {
             batch
-                .records
+                .records()
                 .iter()
                 .map(|record| record.value.len())
                 .sum::<usize>()
@@ -373,12 +774,18 @@ fn bytes_count(records: &RecordSet) -> usize {
 }
 
 const MAX_FETCH_BYTES: i32 = 1000000;
+const DEFAULT_STREAM_QUEUE_SIZE: usize = 10;
 
 /// Configures the behavior of consumer fetching and streaming
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ConsumerConfig {
     pub(crate) max_bytes: i32,
     pub(crate) isolation: Isolation,
+    pub(crate) filters: Vec<RecordFilter>,
+    pub(crate) smart_stream: Option<SmartStreamPayload>,
+    pub(crate) end: Option<StreamEnd>,
+    pub(crate) queue_size: usize,
+    pub(crate) max_bytes_in_flight: Option<i32>,
 }
 
 impl Default for ConsumerConfig {
@@ -386,6 +793,11 @@ impl Default for ConsumerConfig {
         Self {
             max_bytes: MAX_FETCH_BYTES,
             isolation: Isolation::default(),
+            filters: vec![],
+            smart_stream: None,
+            end: None,
+            queue_size: DEFAULT_STREAM_QUEUE_SIZE,
+            max_bytes_in_flight: None,
         }
     }
 }
@@ -396,19 +808,294 @@ impl ConsumerConfig {
         self.max_bytes = max_bytes;
         self
     }
+
+    /// Only consume records which match the filter
+    ///
+    /// When more filters are added, records matching any of them are consumed.
+    /// Streams are filtered by the SPU, so records which don't match are never
+    /// sent to the consumer.
+    ///
+    /// ```
+    /// # use fluvio::{ConsumerConfig, RecordFilter};
+    /// let config = ConsumerConfig::default()
+    ///     .with_filter(RecordFilter::key_prefix("user-"))
+    ///     .with_filter(RecordFilter::json_field_equals("status", "failed"));
+    /// ```
+    pub fn with_filter(mut self, filter: RecordFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Run WebAssembly module over streamed records in the SPU
+    ///
+    /// Module is applied after filters, and only to records of streams,
+    /// not to records of a single fetch. See [`SmartStreamPayload`] for functions
+    /// the module has to export.
+    ///
+    /// ```no_run
+    /// # use fluvio::{ConsumerConfig, SmartStreamPayload};
+    /// let wasm = std::fs::read("filter.wasm").expect("module");
+    /// let config = ConsumerConfig::default()
+    ///     .with_smart_stream(SmartStreamPayload::filter(wasm));
+    /// ```
+    ///
+    /// [`SmartStreamPayload`]: struct.SmartStreamPayload.html
+    pub fn with_smart_stream(mut self, smart_stream: SmartStreamPayload) -> Self {
+        self.smart_stream = Some(smart_stream);
+        self
+    }
+
+    /// End stream once the bound is reached, instead of waiting for new records
+    ///
+    /// ```
+    /// # use fluvio::{ConsumerConfig, StreamEnd};
+    /// // stream records which exist when stream is started
+    /// let config = ConsumerConfig::default()
+    ///     .with_end(StreamEnd::HighWatermark);
+    /// ```
+    pub fn with_end(mut self, end: StreamEnd) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// Number of stream responses which are buffered before they are consumed
+    pub fn with_queue_size(mut self, queue_size: usize) -> Self {
+        self.queue_size = queue_size.max(1);
+        self
+    }
+
+    /// Limit bytes which SPU sends ahead of records consumed from stream
+    ///
+    /// Without the limit, SPU sends records as soon as they are committed,
+    /// however fast they are consumed. With the limit, SPU sends up to
+    /// `max_bytes_in_flight / max_bytes` responses, but at least one,
+    /// and waits until the consumer has processed them.
+    ///
+    /// ```
+    /// # use fluvio::ConsumerConfig;
+    /// let config = ConsumerConfig::default()
+    ///     .with_max_bytes(100_000)
+    ///     .with_max_bytes_in_flight(1_000_000);
+    /// ```
+    pub fn with_max_bytes_in_flight(mut self, max_bytes_in_flight: i32) -> Self {
+        self.max_bytes_in_flight = Some(max_bytes_in_flight);
+        self
+    }
+
+    /// responses SPU can send ahead of consumer
+    fn credit(&self) -> Option<i32> {
+        self.max_bytes_in_flight
+            .map(|in_flight| (in_flight / self.max_bytes.max(1)).max(1))
+    }
+}
+
+/// Where a stream of records ends
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEnd {
+    /// end before record at absolute offset
+    Offset(i64),
+    /// end at high watermark of partition when stream is started
+    HighWatermark,
+    /// end after number of records has been delivered
+    Records(u32),
 }
 
 pub struct Record {
+    partition: i32,
     offset: i64,
+    timestamp: Timestamp,
     record: DefaultRecord,
 }
 
 impl Record {
+    /// Partition which the record was consumed from
+    pub fn partition(&self) -> i32 {
+        self.partition
+    }
+
     pub fn offset(&self) -> i64 {
         self.offset
     }
 
+    /// Time of the record in milliseconds since unix epoch
+    ///
+    /// This is the time when the record was produced, or the time when it
+    /// was written by the partition leader if the topic is configured to use
+    /// `LogAppendTime` timestamps.
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Headers which were attached to the record by the producer
+    pub fn headers(&self) -> &[RecordHeader] {
+        self.record.get_headers()
+    }
+
     pub fn try_into_bytes(self) -> Option<Vec<u8>> {
         self.record.value.inner_value()
     }
 }
+
+#[cfg(test)]
+mod test {
+
+    use dataplane::batch::DefaultBatch;
+    use dataplane::record::{DefaultRecord, RecordSet};
+    use fluvio_spu_schema::server::stream_fetch::DefaultStreamFetchResponse;
+    use fluvio_spu_schema::server::smart_stream::SmartStreamPayload;
+
+    use super::skip_delivered;
+    use super::update_accumulator;
+    use super::ConsumerConfig;
+    use super::StreamPosition;
+
+    fn create_batch(base_offset: i64, records: u8) -> DefaultBatch {
+        let mut batch = DefaultBatch::default().base_offset(base_offset);
+        for i in 0..records {
+            batch.add_record(DefaultRecord::from(vec![base_offset as u8 + i]));
+        }
+        batch
+    }
+
+    #[test]
+    fn test_skip_delivered_records() {
+        let mut records = RecordSet::default()
+            .add(create_batch(0, 3))
+            .add(create_batch(3, 3))
+            .add(create_batch(6, 2));
+
+        assert_eq!(skip_delivered(&mut records, 4), Some(8));
+        assert_eq!(records.batches.len(), 2);
+
+        let batch = &records.batches[0];
+        assert_eq!(batch.get_base_offset(), 4);
+        assert_eq!(batch.get_last_offset(), 5);
+        assert_eq!(batch.records().len(), 2);
+        assert_eq!(
+            batch.records()[0].get_value().inner_value_ref(),
+            &Some(vec![4])
+        );
+        assert_eq!(records.batches[1].get_base_offset(), 6);
+
+        // everything was already delivered
+        assert_eq!(skip_delivered(&mut records, 8), None);
+        assert!(records.batches.is_empty());
+    }
+
+    #[test]
+    fn test_skip_delivered_filtered_records() {
+        let mut batch = create_batch(0, 5);
+        batch.retain_records(|record| record.get_offset_delta() % 2 == 0);
+        let mut records = RecordSet::default().add(batch);
+
+        assert_eq!(skip_delivered(&mut records, 1), Some(5));
+
+        let batch = &records.batches[0];
+        assert_eq!(batch.get_base_offset(), 1);
+        let offsets: Vec<i64> = batch
+            .records()
+            .iter()
+            .map(|record| batch.get_base_offset() + record.get_offset_delta())
+            .collect();
+        assert_eq!(offsets, vec![2, 4]);
+    }
+
+    #[test]
+    fn test_bounded_filtered_stream_ends() {
+        let mut position = StreamPosition {
+            next_offset: 0,
+            end_offset: Some(10),
+            remaining_records: None,
+            end_of_stream: false,
+        };
+
+        // last match before end is at offset 3, SPU has scanned up to 6
+        let mut batch = create_batch(0, 6);
+        batch.retain_records(|record| record.get_offset_delta() == 3);
+        let mut response = DefaultStreamFetchResponse::default();
+        response.partition.records = RecordSet::default().add(batch);
+        response.next_offset = 6;
+        position.advance(&mut response);
+        assert_eq!(response.partition.records.batches[0].records().len(), 1);
+        assert_eq!(position.next_offset, 6);
+        assert!(!position.end_reached());
+
+        // no more matches up to end, stream is still resumed after scanned records
+        let mut response = DefaultStreamFetchResponse {
+            next_offset: 8,
+            ..Default::default()
+        };
+        position.advance(&mut response);
+        assert_eq!(position.next_offset, 8);
+        assert!(!position.end_reached());
+
+        // SPU scanned past end and ends stream
+        let mut response = DefaultStreamFetchResponse {
+            next_offset: 12,
+            end_of_stream: true,
+            ..Default::default()
+        };
+        position.advance(&mut response);
+        assert!(position.end_reached());
+    }
+
+    #[test]
+    fn test_end_of_stream_response() {
+        let mut position = StreamPosition {
+            next_offset: 0,
+            end_offset: None,
+            remaining_records: Some(5),
+            end_of_stream: false,
+        };
+
+        let mut response = DefaultStreamFetchResponse {
+            next_offset: 4,
+            ..Default::default()
+        };
+        position.advance(&mut response);
+        assert_eq!(position.next_offset, 4);
+        assert!(!position.end_reached());
+
+        let mut response = DefaultStreamFetchResponse {
+            end_of_stream: true,
+            ..Default::default()
+        };
+        position.advance(&mut response);
+        assert!(position.end_reached());
+    }
+
+    #[test]
+    fn test_update_accumulator() {
+        let mut batch = DefaultBatch::default();
+        batch.add_record(DefaultRecord::from(b"1".to_vec()));
+        batch.add_record(DefaultRecord::from(b"2".to_vec()));
+        let records = RecordSet::default().add(batch);
+
+        // resumed aggregate starts from last delivered value
+        let mut smart_stream = Some(SmartStreamPayload::aggregate(vec![], b"0".to_vec()));
+        update_accumulator(&mut smart_stream, &records);
+        assert_eq!(smart_stream.expect("aggregate").accumulator, b"2".to_vec());
+
+        // no records delivered, accumulator is kept
+        let mut smart_stream = Some(SmartStreamPayload::aggregate(vec![], b"0".to_vec()));
+        update_accumulator(&mut smart_stream, &RecordSet::default());
+        assert_eq!(smart_stream.expect("aggregate").accumulator, b"0".to_vec());
+
+        let mut smart_stream = Some(SmartStreamPayload::map(vec![]));
+        update_accumulator(&mut smart_stream, &records);
+        assert!(smart_stream.expect("map").accumulator.is_empty());
+    }
+
+    #[test]
+    fn test_consumer_config_credit() {
+        assert_eq!(ConsumerConfig::default().credit(), None);
+
+        let config = ConsumerConfig::default()
+            .with_max_bytes(1000)
+            .with_max_bytes_in_flight(4500);
+        assert_eq!(config.credit(), Some(4));
+
+        let config = config.with_max_bytes_in_flight(10);
+        assert_eq!(config.credit(), Some(1));
+    }
+}
@@ -0,0 +1,374 @@
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use async_rwlock::RwLock;
+use async_channel::Sender;
+use futures_util::stream::Stream;
+use futures_util::stream::StreamExt;
+use tracing::{debug, trace};
+
+use fluvio_spu_schema::server::consumer_group::*;
+use dataplane::ErrorCode;
+use dataplane::ReplicaKey;
+use fluvio_types::defaults::CONSUMER_OFFSETS_TOPIC;
+
+use crate::FluvioError;
+use crate::client::SerialFrame;
+use crate::consumer::{PartitionConsumer, ConsumerConfig, Record};
+use crate::offset::Offset;
+use crate::spu::SpuPool;
+
+const DEFAULT_SESSION_TIMEOUT: Duration = Duration::from_secs(10);
+const RECORD_CHANNEL_SIZE: usize = 100;
+
+/// current membership of the group consumer
+#[derive(Debug, Clone)]
+struct Membership {
+    member_id: String,
+    generation: i32,
+}
+
+/// An interface for consuming a topic as a member of a consumer group
+///
+/// Partitions of the topic are shared by all members of the group. Each
+/// partition is assigned to exactly one member, and partitions are re-assigned
+/// whenever members join or leave the group. Members stop consuming their
+/// partitions before any partition is re-assigned. A member which stops sending
+/// heartbeats within the session timeout is removed from the group.
+///
+/// Members commit offsets of records they have processed. When a partition
+/// is assigned to a member, it resumes from the committed offset of the group.
+///
+/// The group is coordinated by the leader of its partition of the internal
+/// consumer offsets topic, where committed offsets are stored and replicated.
+///
+/// # Example
+///
+/// ```no_run
+/// # use fluvio::{Fluvio, Offset, FluvioError};
+/// # mod futures {
+/// #     pub use futures_util::stream::StreamExt;
+/// # }
+/// # async fn do_consume_group(fluvio: &Fluvio) -> Result<(), FluvioError> {
+/// use futures::StreamExt;
+/// let consumer = fluvio.group_consumer("my-group", "my-topic").await?;
+/// let mut stream = consumer.stream(Offset::beginning()).await?;
+/// while let Some(Ok(record)) = stream.next().await {
+///     println!("partition: {}, offset: {}", record.partition(), record.offset());
+///     consumer.commit(&record).await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct GroupConsumer {
+    group: String,
+    topic: String,
+    pool: Arc<SpuPool>,
+    session_timeout: Duration,
+    membership: Arc<RwLock<Option<Membership>>>,
+}
+
+impl GroupConsumer {
+    pub(crate) fn new(group: String, topic: String, pool: Arc<SpuPool>) -> Self {
+        Self {
+            group,
+            topic,
+            pool,
+            session_timeout: DEFAULT_SESSION_TIMEOUT,
+            membership: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Set how long group keeps this member without receiving heartbeat
+    ///
+    /// Heartbeats are sent at a third of the session timeout.
+    pub fn with_session_timeout(mut self, session_timeout: Duration) -> Self {
+        self.session_timeout = session_timeout;
+        self
+    }
+
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Joins the group and continuously streams records of assigned partitions
+    ///
+    /// Each assigned partition starts from the offset committed by the group.
+    /// Partitions without committed offset start at the given `offset`.
+    /// Whenever partitions are re-assigned, streams are restarted from the
+    /// committed offsets. Records which were received but not committed may
+    /// be delivered again to the member that is assigned the partition.
+    ///
+    /// Member leaves the group when returned stream is dropped.
+    pub async fn stream(
+        &self,
+        offset: Offset,
+    ) -> Result<impl Stream<Item = Result<Record, FluvioError>>, FluvioError> {
+        let context = GroupContext {
+            group: self.group.clone(),
+            topic: self.topic.clone(),
+            pool: self.pool.clone(),
+            session_timeout: self.session_timeout,
+            membership: self.membership.clone(),
+        };
+        let assignment = context.join().await?;
+
+        let (sender, receiver) = async_channel::bounded(RECORD_CHANNEL_SIZE);
+        fluvio_future::task::spawn(context.member_loop(assignment, offset, sender));
+        Ok(receiver)
+    }
+
+    /// Commits offset of the record, so group resumes after this record
+    pub async fn commit(&self, record: &Record) -> Result<(), FluvioError> {
+        self.commit_offset(record.partition(), record.offset() + 1)
+            .await
+    }
+
+    /// Commits offset of next record to be consumed from the partition
+    pub async fn commit_offset(&self, partition: i32, offset: i64) -> Result<(), FluvioError> {
+        let membership = match self.membership.read().await.clone() {
+            Some(membership) => membership,
+            None => return Err(ErrorCode::UnknownMemberId.into()),
+        };
+        let request = OffsetCommitRequest {
+            group: self.group.clone(),
+            member_id: membership.member_id,
+            generation: membership.generation,
+            topic: self.topic.clone(),
+            partitions: vec![PartitionOffset { partition, offset }],
+        };
+        let mut coordinator = coordinator_socket(&self.pool, &self.group).await?;
+        let response = coordinator.send_receive(request).await?;
+        check_error(response.error_code)
+    }
+
+    /// Offsets committed by the group, by partition
+    pub async fn committed_offsets(&self) -> Result<Vec<(i32, i64)>, FluvioError> {
+        let response = fetch_committed(&self.pool, &self.group, &self.topic).await?;
+        Ok(response
+            .partitions
+            .iter()
+            .map(|partition| (partition.partition, partition.offset))
+            .collect())
+    }
+}
+
+/// socket to leader of consumer offsets partition which coordinates the group
+async fn coordinator_socket(pool: &SpuPool, group: &str) -> Result<impl SerialFrame, FluvioError> {
+    let partition = coordinator_partition(group);
+    pool.create_serial_socket(&ReplicaKey::new(CONSUMER_OFFSETS_TOPIC, partition))
+        .await
+}
+
+async fn fetch_committed(
+    pool: &SpuPool,
+    group: &str,
+    topic: &str,
+) -> Result<OffsetFetchResponse, FluvioError> {
+    let request = OffsetFetchRequest {
+        group: group.to_owned(),
+        topic: topic.to_owned(),
+    };
+    let mut coordinator = coordinator_socket(pool, group).await?;
+    let response = coordinator.send_receive(request).await?;
+    check_error(response.error_code)?;
+    Ok(response)
+}
+
+fn check_error(error_code: ErrorCode) -> Result<(), FluvioError> {
+    if error_code.is_error() {
+        Err(error_code.into())
+    } else {
+        Ok(())
+    }
+}
+
+/// state used by background member loop
+struct GroupContext {
+    group: String,
+    topic: String,
+    pool: Arc<SpuPool>,
+    session_timeout: Duration,
+    membership: Arc<RwLock<Option<Membership>>>,
+}
+
+impl GroupContext {
+    /// join group, existing member id is reused
+    async fn join(&self) -> Result<GroupAssignment, FluvioError> {
+        let member_id = match self.membership.read().await.as_ref() {
+            Some(membership) => membership.member_id.clone(),
+            None => String::new(),
+        };
+        let partitions = self
+            .pool
+            .metadata()
+            .topics()
+            .topic_partition_count(&self.topic)
+            .await?;
+        let request = JoinGroupRequest {
+            group: self.group.clone(),
+            topic: self.topic.clone(),
+            partitions,
+            member_id,
+            session_timeout_ms: self.session_timeout.as_millis() as u32,
+        };
+        let mut coordinator = coordinator_socket(&self.pool, &self.group).await?;
+        let response = coordinator.send_receive(request).await?;
+        check_error(response.error_code)?;
+
+        debug!(
+            "joined group: {} as: {}, assignment: {:?}",
+            self.group, response.member_id, response.assignment
+        );
+        self.set_membership(response.member_id, &response.assignment)
+            .await;
+        Ok(response.assignment)
+    }
+
+    async fn set_membership(&self, member_id: String, assignment: &GroupAssignment) {
+        *self.membership.write().await = Some(Membership {
+            member_id,
+            generation: assignment.generation,
+        });
+    }
+
+    /// send heartbeat, re-join if group no longer knows this member
+    async fn heartbeat(&self) -> Result<GroupAssignment, FluvioError> {
+        let membership = self.membership.read().await.clone();
+        let (member_id, generation) = match membership {
+            Some(membership) => (membership.member_id, membership.generation),
+            None => return self.join().await,
+        };
+        let request = HeartbeatRequest {
+            group: self.group.clone(),
+            member_id: member_id.clone(),
+            generation,
+        };
+        let mut coordinator = coordinator_socket(&self.pool, &self.group).await?;
+        let response = coordinator.send_receive(request).await?;
+        if response.error_code == ErrorCode::UnknownMemberId {
+            debug!("member: {} is no longer in group, re-joining", member_id);
+            *self.membership.write().await = None;
+            return self.join().await;
+        }
+        check_error(response.error_code)?;
+        self.set_membership(member_id, &response.assignment).await;
+        Ok(response.assignment)
+    }
+
+    async fn leave(&self) {
+        let membership = self.membership.write().await.take();
+        if let Some(membership) = membership {
+            let request = LeaveGroupRequest {
+                group: self.group.clone(),
+                member_id: membership.member_id,
+            };
+            match coordinator_socket(&self.pool, &self.group).await {
+                Ok(mut coordinator) => {
+                    if let Err(err) = coordinator.send_receive(request).await {
+                        debug!("error leaving group: {}", err);
+                    }
+                }
+                Err(err) => debug!("error leaving group: {}", err),
+            }
+        }
+    }
+
+    /// stream records of assigned partitions starting from committed offsets
+    async fn partition_streams(
+        &self,
+        assignment: &GroupAssignment,
+        offset: &Offset,
+    ) -> Result<Vec<impl Stream<Item = Result<Record, FluvioError>>>, FluvioError> {
+        let committed = fetch_committed(&self.pool, &self.group, &self.topic).await?;
+        let mut streams = vec![];
+        for partition in &assignment.partitions {
+            let start = match committed.find_partition(*partition) {
+                Some(committed_offset) => Offset::absolute(committed_offset)?,
+                None => offset.clone(),
+            };
+            debug!("streaming partition: {} from: {:?}", partition, start);
+            let consumer =
+                PartitionConsumer::new(self.topic.clone(), *partition, self.pool.clone());
+            let stream = consumer
+                .stream_with_config(start, ConsumerConfig::default())
+                .await?;
+            streams.push(stream);
+        }
+        Ok(streams)
+    }
+
+    /// forward records to member until receiver is dropped, sending heartbeats
+    /// and restarting partition streams whenever assignment changes
+    async fn member_loop(
+        self,
+        mut assignment: GroupAssignment,
+        offset: Offset,
+        sender: Sender<Result<Record, FluvioError>>,
+    ) {
+        use tokio::select;
+        use fluvio_future::timer::sleep;
+        use futures_util::stream::select_all;
+
+        let heartbeat_interval = self.session_timeout / 3;
+
+        'assignment: loop {
+            let mut records = match self.partition_streams(&assignment, &offset).await {
+                Ok(streams) => select_all(streams.into_iter().map(|stream| stream.boxed())),
+                Err(err) => {
+                    let _ = sender.send(Err(err)).await;
+                    break;
+                }
+            };
+            let mut has_records = !assignment.partitions.is_empty();
+            // partitions were revoked, confirm generation right away
+            let mut next_heartbeat = if assignment.rebalancing {
+                Instant::now()
+            } else {
+                Instant::now() + heartbeat_interval
+            };
+
+            loop {
+                let wait = next_heartbeat.saturating_duration_since(Instant::now());
+                select! {
+                    record = records.next(), if has_records => match record {
+                        Some(record) => {
+                            if sender.send(record).await.is_err() {
+                                break 'assignment;
+                            }
+                        }
+                        None => has_records = false,
+                    },
+                    _ = sleep(wait) => {
+                        if sender.is_closed() {
+                            break 'assignment;
+                        }
+                        next_heartbeat = Instant::now() + heartbeat_interval;
+                        match self.heartbeat().await {
+                            Ok(new_assignment) => {
+                                if new_assignment != assignment {
+                                    debug!("group: {} re-assigned: {:?}", self.group, new_assignment);
+                                    assignment = new_assignment;
+                                    continue 'assignment;
+                                }
+                                trace!("heartbeat, generation: {}", assignment.generation);
+                            }
+                            Err(err) => {
+                                let _ = sender.send(Err(err)).await;
+                                break 'assignment;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        self.leave().await;
+        debug!("member loop of group: {} terminated", self.group);
+    }
+}
@@ -1,6 +1,8 @@
 use std::io::Error as IoError;
+use std::io::ErrorKind;
 use thiserror::Error;
 
+use dataplane::ErrorCode;
 use fluvio_socket::FlvSocketError;
 use fluvio_sc_schema::ApiError;
 use crate::config::ConfigError;
@@ -23,9 +25,11 @@ pub enum FluvioError {
         source: FlvSocketError,
     },
     #[error("Fluvio SC schema error")]
-    ApiError {
-        #[from]
-        source: ApiError,
+    ApiError { source: ApiError },
+    #[error("{}", server_error_message(.error_code, .message))]
+    ServerError {
+        error_code: ErrorCode,
+        message: Option<String>,
     },
     #[error("Fluvio config error")]
     ConfigError {
@@ -37,3 +41,101 @@ pub enum FluvioError {
     #[error("Unknown error: {0}")]
     Other(String),
 }
+
+fn server_error_message(error_code: &ErrorCode, message: &Option<String>) -> String {
+    match message {
+        Some(message) => format!("Server error: {:?}, {}", error_code, message),
+        None => format!("Server error: {:?}", error_code),
+    }
+}
+
+impl FluvioError {
+    /// Error code returned by SC or SPU, if this error was reported by server
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        match self {
+            Self::ServerError { error_code, .. } => Some(*error_code),
+            _ => None,
+        }
+    }
+
+    /// Check if operation may succeed when it is tried again
+    ///
+    /// This is the case for lost connections and for server errors which are
+    /// resolved over time, such as a partition leader moving to another SPU.
+    pub fn is_retriable(&self) -> bool {
+        match self {
+            Self::ServerError { error_code, .. } => error_code.is_retriable(),
+            Self::IoError { source } => is_retriable_io(source),
+            Self::FlvSocketError {
+                source: FlvSocketError::IoError { source },
+            } => is_retriable_io(source),
+            _ => false,
+        }
+    }
+}
+
+/// io errors caused by lost or timed out connection
+fn is_retriable_io(err: &IoError) -> bool {
+    matches!(
+        err.kind(),
+        ErrorKind::TimedOut
+            | ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::BrokenPipe
+            | ErrorKind::UnexpectedEof
+            | ErrorKind::Interrupted
+    )
+}
+
+impl From<ErrorCode> for FluvioError {
+    fn from(error_code: ErrorCode) -> Self {
+        Self::ServerError {
+            error_code,
+            message: None,
+        }
+    }
+}
+
+impl From<ApiError> for FluvioError {
+    fn from(error: ApiError) -> Self {
+        match error {
+            ApiError::Code(error_code, message) => Self::ServerError {
+                error_code,
+                message,
+            },
+            source => Self::ApiError { source },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::io::Error as IoError;
+    use std::io::ErrorKind;
+
+    use dataplane::ErrorCode;
+    use fluvio_sc_schema::ApiError;
+
+    use super::FluvioError;
+
+    #[test]
+    fn test_server_errors_are_typed() {
+        let err: FluvioError = ApiError::Code(ErrorCode::TopicAlreadyExists, None).into();
+        assert_eq!(err.error_code(), Some(ErrorCode::TopicAlreadyExists));
+        assert!(!err.is_retriable());
+        assert_eq!(err.to_string(), "Server error: TopicAlreadyExists");
+
+        let err: FluvioError = ErrorCode::NotLeaderForPartition.into();
+        assert!(err.is_retriable());
+
+        let err: FluvioError = IoError::new(ErrorKind::BrokenPipe, "closed").into();
+        assert!(err.is_retriable());
+        assert_eq!(err.error_code(), None);
+
+        let err: FluvioError = IoError::new(ErrorKind::InvalidData, "bad").into();
+        assert!(!err.is_retriable());
+    }
+}
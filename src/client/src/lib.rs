@@ -87,6 +87,8 @@ mod client;
 mod admin;
 mod params;
 mod consumer;
+mod consumer_group;
+mod topic_consumer;
 mod producer;
 mod offset;
 mod sync;
@@ -96,9 +98,16 @@ pub mod config;
 
 pub use error::FluvioError;
 pub use config::FluvioConfig;
-pub use producer::TopicProducer;
-pub use consumer::{PartitionConsumer, ConsumerConfig};
+pub use producer::{TopicProducer, ProducerConfig, RecordDelivery};
+pub use producer::{Partitioner, PartitionerConfig, DefaultPartitioner};
+pub use consumer::{PartitionConsumer, ConsumerConfig, StreamEnd, Record};
+pub use consumer_group::GroupConsumer;
+pub use topic_consumer::{TopicConsumer, TopicOffsets};
 pub use offset::Offset;
+pub use dataplane::compression::Compression;
+pub use dataplane::record::RecordHeader;
+pub use fluvio_spu_schema::server::record_filter::RecordFilter;
+pub use fluvio_spu_schema::server::smart_stream::{SmartStreamPayload, SmartStreamKind};
 
 pub use crate::admin::FluvioAdmin;
 pub use crate::client::Fluvio;
@@ -3,6 +3,7 @@ use std::io::ErrorKind;
 
 use tracing::{debug, trace};
 use dataplane::ReplicaKey;
+use dataplane::Timestamp;
 use fluvio_spu_schema::server::fetch_offset::FetchOffsetsRequest;
 use fluvio_spu_schema::server::fetch_offset::FetchOffsetPartitionResponse;
 
@@ -14,6 +15,7 @@ pub(crate) enum OffsetInner {
     Absolute(i64),
     FromBeginning(i64),
     FromEnd(i64),
+    FromTimestamp(Timestamp),
 }
 
 /// Describes the location of an event stored in a Fluvio partition
@@ -255,6 +257,32 @@ impl Offset {
         }
     }
 
+    /// Creates an offset pointing to the first event at or after a point in time
+    ///
+    /// The timestamp is in milliseconds since the UNIX epoch. Fluvio uses
+    /// the timestamp of each event to find the earliest event which was
+    /// created at or after the given time. If all events are older, the
+    /// offset points to the end of the log, so only new events are read.
+    ///
+    /// The timestamp must not be less than zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fluvio::Offset;
+    /// // Creates an offset pointing to events since 2020-11-01T09:00:00Z
+    /// let offset = Offset::from_timestamp(1_604_221_200_000).unwrap();
+    /// assert!(Offset::from_timestamp(-10).is_err());
+    /// ```
+    pub fn from_timestamp(timestamp: Timestamp) -> Result<Offset, FluvioError> {
+        if timestamp < 0 {
+            return Err(FluvioError::NegativeOffset(timestamp));
+        }
+        Ok(Self {
+            inner: OffsetInner::FromTimestamp(timestamp),
+        })
+    }
+
     /// Converts this offset into an absolute offset
     ///
     /// If this offset is relative from the beginning (i.e. it was created
@@ -267,6 +295,10 @@ impl Offset {
     /// the absolute offset by finding the last stably-committed event and subtracting
     /// the relative offset from it.
     ///
+    /// If this offset was created using the [`from_timestamp`] function, then
+    /// `to_absolute` will ask Fluvio for the offset of the first event at or
+    /// after that timestamp.
+    ///
     /// Calling `to_absolute` on an offset that is already absolute just returns
     /// that same offset.
     ///
@@ -293,6 +325,25 @@ impl Offset {
                 let offsets = fetch_offsets(client, &replica).await?;
                 offsets.last_stable_offset - offset
             }
+            OffsetInner::FromTimestamp(timestamp) => {
+                let replica = ReplicaKey::new(topic, partition);
+                let request = FetchOffsetsRequest::new_with_timestamp(
+                    replica.topic.to_owned(),
+                    replica.partition,
+                    timestamp,
+                );
+                let offsets = send_fetch_offsets(client, &replica, request).await?;
+                match offsets.timestamp_offset {
+                    Some(offset) => offset,
+                    None => {
+                        return Err(IoError::new(
+                            ErrorKind::InvalidData,
+                            format!("no timestamp offset for: {}", replica),
+                        )
+                        .into())
+                    }
+                }
+            }
         };
 
         Ok(offset)
@@ -302,15 +353,19 @@ impl Offset {
 async fn fetch_offsets<F: SerialFrame>(
     client: &mut F,
     replica: &ReplicaKey,
+) -> Result<FetchOffsetPartitionResponse, FluvioError> {
+    let request = FetchOffsetsRequest::new(replica.topic.to_owned(), replica.partition);
+    send_fetch_offsets(client, replica, request).await
+}
+
+async fn send_fetch_offsets<F: SerialFrame>(
+    client: &mut F,
+    replica: &ReplicaKey,
+    request: FetchOffsetsRequest,
 ) -> Result<FetchOffsetPartitionResponse, FluvioError> {
     debug!("fetching offset for replica: {}", replica);
 
-    let response = client
-        .send_receive(FetchOffsetsRequest::new(
-            replica.topic.to_owned(),
-            replica.partition,
-        ))
-        .await?;
+    let response = client.send_receive(request).await?;
 
     trace!(
         "receive fetch response replica: {}, {:#?}",
@@ -318,9 +373,12 @@ async fn fetch_offsets<F: SerialFrame>(
         response
     );
 
-    match response.find_partition(&replica) {
+    match response.find_partition(replica) {
         Some(partition_response) => {
             debug!("replica: {}, fetch offset: {}", replica, partition_response);
+            if partition_response.error_code.is_error() {
+                return Err(partition_response.error_code.into());
+            }
             Ok(partition_response)
         }
         None => Err(IoError::new(
@@ -0,0 +1,493 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use tracing::{debug, trace};
+use async_channel::{Sender, Receiver};
+use async_mutex::Mutex;
+use async_trait::async_trait;
+use event_listener::Event;
+use futures_util::future::{BoxFuture, FutureExt};
+
+use dataplane::ReplicaKey;
+use dataplane::current_timestamp;
+use dataplane::batch::DefaultBatch;
+use dataplane::compression::Compression;
+use dataplane::core::Encoder;
+use dataplane::record::DefaultRecord;
+
+use crate::FluvioError;
+
+use super::ProducerConfig;
+use super::sender::shared_error;
+
+type DeliveryResult = Result<(), FluvioError>;
+
+/// max number of sends waiting for send loop, records are not accepted until it catches up
+const READY_QUEUE_SIZE: usize = 16;
+
+/// Future that resolves once a produced record has been acknowledged by the partition leader
+///
+/// A `RecordDelivery` is returned by [`TopicProducer::queue_record`]. Records are
+/// delivered in batches, so all records which were sent in the same batch resolve
+/// together with the same outcome.
+///
+/// [`TopicProducer::queue_record`]: struct.TopicProducer.html#method.queue_record
+pub struct RecordDelivery {
+    inner: BoxFuture<'static, Result<(), FluvioError>>,
+}
+
+impl RecordDelivery {
+    /// delivery that has already completed
+    pub(crate) fn ready(result: Result<(), FluvioError>) -> Self {
+        Self {
+            inner: futures_util::future::ready(result).boxed(),
+        }
+    }
+
+    /// delivery that is completed when the batch containing the record is sent
+    fn pending(receiver: Receiver<DeliveryResult>) -> Self {
+        Self {
+            inner: async move {
+                match receiver.recv().await {
+                    Ok(result) => result,
+                    Err(_) => Err(closed_error()),
+                }
+            }
+            .boxed(),
+        }
+    }
+}
+
+impl Future for RecordDelivery {
+    type Output = Result<(), FluvioError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+/// Destination of batches collected by accumulator
+#[async_trait]
+pub(crate) trait BatchSink: Send + Sync {
+    /// send batches to leaders of their replicas, returns result of each batch in same order
+    async fn send_all(
+        &self,
+        batches: Vec<(ReplicaKey, DefaultBatch)>,
+    ) -> Vec<Result<(), FluvioError>>;
+}
+
+/// records waiting to be sent to a single partition
+struct PendingBatch {
+    batch: DefaultBatch,
+    size: usize,
+    created_at: Instant,
+    deliveries: Vec<Sender<DeliveryResult>>,
+}
+
+impl PendingBatch {
+    fn new(compression: Compression) -> Self {
+        Self {
+            batch: DefaultBatch::default().with_compression(compression),
+            size: 0,
+            created_at: Instant::now(),
+            deliveries: vec![],
+        }
+    }
+
+    fn add(&mut self, record: DefaultRecord, delivery: Sender<DeliveryResult>) {
+        self.size += record.write_size(0);
+        self.batch
+            .add_record_with_timestamp(record, current_timestamp());
+        self.deliveries.push(delivery);
+    }
+}
+
+/// batches handed over to send loop
+struct ReadyBatches {
+    batches: Vec<(i32, PendingBatch)>,
+    /// notified with result once batches are sent
+    sent: Option<Sender<Result<(), FluvioError>>>,
+}
+
+/// notify every record of a batch of the result
+fn notify_deliveries(deliveries: &[Sender<DeliveryResult>], result: &Result<(), FluvioError>) {
+    for delivery in deliveries {
+        let delivery_result = match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(shared_error(err)),
+        };
+        // receiver may have been dropped if caller doesn't care about delivery
+        let _ = delivery.try_send(delivery_result);
+    }
+}
+
+/// Collects records per partition and sends them in batches
+///
+/// Batches are sent when they are full or when they have lingered for
+/// longer than configured linger time. Batches are sent by send loop in
+/// order they are ready, so records don't wait for sending of other batches.
+pub(crate) struct RecordAccumulator {
+    topic: String,
+    linger: Duration,
+    batch_size: usize,
+    compression: Compression,
+    batches: Mutex<HashMap<i32, PendingBatch>>,
+    ready: Sender<ReadyBatches>,
+    /// notified when batch is started for partition, so linger loop can wait for it
+    batch_created: Event,
+    shutdown_flag: AtomicBool,
+    shutdown: Event,
+}
+
+impl RecordAccumulator {
+    /// create accumulator and spawn its linger and send loops
+    pub fn start(topic: String, sender: Arc<dyn BatchSink>, config: &ProducerConfig) -> Arc<Self> {
+        use fluvio_future::task::spawn;
+
+        let (ready, ready_receiver) = async_channel::bounded(READY_QUEUE_SIZE);
+        let accumulator = Arc::new(Self {
+            topic: topic.clone(),
+            linger: config.linger.unwrap_or_default(),
+            batch_size: config.batch_size,
+            compression: config.compression,
+            batches: Mutex::new(HashMap::new()),
+            ready,
+            batch_created: Event::new(),
+            shutdown_flag: AtomicBool::new(false),
+            shutdown: Event::new(),
+        });
+
+        spawn(send_loop(topic, sender, ready_receiver));
+        spawn(accumulator.clone().linger_loop());
+        accumulator
+    }
+
+    /// add record to partition's pending batch
+    ///
+    /// if this fills up the batch, it is handed over to send loop
+    pub async fn push(&self, record: DefaultRecord, partition: i32) -> RecordDelivery {
+        let (sender, receiver) = async_channel::bounded(1);
+
+        let (created, full_batch) = {
+            let mut batches = self.batches.lock().await;
+            let created = !batches.contains_key(&partition);
+            let pending = batches
+                .entry(partition)
+                .or_insert_with(|| PendingBatch::new(self.compression));
+            pending.add(record, sender);
+            if pending.size >= self.batch_size {
+                (created, batches.remove(&partition))
+            } else {
+                (created, None)
+            }
+        };
+
+        match full_batch {
+            Some(pending) => {
+                trace!(partition, "batch is full, sending");
+                // error is reported through the delivery
+                let _ = self.send_ready(vec![(partition, pending)], None).await;
+            }
+            None if created => self.batch_created.notify(usize::MAX),
+            None => {}
+        }
+
+        RecordDelivery::pending(receiver)
+    }
+
+    /// send every pending batch and wait until they and batches sent before are acknowledged
+    pub async fn flush(&self) -> Result<(), FluvioError> {
+        let pending: Vec<(i32, PendingBatch)> = self.batches.lock().await.drain().collect();
+        let (sent, result) = async_channel::bounded(1);
+        self.send_ready(pending, Some(sent)).await?;
+        match result.recv().await {
+            Ok(result) => result,
+            Err(_) => Err(closed_error()),
+        }
+    }
+
+    /// stop linger loop, remaining records are flushed
+    pub fn shutdown(&self) {
+        self.shutdown_flag.store(true, Ordering::SeqCst);
+        self.shutdown.notify(usize::MAX);
+    }
+
+    async fn linger_loop(self: Arc<Self>) {
+        use tokio::select;
+        use futures_util::future::pending;
+        use fluvio_future::timer::sleep;
+
+        debug!(topic = &*self.topic, "starting producer linger loop");
+
+        loop {
+            let shutdown = self.shutdown.listen();
+            let batch_created = self.batch_created.listen();
+            if self.shutdown_flag.load(Ordering::SeqCst) {
+                break;
+            }
+
+            // without pending batches, wait until first record is added
+            let expiration = self.next_expiration().await;
+            let lingered = async {
+                match expiration {
+                    Some(wait) => sleep(wait).await,
+                    None => pending().await,
+                }
+            };
+
+            select! {
+                _ = lingered => {
+                    let expired = self.take_expired().await;
+                    if !expired.is_empty() {
+                        trace!("sending {} lingering batches", expired.len());
+                        let _ = self.send_ready(expired, None).await;
+                    }
+                },
+                _ = batch_created => {},
+                _ = shutdown => {}
+            }
+        }
+
+        if let Err(err) = self.flush().await {
+            debug!("error flushing records at shutdown: {}", err);
+        }
+        debug!(topic = &*self.topic, "producer linger loop terminated");
+    }
+
+    /// time until oldest pending batch has lingered long enough, None if there is none
+    async fn next_expiration(&self) -> Option<Duration> {
+        let batches = self.batches.lock().await;
+        batches
+            .values()
+            .map(|pending| self.linger.saturating_sub(pending.created_at.elapsed()))
+            .min()
+    }
+
+    async fn take_expired(&self) -> Vec<(i32, PendingBatch)> {
+        let mut batches = self.batches.lock().await;
+        let expired: Vec<i32> = batches
+            .iter()
+            .filter(|(_, pending)| pending.created_at.elapsed() >= self.linger)
+            .map(|(partition, _)| *partition)
+            .collect();
+
+        expired
+            .into_iter()
+            .filter_map(|partition| batches.remove(&partition).map(|batch| (partition, batch)))
+            .collect()
+    }
+
+    /// hand batches over to send loop, waits only if send loop is behind
+    async fn send_ready(
+        &self,
+        batches: Vec<(i32, PendingBatch)>,
+        sent: Option<Sender<Result<(), FluvioError>>>,
+    ) -> Result<(), FluvioError> {
+        self.ready
+            .send(ReadyBatches { batches, sent })
+            .await
+            .map_err(|_| closed_error())
+    }
+}
+
+fn closed_error() -> FluvioError {
+    FluvioError::Other("producer was closed before record was sent".to_owned())
+}
+
+/// send batches handed over by accumulator, until accumulator is dropped
+async fn send_loop(topic: String, sender: Arc<dyn BatchSink>, ready: Receiver<ReadyBatches>) {
+    debug!(topic = &*topic, "starting producer send loop");
+    while let Ok(ready) = ready.recv().await {
+        let result = send_batches(&topic, sender.as_ref(), ready.batches).await;
+        if let Some(sent) = ready.sent {
+            let _ = sent.try_send(result);
+        }
+    }
+    debug!(topic = &*topic, "producer send loop terminated");
+}
+
+/// send batches together, so batches to the same leader go in one request
+async fn send_batches(
+    topic: &str,
+    sender: &dyn BatchSink,
+    pending: Vec<(i32, PendingBatch)>,
+) -> Result<(), FluvioError> {
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    let mut batches = Vec::with_capacity(pending.len());
+    let mut deliveries = Vec::with_capacity(pending.len());
+    for (partition, pending) in pending {
+        let replica = ReplicaKey::new(topic, partition);
+        debug!(
+            "sending batch of {} records to: {}",
+            pending.deliveries.len(),
+            replica
+        );
+        batches.push((replica, pending.batch));
+        deliveries.push(pending.deliveries);
+    }
+
+    let results = sender.send_all(batches).await;
+    for (deliveries, result) in deliveries.iter().zip(&results) {
+        notify_deliveries(deliveries, result);
+    }
+    results.into_iter().collect()
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    use async_trait::async_trait;
+    use fluvio_future::test_async;
+
+    use dataplane::ErrorCode;
+    use dataplane::ReplicaKey;
+    use dataplane::batch::DefaultBatch;
+    use dataplane::record::DefaultRecord;
+
+    use crate::FluvioError;
+    use crate::producer::ProducerConfig;
+
+    use super::BatchSink;
+    use super::RecordAccumulator;
+
+    /// keeps partition and record count of sent batches
+    #[derive(Default)]
+    struct TestSink {
+        sent: Mutex<Vec<(i32, usize)>>,
+        error: Option<ErrorCode>,
+    }
+
+    impl TestSink {
+        fn sent(&self) -> Vec<(i32, usize)> {
+            let mut sent = self.sent.lock().expect("lock").clone();
+            sent.sort_unstable();
+            sent
+        }
+    }
+
+    #[async_trait]
+    impl BatchSink for TestSink {
+        async fn send_all(
+            &self,
+            batches: Vec<(ReplicaKey, DefaultBatch)>,
+        ) -> Vec<Result<(), FluvioError>> {
+            let mut sent = self.sent.lock().expect("lock");
+            batches
+                .iter()
+                .map(|(replica, batch)| {
+                    sent.push((replica.partition, batch.records().len()));
+                    match self.error {
+                        Some(error_code) => Err(error_code.into()),
+                        None => Ok(()),
+                    }
+                })
+                .collect()
+        }
+    }
+
+    fn record() -> DefaultRecord {
+        DefaultRecord::from(vec![0u8; 100])
+    }
+
+    #[test_async]
+    async fn test_batch_size_trigger() -> Result<(), ()> {
+        let sink = Arc::new(TestSink::default());
+        let config = ProducerConfig::default()
+            .with_linger(Duration::from_secs(60))
+            .with_batch_size(250);
+        let accumulator = RecordAccumulator::start("test".to_owned(), sink.clone(), &config);
+
+        let first = accumulator.push(record(), 0).await;
+        let second = accumulator.push(record(), 0).await;
+        assert!(sink.sent().is_empty());
+
+        // third record fills up batch, which is sent without lingering
+        let third = accumulator.push(record(), 0).await;
+        for delivery in [first, second, third] {
+            delivery.await.expect("delivered");
+        }
+        assert_eq!(sink.sent(), vec![(0, 3)]);
+
+        accumulator.shutdown();
+        Ok(())
+    }
+
+    #[test_async]
+    async fn test_linger() -> Result<(), ()> {
+        let linger = Duration::from_millis(100);
+        let sink = Arc::new(TestSink::default());
+        let config = ProducerConfig::default().with_linger(linger);
+        let accumulator = RecordAccumulator::start("test".to_owned(), sink.clone(), &config);
+
+        // linger loop is waiting for first record
+        fluvio_future::timer::sleep(Duration::from_millis(250)).await;
+
+        let start = Instant::now();
+        let delivery = accumulator.push(record(), 1).await;
+        delivery.await.expect("delivered");
+        let elapsed = start.elapsed();
+        assert!(elapsed >= linger, "sent after: {:?}", elapsed);
+        assert!(elapsed < linger * 2, "sent after: {:?}", elapsed);
+        assert_eq!(sink.sent(), vec![(1, 1)]);
+
+        accumulator.shutdown();
+        Ok(())
+    }
+
+    #[test_async]
+    async fn test_flush() -> Result<(), ()> {
+        let sink = Arc::new(TestSink::default());
+        let config = ProducerConfig::default().with_linger(Duration::from_secs(60));
+        let accumulator = RecordAccumulator::start("test".to_owned(), sink.clone(), &config);
+
+        let first = accumulator.push(record(), 0).await;
+        let second = accumulator.push(record(), 1).await;
+        let third = accumulator.push(record(), 1).await;
+
+        accumulator.flush().await.expect("flush");
+        assert_eq!(sink.sent(), vec![(0, 1), (1, 2)]);
+        for delivery in [first, second, third] {
+            delivery.await.expect("delivered");
+        }
+
+        accumulator.shutdown();
+        Ok(())
+    }
+
+    #[test_async]
+    async fn test_delivery_error() -> Result<(), ()> {
+        let sink = Arc::new(TestSink {
+            error: Some(ErrorCode::NotLeaderForPartition),
+            ..Default::default()
+        });
+        let config = ProducerConfig::default().with_linger(Duration::from_secs(60));
+        let accumulator = RecordAccumulator::start("test".to_owned(), sink, &config);
+
+        let first = accumulator.push(record(), 0).await;
+        let second = accumulator.push(record(), 0).await;
+        let err = accumulator.flush().await.expect_err("flush fails");
+        assert_eq!(err.error_code(), Some(ErrorCode::NotLeaderForPartition));
+
+        // every record of batch gets typed error
+        for delivery in [first, second] {
+            let err = delivery.await.expect_err("not delivered");
+            assert_eq!(err.error_code(), Some(ErrorCode::NotLeaderForPartition));
+            assert!(err.is_retriable());
+        }
+
+        accumulator.shutdown();
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dataplane::compression::Compression;
+
+use super::{Partitioner, DefaultPartitioner};
+
+/// default max bytes collected for a partition before the batch is sent
+const DEFAULT_BATCH_SIZE: usize = 16384;
+/// default number of times a batch is sent again after a retriable error
+const DEFAULT_RETRIES: usize = 5;
+const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(100);
+/// records are acknowledged once partition leader has written them
+const DEFAULT_ACKS: i16 = 1;
+
+/// Configures the behavior of a `TopicProducer`
+///
+/// By default, every record is sent to the partition leader as soon as it is
+/// produced. Setting a `linger` time switches the producer to batching mode:
+/// records are collected per partition in memory and sent together once the
+/// oldest record has waited for `linger`, or once the pending records reach
+/// `batch_size` bytes, whichever comes first.
+///
+/// Records which are sent without an explicit partition are assigned to
+/// a partition by a [`Partitioner`]. Unless a custom one is given with
+/// [`with_partitioner`], the [`DefaultPartitioner`] is used.
+///
+/// Each batch of records can be compressed with a codec set by
+/// [`with_compression`]. Consumers decompress the records transparently.
+///
+/// Batches which fail with a retriable error, such as a lost connection or
+/// a change of partition leader, are sent again up to [`with_retries`] times.
+/// The producer is idempotent by default: the partition leader drops batches
+/// which it has already written, so retries never duplicate records.
+///
+/// [`with_acks`] controls when the partition leader acknowledges records:
+/// `0` doesn't wait for any acknowledgement, `1` waits until the leader has
+/// written them, and `-1` waits until they are replicated to the min in sync
+/// replicas of the topic.
+///
+/// # Example
+///
+/// ```
+/// # use std::time::Duration;
+/// # use fluvio::{ProducerConfig, Compression};
+/// let config = ProducerConfig::default()
+///     .with_linger(Duration::from_millis(10))
+///     .with_batch_size(32 * 1024)
+///     .with_compression(Compression::Gzip);
+/// ```
+///
+/// [`Partitioner`]: trait.Partitioner.html
+/// [`with_partitioner`]: struct.ProducerConfig.html#method.with_partitioner
+/// [`DefaultPartitioner`]: struct.DefaultPartitioner.html
+/// [`with_compression`]: struct.ProducerConfig.html#method.with_compression
+/// [`with_retries`]: struct.ProducerConfig.html#method.with_retries
+/// [`with_acks`]: struct.ProducerConfig.html#method.with_acks
+#[derive(Clone)]
+pub struct ProducerConfig {
+    pub(crate) linger: Option<Duration>,
+    pub(crate) batch_size: usize,
+    pub(crate) compression: Compression,
+    pub(crate) partitioner: Arc<dyn Partitioner>,
+    pub(crate) retries: usize,
+    pub(crate) retry_backoff: Duration,
+    pub(crate) idempotence: bool,
+    pub(crate) acks: i16,
+}
+
+impl fmt::Debug for ProducerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ProducerConfig")
+            .field("linger", &self.linger)
+            .field("batch_size", &self.batch_size)
+            .field("compression", &self.compression)
+            .field("retries", &self.retries)
+            .field("retry_backoff", &self.retry_backoff)
+            .field("idempotence", &self.idempotence)
+            .field("acks", &self.acks)
+            .finish()
+    }
+}
+
+impl Default for ProducerConfig {
+    fn default() -> Self {
+        Self {
+            linger: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            compression: Compression::None,
+            partitioner: Arc::new(DefaultPartitioner::default()),
+            retries: DEFAULT_RETRIES,
+            retry_backoff: DEFAULT_RETRY_BACKOFF,
+            idempotence: true,
+            acks: DEFAULT_ACKS,
+        }
+    }
+}
+
+impl ProducerConfig {
+    /// Max time a record is held in memory before its batch is sent
+    ///
+    /// Setting a linger time enables batching.
+    pub fn with_linger(mut self, linger: Duration) -> Self {
+        self.linger = Some(linger);
+        self
+    }
+
+    /// Max bytes of pending records in a partition before its batch is sent
+    ///
+    /// This only has an effect when batching is enabled with [`with_linger`].
+    ///
+    /// [`with_linger`]: struct.ProducerConfig.html#method.with_linger
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Codec used to compress each batch of records
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Strategy to choose the partition of records sent without an explicit partition
+    pub fn with_partitioner<P: Partitioner + 'static>(mut self, partitioner: P) -> Self {
+        self.partitioner = Arc::new(partitioner);
+        self
+    }
+
+    /// Max number of times a batch is sent again after a retriable error
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Time to wait before first retry, it doubles with every following retry
+    pub fn with_retry_backoff(mut self, retry_backoff: Duration) -> Self {
+        self.retry_backoff = retry_backoff;
+        self
+    }
+
+    /// Tag batches with producer id and sequence, so retries don't duplicate records
+    ///
+    /// When enabled, batches to the same partition are sent one at a time.
+    pub fn with_idempotence(mut self, idempotence: bool) -> Self {
+        self.idempotence = idempotence;
+        self
+    }
+
+    /// Acknowledgement required from partition leader: `0`, `1` or `-1` (all in sync replicas)
+    pub fn with_acks(mut self, acks: i16) -> Self {
+        self.acks = acks;
+        self
+    }
+
+    /// check if records should be batched
+    pub(crate) fn is_batching(&self) -> bool {
+        self.linger.is_some()
+    }
+}
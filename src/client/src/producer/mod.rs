@@ -0,0 +1,314 @@
+mod config;
+mod accumulator;
+mod partitioning;
+mod sender;
+
+use std::sync::Arc;
+
+use tracing::{debug, trace, instrument};
+use dataplane::ReplicaKey;
+use dataplane::current_timestamp;
+use dataplane::batch::DefaultBatch;
+use dataplane::compression::Compression;
+use dataplane::record::DefaultRecord;
+use dataplane::record::RecordHeader;
+
+use crate::FluvioError;
+use crate::spu::SpuPool;
+
+pub use config::ProducerConfig;
+pub use accumulator::RecordDelivery;
+pub use partitioning::{Partitioner, PartitionerConfig, DefaultPartitioner};
+use accumulator::RecordAccumulator;
+use sender::BatchSender;
+
+/// An interface for producing events to a particular topic
+///
+/// A `TopicProducer` allows you to send events to the specific
+/// topic it was initialized for. Once you have a `TopicProducer`,
+/// you can send events to the topic, either choosing which partition
+/// each event should be delivered to, or letting the producer's
+/// [`Partitioner`] pick the partition based on the event's key.
+///
+/// When created with a [`ProducerConfig`] that has a linger time,
+/// the producer collects records in memory and sends them to each
+/// partition in batches. Pending records are sent when the producer
+/// is dropped, or explicitly with [`flush`].
+///
+/// [`Partitioner`]: trait.Partitioner.html
+/// [`ProducerConfig`]: struct.ProducerConfig.html
+/// [`flush`]: struct.TopicProducer.html#method.flush
+pub struct TopicProducer {
+    topic: String,
+    pool: Arc<SpuPool>,
+    sender: Arc<BatchSender>,
+    partitioner: Arc<dyn Partitioner>,
+    compression: Compression,
+    accumulator: Option<Arc<RecordAccumulator>>,
+}
+
+impl Drop for TopicProducer {
+    fn drop(&mut self) {
+        if let Some(accumulator) = &self.accumulator {
+            accumulator.shutdown();
+        }
+    }
+}
+
+impl TopicProducer {
+    pub(crate) fn new(topic: String, pool: Arc<SpuPool>, config: ProducerConfig) -> Self {
+        let sender = Arc::new(BatchSender::new(pool.clone(), &config));
+        let accumulator = if config.is_batching() {
+            Some(RecordAccumulator::start(
+                topic.clone(),
+                sender.clone(),
+                &config,
+            ))
+        } else {
+            None
+        };
+
+        Self {
+            topic,
+            pool,
+            sender,
+            partitioner: config.partitioner,
+            compression: config.compression,
+            accumulator,
+        }
+    }
+
+    /// Sends a key/value event to this producer's topic
+    ///
+    /// The key is stored with the event, and the partition is chosen by
+    /// the producer's [`Partitioner`]. With the default partitioner, events
+    /// with the same key are always sent to the same partition.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use fluvio::{TopicProducer, FluvioError};
+    /// # async fn do_send(producer: &TopicProducer) -> Result<(), FluvioError> {
+    /// producer.send("user-1", "logged in").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Partitioner`]: trait.Partitioner.html
+    pub async fn send<K, V>(&self, key: K, value: V) -> Result<(), FluvioError>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.queue(key, value).await?.await
+    }
+
+    /// Sends an event without a key to this producer's topic
+    ///
+    /// The partition is chosen by the producer's [`Partitioner`]. The default
+    /// partitioner spreads events without a key over all partitions in
+    /// round-robin order.
+    ///
+    /// [`Partitioner`]: trait.Partitioner.html
+    pub async fn send_value<V: AsRef<[u8]>>(&self, value: V) -> Result<(), FluvioError> {
+        let record = self.create_record(None, value.as_ref(), vec![]);
+        let partition = self.select_partition(None, value.as_ref()).await?;
+        self.queue_to_partition(record, partition).await?.await
+    }
+
+    /// Sends a key/value event with headers to this producer's topic
+    ///
+    /// Headers are passed along with the event to consumers, they don't
+    /// have an effect on which partition the event is sent to.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use fluvio::{TopicProducer, FluvioError, RecordHeader};
+    /// # async fn do_send(producer: &TopicProducer) -> Result<(), FluvioError> {
+    /// let headers = vec![RecordHeader::new("trace-id", "4bf92f3577b34da6")];
+    /// producer.send_with_headers("user-1", "logged in", headers).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_with_headers<K, V>(
+        &self,
+        key: K,
+        value: V,
+        headers: Vec<RecordHeader>,
+    ) -> Result<(), FluvioError>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.queue_with_headers(key, value, headers).await?.await
+    }
+
+    /// Queues a key/value event and returns its delivery
+    ///
+    /// This is the same as [`send`], except that it doesn't wait until the
+    /// event is acknowledged. See [`queue_record`] for details.
+    ///
+    /// [`send`]: struct.TopicProducer.html#method.send
+    /// [`queue_record`]: struct.TopicProducer.html#method.queue_record
+    pub async fn queue<K, V>(&self, key: K, value: V) -> Result<RecordDelivery, FluvioError>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.queue_with_headers(key, value, vec![]).await
+    }
+
+    /// Queues a key/value event with headers and returns its delivery
+    ///
+    /// This is the same as [`send_with_headers`], except that it doesn't wait
+    /// until the event is acknowledged.
+    ///
+    /// [`send_with_headers`]: struct.TopicProducer.html#method.send_with_headers
+    pub async fn queue_with_headers<K, V>(
+        &self,
+        key: K,
+        value: V,
+        headers: Vec<RecordHeader>,
+    ) -> Result<RecordDelivery, FluvioError>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+        let record = self.create_record(Some(key), value, headers);
+        let partition = self.select_partition(Some(key), value).await?;
+        self.queue_to_partition(record, partition).await
+    }
+
+    /// Sends an event to a specific partition within this producer's topic
+    ///
+    /// This waits until the event has been acknowledged by the partition leader.
+    /// With batching enabled, the event is sent along with other events
+    /// produced to the same partition.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use fluvio::{TopicProducer, FluvioError};
+    /// # async fn do_send_record(producer: &TopicProducer) -> Result<(), FluvioError> {
+    /// let partition = 0;
+    /// producer.send_record("Hello, Fluvio!", partition).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(
+        skip(self, buffer),
+        fields(topic = &*self.topic),
+    )]
+    pub async fn send_record<B: AsRef<[u8]>>(
+        &self,
+        buffer: B,
+        partition: i32,
+    ) -> Result<(), FluvioError> {
+        self.queue_record(buffer, partition).await?.await
+    }
+
+    /// Queues an event for a specific partition and returns its delivery
+    ///
+    /// With batching enabled, this returns as soon as the event has been added
+    /// to its partition's batch. The returned [`RecordDelivery`] completes once
+    /// the batch has been acknowledged by the partition leader. Without batching,
+    /// the event is sent right away and the delivery is already complete.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use fluvio::{TopicProducer, FluvioError};
+    /// # async fn do_queue_records(producer: &TopicProducer) -> Result<(), FluvioError> {
+    /// let mut deliveries = vec![];
+    /// for i in 0..100 {
+    ///     deliveries.push(producer.queue_record(format!("event {}", i), 0).await?);
+    /// }
+    /// producer.flush().await?;
+    /// for delivery in deliveries {
+    ///     delivery.await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`RecordDelivery`]: struct.RecordDelivery.html
+    pub async fn queue_record<B: AsRef<[u8]>>(
+        &self,
+        buffer: B,
+        partition: i32,
+    ) -> Result<RecordDelivery, FluvioError> {
+        let record = self.create_record(None, buffer.as_ref(), vec![]);
+        self.queue_to_partition(record, partition).await
+    }
+
+    /// Sends all pending events and waits until they are acknowledged
+    ///
+    /// This is only needed when batching is enabled. Otherwise, events
+    /// are never held in memory and this returns immediately.
+    pub async fn flush(&self) -> Result<(), FluvioError> {
+        match &self.accumulator {
+            Some(accumulator) => accumulator.flush().await,
+            None => Ok(()),
+        }
+    }
+
+    fn create_record(
+        &self,
+        key: Option<&[u8]>,
+        value: &[u8],
+        headers: Vec<RecordHeader>,
+    ) -> DefaultRecord {
+        let mut record: DefaultRecord = value.into();
+        if let Some(key) = key {
+            record.key = key.into();
+        }
+        record.headers = headers;
+        record
+    }
+
+    /// choose partition using partitioner
+    async fn select_partition(&self, key: Option<&[u8]>, value: &[u8]) -> Result<i32, FluvioError> {
+        let partition_count = self
+            .pool
+            .metadata()
+            .topics()
+            .topic_partition_count(&self.topic)
+            .await?;
+        let config = PartitionerConfig { partition_count };
+        let partition = self.partitioner.partition(&config, key, value);
+        trace!(partition, partition_count, "partition selected");
+        if partition < 0 || partition >= partition_count {
+            return Err(FluvioError::PartitionNotFound(
+                self.topic.clone(),
+                partition,
+            ));
+        }
+        Ok(partition)
+    }
+
+    async fn queue_to_partition(
+        &self,
+        record: DefaultRecord,
+        partition: i32,
+    ) -> Result<RecordDelivery, FluvioError> {
+        match &self.accumulator {
+            Some(accumulator) => Ok(accumulator.push(record, partition).await),
+            None => {
+                let replica = ReplicaKey::new(&self.topic, partition);
+                debug!(
+                    "sending records: {} bytes to: {}",
+                    record.value.len(),
+                    &replica
+                );
+                let mut batch = DefaultBatch::default().with_compression(self.compression);
+                batch.add_record_with_timestamp(record, current_timestamp());
+                Ok(RecordDelivery::ready(
+                    self.sender.send(&replica, batch).await,
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Information about the topic a record is being partitioned for
+#[derive(Debug, Clone)]
+pub struct PartitionerConfig {
+    /// number of partitions of the topic
+    pub partition_count: i32,
+}
+
+/// A strategy for choosing the partition a record is sent to
+///
+/// A `Partitioner` is used by [`TopicProducer::send`] and the other
+/// methods which don't take an explicit partition. The returned
+/// partition must be less than `config.partition_count`, otherwise
+/// the record is rejected. Partition count is never zero.
+///
+/// # Example
+///
+/// ```
+/// use fluvio::{Partitioner, PartitionerConfig};
+///
+/// /// sends every record to the last partition
+/// struct LastPartitioner;
+///
+/// impl Partitioner for LastPartitioner {
+///     fn partition(&self, config: &PartitionerConfig, _key: Option<&[u8]>, _value: &[u8]) -> i32 {
+///         config.partition_count - 1
+///     }
+/// }
+/// ```
+///
+/// [`TopicProducer::send`]: struct.TopicProducer.html#method.send
+pub trait Partitioner: Send + Sync {
+    fn partition(&self, config: &PartitionerConfig, key: Option<&[u8]>, value: &[u8]) -> i32;
+}
+
+/// Default partitioner of the producer
+///
+/// Records with a key are assigned to a partition using murmur2 hash of the
+/// key, compatible with the Kafka default partitioner. Records without a key
+/// are spread over all partitions in round-robin order.
+#[derive(Debug, Default)]
+pub struct DefaultPartitioner {
+    index: AtomicUsize,
+}
+
+impl Partitioner for DefaultPartitioner {
+    fn partition(&self, config: &PartitionerConfig, key: Option<&[u8]>, _value: &[u8]) -> i32 {
+        match key {
+            Some(key) => partition_for_key(key, config.partition_count),
+            None => {
+                let index = self.index.fetch_add(1, Ordering::Relaxed);
+                (index % config.partition_count as usize) as i32
+            }
+        }
+    }
+}
+
+/// compute partition of a key by hashing it
+fn partition_for_key(key: &[u8], partition_count: i32) -> i32 {
+    to_positive(murmur2(key)) % partition_count
+}
+
+fn to_positive(number: i32) -> i32 {
+    number & 0x7fffffff
+}
+
+/// murmur2 hash, same as used by Kafka clients
+fn murmur2(data: &[u8]) -> i32 {
+    const SEED: u32 = 0x9747b28c;
+    const M: u32 = 0x5bd1e995;
+    const R: u32 = 24;
+
+    let length = data.len();
+    let mut h: u32 = SEED ^ length as u32;
+
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M);
+        h ^= k;
+    }
+
+    let remainder = chunks.remainder();
+    if remainder.len() == 3 {
+        h ^= (remainder[2] as u32) << 16;
+    }
+    if remainder.len() >= 2 {
+        h ^= (remainder[1] as u32) << 8;
+    }
+    if !remainder.is_empty() {
+        h ^= remainder[0] as u32;
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^= h >> 15;
+
+    h as i32
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_murmur2_kafka_compatible() {
+        // values computed by org.apache.kafka.common.utils.Utils.murmur2
+        assert_eq!(murmur2(b"21"), -973932308);
+        assert_eq!(murmur2(b"foobar"), -790332482);
+        assert_eq!(murmur2(b"a-little-bit-long-string"), -985981536);
+        assert_eq!(murmur2(b"a-little-bit-longer-string"), -1486304829);
+        assert_eq!(
+            murmur2(b"lkjh234lh9fiuh90y23oiuhsafujhadof229phr9h19h89h8"),
+            -58897971
+        );
+        assert_eq!(murmur2(b"abc"), 479470107);
+    }
+
+    #[test]
+    fn test_keyed_partition_is_stable() {
+        let partitioner = DefaultPartitioner::default();
+        let config = PartitionerConfig { partition_count: 3 };
+
+        let partition = partitioner.partition(&config, Some(b"user-1"), b"a");
+        for _ in 0..10 {
+            assert_eq!(
+                partitioner.partition(&config, Some(b"user-1"), b"b"),
+                partition
+            );
+        }
+        assert!((0..3).contains(&partition));
+    }
+
+    #[test]
+    fn test_unkeyed_round_robin() {
+        let partitioner = DefaultPartitioner::default();
+        let config = PartitionerConfig { partition_count: 3 };
+
+        let partitions: Vec<i32> = (0..6)
+            .map(|_| partitioner.partition(&config, None, b"value"))
+            .collect();
+        assert_eq!(partitions, vec![0, 1, 2, 0, 1, 2]);
+    }
+}
@@ -0,0 +1,380 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::Error as IoError;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_mutex::Mutex;
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use tracing::{debug, trace};
+
+use dataplane::ErrorCode;
+use dataplane::ReplicaKey;
+use dataplane::batch::DefaultBatch;
+use dataplane::produce::DefaultProduceRequest;
+use dataplane::produce::DefaultPartitionRequest;
+use dataplane::produce::DefaultTopicRequest;
+use fluvio_spu_schema::server::init_producer_id::InitProducerIdRequest;
+use fluvio_types::SpuId;
+
+use crate::FluvioError;
+use crate::client::SerialFrame;
+use crate::spu::SpuPool;
+
+use super::ProducerConfig;
+use super::accumulator::BatchSink;
+
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(5);
+
+/// id of idempotent producer allocated by SPU
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ProducerId {
+    id: i64,
+    epoch: i16,
+}
+
+/// sequence of next record sent to partition by producer
+#[derive(Debug, Default)]
+struct PartitionSequence {
+    producer_id: Option<ProducerId>,
+    next_sequence: i32,
+}
+
+/// Sends batches to partition leaders
+///
+/// Batches are grouped by leader SPU, and all batches for the same SPU are sent
+/// in one produce request. Batches which fail with a retriable error, such as lost
+/// connection or leader change, are sent again after backoff.
+///
+/// With idempotence enabled, each batch is tagged with producer id and sequence of
+/// its first record, so leader drops batch that was already written but whose response
+/// was lost. Batches to a partition are then sent one at a time, so they always reach
+/// the leader in sequence order.
+pub(crate) struct BatchSender {
+    pool: Arc<SpuPool>,
+    retries: usize,
+    retry_backoff: Duration,
+    idempotence: bool,
+    acks: i16,
+    producer_id: Mutex<Option<ProducerId>>,
+    partitions: Mutex<HashMap<ReplicaKey, Arc<Mutex<PartitionSequence>>>>,
+}
+
+impl BatchSender {
+    pub fn new(pool: Arc<SpuPool>, config: &ProducerConfig) -> Self {
+        Self {
+            pool,
+            retries: config.retries,
+            retry_backoff: config.retry_backoff,
+            idempotence: config.idempotence,
+            acks: config.acks,
+            producer_id: Mutex::new(None),
+            partitions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// send batch to leader of the replica
+    pub async fn send(&self, replica: &ReplicaKey, batch: DefaultBatch) -> Result<(), FluvioError> {
+        self.send_all(vec![(replica.clone(), batch)])
+            .await
+            .remove(0)
+    }
+
+    /// send batches to leaders of their replicas, returns result of each batch in same order
+    pub async fn send_all(
+        &self,
+        mut batches: Vec<(ReplicaKey, DefaultBatch)>,
+    ) -> Vec<Result<(), FluvioError>> {
+        if !self.idempotence {
+            return self.send_with_retries(&batches).await;
+        }
+
+        // partitions are locked in order, so concurrent sends can't deadlock
+        let mut sequences = BTreeMap::new();
+        {
+            let mut partitions = self.partitions.lock().await;
+            for (replica, _) in &batches {
+                let sequence = partitions.entry(replica.clone()).or_default().clone();
+                sequences.insert(replica.clone(), sequence);
+            }
+        }
+        let mut guards = BTreeMap::new();
+        for (replica, sequence) in sequences {
+            guards.insert(replica, sequence.lock_arc().await);
+        }
+
+        let producer_id = match self.producer_id(&batches[0].0).await {
+            Ok(producer_id) => producer_id,
+            Err(err) => return batches.iter().map(|_| Err(shared_error(&err))).collect(),
+        };
+
+        for (replica, batch) in batches.iter_mut() {
+            let sequence = guards
+                .get_mut(replica)
+                .expect("partition of batch is locked");
+            if sequence.producer_id != Some(producer_id) {
+                sequence.producer_id = Some(producer_id);
+                sequence.next_sequence = 0;
+            }
+
+            let header = batch.get_mut_header();
+            header.producer_id = producer_id.id;
+            header.producer_epoch = producer_id.epoch;
+            header.first_sequence = sequence.next_sequence;
+            sequence.next_sequence = sequence
+                .next_sequence
+                .wrapping_add(batch.records().len() as i32);
+        }
+
+        let results = self.send_with_retries(&batches).await;
+        if results.iter().any(|result| result.is_err()) {
+            // failed batch may have been written, so following batches can't continue
+            // its sequence. they are sent with a new producer id instead
+            let mut current = self.producer_id.lock().await;
+            if *current == Some(producer_id) {
+                *current = None;
+            }
+        }
+        results
+    }
+
+    /// producer id, allocated by the first SPU it is requested from
+    async fn producer_id(&self, replica: &ReplicaKey) -> Result<ProducerId, FluvioError> {
+        let mut producer_id = self.producer_id.lock().await;
+        if let Some(producer_id) = *producer_id {
+            return Ok(producer_id);
+        }
+
+        let mut leader = self.pool.create_serial_socket(replica).await?;
+        let response = leader.send_receive(InitProducerIdRequest {}).await?;
+        if response.error_code.is_error() {
+            return Err(response.error_code.into());
+        }
+        debug!("allocated producer id: {}", response.producer_id);
+
+        let allocated = ProducerId {
+            id: response.producer_id,
+            epoch: response.producer_epoch,
+        };
+        *producer_id = Some(allocated);
+        Ok(allocated)
+    }
+
+    async fn send_with_retries(
+        &self,
+        batches: &[(ReplicaKey, DefaultBatch)],
+    ) -> Vec<Result<(), FluvioError>> {
+        use fluvio_future::timer::sleep;
+
+        let mut results: Vec<Option<Result<(), FluvioError>>> =
+            batches.iter().map(|_| None).collect();
+        let mut pending: Vec<usize> = (0..batches.len()).collect();
+        let mut attempt = 0;
+
+        loop {
+            let mut by_leader: HashMap<SpuId, Vec<usize>> = HashMap::new();
+            for index in pending {
+                match self.pool.lookup_leader(&batches[index].0).await {
+                    Ok(leader) => by_leader.entry(leader).or_default().push(index),
+                    Err(err) => results[index] = Some(Err(err)),
+                }
+            }
+
+            let responses = join_all(by_leader.into_iter().map(|(leader, indexes)| async move {
+                let response = self.send_to_leader(leader, batches, &indexes).await;
+                (leader, indexes, response)
+            }))
+            .await;
+
+            for (leader, indexes, response) in responses {
+                match response {
+                    Ok(partition_results) => {
+                        for (index, result) in indexes.into_iter().zip(partition_results) {
+                            results[index] = Some(result);
+                        }
+                    }
+                    Err(err) => {
+                        if matches!(
+                            err,
+                            FluvioError::IoError { .. } | FluvioError::FlvSocketError { .. }
+                        ) {
+                            self.pool.remove_spu_socket(leader).await;
+                        }
+                        for index in indexes {
+                            results[index] = Some(Err(shared_error(&err)));
+                        }
+                    }
+                }
+            }
+
+            pending = results
+                .iter()
+                .enumerate()
+                .filter(|(_, result)| matches!(result, Some(Err(err)) if self.can_retry(err)))
+                .map(|(index, _)| index)
+                .collect();
+            if pending.is_empty() || attempt >= self.retries {
+                break;
+            }
+
+            debug!(
+                "retrying {} batches, attempt: {}",
+                pending.len(),
+                attempt + 1
+            );
+            let backoff = self
+                .retry_backoff
+                .checked_mul(1 << attempt.min(16))
+                .map_or(MAX_RETRY_BACKOFF, |backoff| backoff.min(MAX_RETRY_BACKOFF));
+            sleep(backoff).await;
+            attempt += 1;
+        }
+
+        results
+            .into_iter()
+            .map(|result| result.expect("every batch has result"))
+            .collect()
+    }
+
+    /// batches written by leader but not replicated are only sent again by idempotent
+    /// producer, leader drops the ones it has already written
+    fn can_retry(&self, err: &FluvioError) -> bool {
+        match err {
+            FluvioError::ServerError {
+                error_code: ErrorCode::NotEnoughReplicasAfterAppend,
+                ..
+            } => self.idempotence,
+            err => err.is_retriable(),
+        }
+    }
+
+    /// send batches at indexes in one produce request, returns result of each batch
+    async fn send_to_leader(
+        &self,
+        leader: SpuId,
+        batches: &[(ReplicaKey, DefaultBatch)],
+        indexes: &[usize],
+    ) -> Result<Vec<Result<(), FluvioError>>, FluvioError> {
+        let mut socket = self.pool.create_serial_socket_to_spu(leader).await?;
+
+        let request = produce_request(batches, indexes, self.acks);
+        debug!(
+            "send {} batches in {} topics to spu: {}",
+            indexes.len(),
+            request.topics.len(),
+            leader
+        );
+        trace!("produce request: {:#?}", request);
+
+        let response = socket.send_receive(request).await?;
+        trace!("received response: {:?}", response);
+
+        Ok(indexes
+            .iter()
+            .map(|index| {
+                let replica = &batches[*index].0;
+                match response.find_partition_response(&replica.topic, replica.partition) {
+                    Some(partition_response) if partition_response.error_code.is_error() => {
+                        Err(partition_response.error_code.into())
+                    }
+                    Some(_) => Ok(()),
+                    None => Err(IoError::other("unknown error").into()),
+                }
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl BatchSink for BatchSender {
+    async fn send_all(
+        &self,
+        batches: Vec<(ReplicaKey, DefaultBatch)>,
+    ) -> Vec<Result<(), FluvioError>> {
+        BatchSender::send_all(self, batches).await
+    }
+}
+
+/// produce request with batches at indexes, grouped by topic
+fn produce_request(
+    batches: &[(ReplicaKey, DefaultBatch)],
+    indexes: &[usize],
+    acks: i16,
+) -> DefaultProduceRequest {
+    let mut topics: Vec<DefaultTopicRequest> = vec![];
+    for index in indexes {
+        let (replica, batch) = &batches[*index];
+        let position = match topics.iter().position(|topic| topic.name == replica.topic) {
+            Some(position) => position,
+            None => {
+                topics.push(DefaultTopicRequest {
+                    name: replica.topic.clone(),
+                    ..Default::default()
+                });
+                topics.len() - 1
+            }
+        };
+        let mut partition_request = DefaultPartitionRequest {
+            partition_index: replica.partition,
+            ..Default::default()
+        };
+        partition_request.records.batches.push(batch.clone());
+        topics[position].partitions.push(partition_request);
+    }
+
+    DefaultProduceRequest {
+        acks,
+        timeout_ms: 1500,
+        topics,
+        ..Default::default()
+    }
+}
+
+/// same error reported for every batch of a failed request
+pub(super) fn shared_error(err: &FluvioError) -> FluvioError {
+    match err {
+        FluvioError::ServerError {
+            error_code,
+            message,
+        } => FluvioError::ServerError {
+            error_code: *error_code,
+            message: message.clone(),
+        },
+        FluvioError::IoError { source } => IoError::new(source.kind(), source.to_string()).into(),
+        FluvioError::FlvSocketError { source } => IoError::other(source.to_string()).into(),
+        err => FluvioError::Other(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use dataplane::ReplicaKey;
+    use dataplane::batch::DefaultBatch;
+
+    use super::produce_request;
+
+    #[test]
+    fn test_produce_request_groups_topics() {
+        let batches = vec![
+            (ReplicaKey::new("a", 0), DefaultBatch::default()),
+            (ReplicaKey::new("b", 1), DefaultBatch::default()),
+            (ReplicaKey::new("a", 2), DefaultBatch::default()),
+            (ReplicaKey::new("c", 0), DefaultBatch::default()),
+        ];
+
+        let request = produce_request(&batches, &[0, 1, 2], -1);
+        assert_eq!(request.acks, -1);
+        assert_eq!(request.topics.len(), 2);
+        assert_eq!(request.topics[0].name, "a");
+        let partitions: Vec<i32> = request.topics[0]
+            .partitions
+            .iter()
+            .map(|partition| partition.partition_index)
+            .collect();
+        assert_eq!(partitions, vec![0, 2]);
+        assert_eq!(request.topics[1].name, "b");
+        assert_eq!(request.topics[1].partitions.len(), 1);
+        assert_eq!(request.topics[1].partitions[0].records.batches.len(), 1);
+    }
+}
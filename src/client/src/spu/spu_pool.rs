@@ -15,8 +15,6 @@ use crate::sync::MetadataStores;
 use crate::client::VersionedSerialSocket;
 use crate::client::Versions;
 
-const DEFAULT_STREAM_QUEUE_SIZE: usize = 10;
-
 struct SpuSocket {
     config: ClientConfig,
     socket: SharedAllMultiplexerSocket,
@@ -35,10 +33,11 @@ impl SpuSocket {
     async fn create_stream<R: Request>(
         &mut self,
         request: R,
+        queue_size: usize,
     ) -> Result<AsyncResponse<R>, FluvioError> {
         let req_msg = RequestMessage::new_request(request);
         self.socket
-            .create_stream(req_msg, DEFAULT_STREAM_QUEUE_SIZE)
+            .create_stream(req_msg, queue_size)
             .await
             .map_err(|err| err.into())
     }
@@ -73,6 +72,11 @@ impl SpuPool {
         })
     }
 
+    /// metadata cached by this pool
+    pub fn metadata(&self) -> &MetadataStores {
+        &self.metadata
+    }
+
     /// create new spu socket
     async fn connect_to_leader(&self, leader: SpuId) -> Result<SpuSocket, FluvioError> {
         let spu = self.metadata.spus().look_up_by_id(leader).await?;
@@ -96,6 +100,12 @@ impl SpuPool {
         &self,
         replica: &ReplicaKey,
     ) -> Result<VersionedSerialSocket, FluvioError> {
+        let leader_id = self.lookup_leader(replica).await?;
+        self.create_serial_socket_to_spu(leader_id).await
+    }
+
+    /// spu which is leader of replica
+    pub(crate) async fn lookup_leader(&self, replica: &ReplicaKey) -> Result<SpuId, FluvioError> {
         use std::io::ErrorKind;
 
         let partition = match self.metadata.partitions().lookup_by_key(replica).await {
@@ -114,9 +124,14 @@ impl SpuPool {
             }),
         }?;
 
-        let leader_id = partition.spec.leader;
+        Ok(partition.spec.leader)
+    }
 
-        // check if already have existing leader
+    /// create serial socket connection to spu, existing connection is reused
+    pub(crate) async fn create_serial_socket_to_spu(
+        &self,
+        leader_id: SpuId,
+    ) -> Result<VersionedSerialSocket, FluvioError> {
         let mut client_lock = self.spu_clients.lock().await;
 
         if let Some(spu_socket) = client_lock.get_mut(&leader_id) {
@@ -130,11 +145,12 @@ impl SpuPool {
         Ok(serial_socket)
     }
 
-    // create stream to replica
+    /// create stream to replica, up to `queue_size` responses are buffered
     pub async fn create_stream<R: Request>(
         &self,
         replica: &ReplicaKey,
         request: R,
+        queue_size: usize,
     ) -> Result<AsyncResponse<R>, FluvioError> {
         let partition = self.metadata.partitions().lookup_by_key(replica).await?;
 
@@ -144,16 +160,23 @@ impl SpuPool {
         let mut client_lock = self.spu_clients.lock().await;
 
         if let Some(spu_socket) = client_lock.get_mut(&leader_id) {
-            return spu_socket.create_stream(request).await;
+            return spu_socket.create_stream(request, queue_size).await;
         }
 
         let mut spu_socket = self.connect_to_leader(leader_id).await?;
-        let stream = spu_socket.create_stream(request).await?;
+        let stream = spu_socket.create_stream(request, queue_size).await?;
         client_lock.insert(leader_id, spu_socket);
 
         Ok(stream)
     }
 
+    /// drop cached connection to spu, next request will reconnect
+    pub(crate) async fn remove_spu_socket(&self, leader: SpuId) {
+        if self.spu_clients.lock().await.remove(&leader).is_some() {
+            debug!("removed connection to spu: {}", leader);
+        }
+    }
+
     pub fn shutdown(&mut self) {
         self.metadata.shutdown();
     }
@@ -21,6 +21,8 @@ mod context {
     use crate::metadata::store::DualEpochMap;
     use crate::metadata::store::MetadataStoreObject;
     use crate::metadata::spu::SpuSpec;
+    use crate::metadata::partition::PartitionSpec;
+    use crate::metadata::topic::TopicSpec;
     use crate::metadata::core::MetadataItem;
 
     pub(crate) type CacheMetadataStoreObject<S> = MetadataStoreObject<S, AlwaysNewContext>;
@@ -87,7 +89,6 @@ mod context {
         }
 
         /// look up object by index key
-        #[allow(unused)]
         pub async fn try_lookup_by_key(
             &self,
             key: &S::IndexKey,
@@ -186,4 +187,37 @@ mod context {
             .await
         }
     }
+
+    impl StoreContext<TopicSpec> {
+        /// number of partitions of a topic as set by its spec, wait until topic is known
+        /// error if topic has no partitions
+        pub async fn topic_partition_count(&self, topic: &str) -> Result<i32, FluvioError> {
+            let topic_spec = self
+                .lookup_by_key(&topic.to_owned())
+                .await
+                .map_err(|_| FluvioError::TopicNotFound(topic.to_owned()))?;
+
+            match topic_spec.spec.partitions() {
+                Some(count) if count > 0 => Ok(count),
+                _ => Err(FluvioError::Other(format!(
+                    "topic: {} has no partitions",
+                    topic
+                ))),
+            }
+        }
+    }
+
+    impl StoreContext<PartitionSpec> {
+        /// partitions of a topic which are currently known, in order
+        pub async fn topic_partitions(&self, topic: &str) -> Vec<i32> {
+            let read_lock = self.store.read().await;
+            let mut partitions: Vec<i32> = read_lock
+                .values()
+                .filter(|partition| partition.key.topic == topic)
+                .map(|partition| partition.key.partition)
+                .collect();
+            partitions.sort_unstable();
+            partitions
+        }
+    }
 }
@@ -7,6 +7,7 @@ use fluvio_socket::FlvSocketError;
 
 use crate::metadata::spu::SpuSpec;
 use crate::metadata::partition::PartitionSpec;
+use crate::metadata::topic::TopicSpec;
 
 use super::controller::{MetadataSyncController, SimpleEvent};
 use super::StoreContext;
@@ -17,6 +18,7 @@ pub struct MetadataStores {
     shutdown: Arc<SimpleEvent>,
     spus: StoreContext<SpuSpec>,
     partitions: StoreContext<PartitionSpec>,
+    topics: StoreContext<TopicSpec>,
 }
 
 impl MetadataStores {
@@ -26,10 +28,12 @@ impl MetadataStores {
             shutdown: SimpleEvent::shared(),
             spus: StoreContext::new(),
             partitions: StoreContext::new(),
+            topics: StoreContext::new(),
         };
 
         store.start_watch_for_spu(socket).await?;
         store.start_watch_for_partition(socket).await?;
+        store.start_watch_for_topic(socket).await?;
 
         Ok(store)
     }
@@ -42,6 +46,10 @@ impl MetadataStores {
         &self.partitions
     }
 
+    pub fn topics(&self) -> &StoreContext<TopicSpec> {
+        &self.topics
+    }
+
     pub fn shutdown(&mut self) {
         self.shutdown.notify();
     }
@@ -88,4 +96,25 @@ impl MetadataStores {
 
         Ok(())
     }
+
+    pub async fn start_watch_for_topic(
+        &self,
+        socket: &AllMultiplexerSocket,
+    ) -> Result<(), FlvSocketError> {
+        use dataplane::api::RequestMessage;
+        use fluvio_sc_schema::objects::WatchRequest;
+
+        debug!("start watch for topic");
+
+        let req_msg = RequestMessage::new_request(WatchRequest::Topic(0));
+        let async_response = socket.create_stream(req_msg, 10).await?;
+
+        MetadataSyncController::<TopicSpec>::start(
+            self.topics.clone(),
+            async_response,
+            self.shutdown.clone(),
+        );
+
+        Ok(())
+    }
 }
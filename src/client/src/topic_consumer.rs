@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures_util::stream::{BoxStream, SelectAll, Stream, StreamExt};
+use tracing::debug;
+
+use crate::FluvioError;
+use crate::consumer::{PartitionConsumer, ConsumerConfig, Record};
+use crate::offset::Offset;
+use crate::spu::SpuPool;
+
+type RecordStream = BoxStream<'static, Result<Record, FluvioError>>;
+
+/// Starting offsets of partitions consumed by a [`TopicConsumer`]
+///
+/// Partitions without their own offset start at the default offset.
+///
+/// # Example
+///
+/// ```
+/// # use fluvio::{Offset, TopicOffsets, FluvioError};
+/// # fn do_create_offsets() -> Result<(), FluvioError> {
+/// let offsets = TopicOffsets::new(Offset::end())
+///     .with_partition(0, Offset::absolute(120)?)
+///     .with_partition(1, Offset::beginning());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`TopicConsumer`]: struct.TopicConsumer.html
+#[derive(Debug, Clone)]
+pub struct TopicOffsets {
+    default: Offset,
+    partitions: HashMap<i32, Offset>,
+}
+
+impl TopicOffsets {
+    /// Every partition starts at the `default` offset
+    pub fn new(default: Offset) -> Self {
+        Self {
+            default,
+            partitions: HashMap::new(),
+        }
+    }
+
+    /// Start the partition at the given offset instead of the default
+    pub fn with_partition(mut self, partition: i32, offset: Offset) -> Self {
+        self.partitions.insert(partition, offset);
+        self
+    }
+
+    fn partition_offset(&self, partition: i32) -> Offset {
+        self.partitions
+            .get(&partition)
+            .unwrap_or(&self.default)
+            .clone()
+    }
+}
+
+impl From<Offset> for TopicOffsets {
+    fn from(default: Offset) -> Self {
+        Self::new(default)
+    }
+}
+
+/// An interface for consuming events from all partitions of a topic
+///
+/// The partitions are discovered from the metadata of the topic. Each partition
+/// is streamed from its leader, and streams to partitions with the same leader
+/// share a connection. Records of all partitions are merged into one stream,
+/// [`Record::partition`] tells which partition a record belongs to. Records
+/// are in order within a partition, but not across partitions.
+///
+/// Partitions which are added to the topic while streaming are picked up
+/// automatically.
+///
+/// # Example
+///
+/// ```no_run
+/// # use fluvio::{Fluvio, Offset, FluvioError};
+/// # mod futures {
+/// #     pub use futures_util::stream::StreamExt;
+/// # }
+/// # async fn do_consume_topic(fluvio: &Fluvio) -> Result<(), FluvioError> {
+/// use futures::StreamExt;
+/// let consumer = fluvio.topic_consumer("my-topic").await?;
+/// let mut stream = consumer.stream(Offset::beginning()).await?;
+/// while let Some(Ok(record)) = stream.next().await {
+///     println!("partition: {}, offset: {}", record.partition(), record.offset());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Record::partition`]: struct.Record.html#method.partition
+pub struct TopicConsumer {
+    topic: String,
+    pool: Arc<SpuPool>,
+}
+
+impl TopicConsumer {
+    pub(crate) fn new(topic: String, pool: Arc<SpuPool>) -> Self {
+        Self { topic, pool }
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Continuously streams events of every partition, starting at the same offset
+    pub async fn stream(
+        &self,
+        offset: Offset,
+    ) -> Result<impl Stream<Item = Result<Record, FluvioError>>, FluvioError> {
+        self.stream_with_config(offset, ConsumerConfig::default())
+            .await
+    }
+
+    /// Continuously streams events of every partition from their starting offsets
+    ///
+    /// Partitions added after the stream has started are read from the
+    /// beginning, so none of their records are missed.
+    ///
+    /// A partition stream ends after an error which can't be recovered, as described
+    /// in [`PartitionConsumer::stream_with_config`]. Other partitions keep streaming,
+    /// and the stream ends once every partition stream has ended.
+    ///
+    /// [`PartitionConsumer::stream_with_config`]: struct.PartitionConsumer.html#method.stream_with_config
+    pub async fn stream_with_config<O: Into<TopicOffsets>>(
+        &self,
+        offsets: O,
+        config: ConsumerConfig,
+    ) -> Result<impl Stream<Item = Result<Record, FluvioError>>, FluvioError> {
+        let offsets = offsets.into();
+        // wait until topic is known
+        self.pool
+            .metadata()
+            .topics()
+            .topic_partition_count(&self.topic)
+            .await?;
+
+        let mut stream = TopicStream {
+            topic: self.topic.clone(),
+            pool: self.pool.clone(),
+            config,
+            partitions: vec![],
+            records: SelectAll::new(),
+        };
+        for partition in self.topic_partitions().await {
+            let offset = offsets.partition_offset(partition);
+            stream.add_partition(partition, offset).await?;
+        }
+
+        Ok(Box::pin(futures_util::stream::unfold(
+            stream,
+            |mut stream| async move { stream.next_record().await.map(|record| (record, stream)) },
+        )))
+    }
+
+    async fn topic_partitions(&self) -> Vec<i32> {
+        self.pool
+            .metadata()
+            .partitions()
+            .topic_partitions(&self.topic)
+            .await
+    }
+}
+
+/// merged streams of partitions which have been discovered
+struct TopicStream {
+    topic: String,
+    pool: Arc<SpuPool>,
+    config: ConsumerConfig,
+    partitions: Vec<i32>,
+    records: SelectAll<RecordStream>,
+}
+
+impl TopicStream {
+    async fn add_partition(&mut self, partition: i32, offset: Offset) -> Result<(), FluvioError> {
+        debug!(
+            "streaming topic: {} partition: {} from: {:?}",
+            self.topic, partition, offset
+        );
+        let consumer = PartitionConsumer::new(self.topic.clone(), partition, self.pool.clone());
+        let stream = consumer
+            .stream_with_config(offset, self.config.clone())
+            .await?;
+        self.records.push(stream.boxed());
+        self.partitions.push(partition);
+        Ok(())
+    }
+
+    /// stream partitions of topic which are not streamed yet
+    async fn add_new_partitions(&mut self) -> Result<(), FluvioError> {
+        let partitions = self
+            .pool
+            .metadata()
+            .partitions()
+            .topic_partitions(&self.topic)
+            .await;
+        for partition in partitions {
+            if !self.partitions.contains(&partition) {
+                self.add_partition(partition, Offset::beginning()).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn next_record(&mut self) -> Option<Result<Record, FluvioError>> {
+        use tokio::select;
+
+        loop {
+            if self.records.is_empty() {
+                return None;
+            }
+
+            let metadata_listener = self.pool.metadata().partitions().listen();
+            select! {
+                record = self.records.next() => return record,
+                _ = metadata_listener => {
+                    if let Err(err) = self.add_new_partitions().await {
+                        return Some(Err(err));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::offset::Offset;
+
+    use super::TopicOffsets;
+
+    #[test]
+    fn test_topic_offsets() {
+        let offsets = TopicOffsets::new(Offset::end()).with_partition(2, Offset::beginning());
+        assert_eq!(offsets.partition_offset(0), Offset::end());
+        assert_eq!(offsets.partition_offset(2), Offset::beginning());
+    }
+}
@@ -8,6 +8,7 @@ use crate::partition::ReplicaKey;
 use crate::core::*;
 use crate::store::MetadataStoreObject;
 use crate::partition::PartitionSpec;
+use crate::topic::TopicConfig;
 use super::store::*;
 
 #[derive(Decode, Encode, Debug, PartialEq, Clone, Default)]
@@ -15,6 +16,8 @@ pub struct Replica {
     pub id: ReplicaKey,
     pub leader: SpuId,
     pub replicas: Vec<SpuId>,
+    #[fluvio(min_version = 1)]
+    pub config: TopicConfig,
 }
 
 impl Replica {
@@ -23,8 +26,14 @@ impl Replica {
             id,
             leader,
             replicas,
+            config: TopicConfig::default(),
         }
     }
+
+    pub fn with_config(mut self, config: TopicConfig) -> Self {
+        self.config = config;
+        self
+    }
 }
 
 impl<C> From<PartitionMetadata<C>> for Replica
@@ -37,6 +46,7 @@ where
             id: inner.key,
             leader: inner.spec.leader,
             replicas: inner.spec.replicas,
+            config: inner.spec.config,
         }
     }
 }
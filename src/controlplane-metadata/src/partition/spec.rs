@@ -7,6 +7,8 @@
 use fluvio_types::SpuId;
 use dataplane::derive::{Decode, Encode};
 
+use crate::topic::TopicConfig;
+
 /// Spec for Partition
 /// Each partition has replicas spread among SPU
 /// one of replica is leader which is duplicated in the leader field
@@ -19,6 +21,13 @@ use dataplane::derive::{Decode, Encode};
 pub struct PartitionSpec {
     pub leader: SpuId,
     pub replicas: Vec<SpuId>,
+    /// config of topic which partition belongs to
+    #[cfg_attr(
+        feature = "use_serde",
+        serde(default, skip_serializing_if = "TopicConfig::is_empty")
+    )]
+    #[fluvio(min_version = 1)]
+    pub config: TopicConfig,
 }
 
 impl std::default::Default for PartitionSpec {
@@ -26,13 +35,23 @@ impl std::default::Default for PartitionSpec {
         PartitionSpec {
             leader: 0,
             replicas: Vec::default(),
+            config: TopicConfig::default(),
         }
     }
 }
 
 impl PartitionSpec {
     pub fn new(leader: SpuId, replicas: Vec<SpuId>) -> Self {
-        Self { leader, replicas }
+        Self {
+            leader,
+            replicas,
+            config: TopicConfig::default(),
+        }
+    }
+
+    pub fn with_config(mut self, config: TopicConfig) -> Self {
+        self.config = config;
+        self
     }
 
     pub fn has_spu(&self, spu: &SpuId) -> bool {
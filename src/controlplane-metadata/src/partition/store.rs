@@ -158,6 +158,7 @@ where
             .into_iter()
             .map(|(replica_key, partition_spec)| {
                 Replica::new(replica_key, partition_spec.leader, partition_spec.replicas)
+                    .with_config(partition_spec.config)
             })
             .collect();
         debug!(
@@ -0,0 +1,326 @@
+#![allow(clippy::assign_op_pattern)]
+
+//!
+//! # Topic Config
+//!
+//! Per topic settings which are applied to every partition of the topic.
+//! Settings are stored as key/value pairs, so new settings can be added
+//! without changing the protocol or the CRD.
+//!
+use std::io::{Error, ErrorKind};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use dataplane::derive::{Decode, Encode};
+
+/// max age of log segment in seconds before it is removed
+pub const RETENTION_SECONDS: &str = "retention.seconds";
+/// max size of partition in bytes before oldest segments are removed
+pub const RETENTION_BYTES: &str = "retention.bytes";
+/// max size of log segment in bytes
+pub const SEGMENT_BYTES: &str = "segment.bytes";
+/// max size of batch of records which can be produced
+pub const MAX_MESSAGE_BYTES: &str = "max.message.bytes";
+/// compression of records: none, gzip, snappy, lz4 or zstd
+pub const COMPRESSION_TYPE: &str = "compression.type";
+/// how old records are cleaned up: delete or compact
+pub const CLEANUP_POLICY: &str = "cleanup.policy";
+/// which time is used as record timestamp: CreateTime or LogAppendTime
+pub const TIMESTAMP_TYPE: &str = "message.timestamp.type";
+/// sync records to disk once this many records are written, takes precedence over flush.ms
+pub const FLUSH_MESSAGES: &str = "flush.messages";
+/// sync records to disk when they are written this many milliseconds after last sync
+pub const FLUSH_MS: &str = "flush.ms";
+/// sync records to disk before they are committed: true or false
+pub const FLUSH_BEFORE_COMMIT: &str = "flush.before.commit";
+/// how long tombstones of compacted topic are kept in seconds
+pub const DELETE_RETENTION_SECONDS: &str = "delete.retention.seconds";
+/// min number of replicas, including leader, which must have records produced with acks all
+pub const MIN_INSYNC_REPLICAS: &str = "min.insync.replicas";
+
+pub const COMPRESSION_TYPES: [&str; 5] = ["none", "gzip", "snappy", "lz4", "zstd"];
+pub const CLEANUP_POLICIES: [&str; 2] = ["delete", "compact"];
+pub const TIMESTAMP_TYPES: [&str; 2] = ["CreateTime", "LogAppendTime"];
+
+const KNOWN_CONFIGS: [&str; 12] = [
+    RETENTION_SECONDS,
+    RETENTION_BYTES,
+    SEGMENT_BYTES,
+    MAX_MESSAGE_BYTES,
+    COMPRESSION_TYPE,
+    CLEANUP_POLICY,
+    TIMESTAMP_TYPE,
+    FLUSH_MESSAGES,
+    FLUSH_MS,
+    FLUSH_BEFORE_COMMIT,
+    DELETE_RETENTION_SECONDS,
+    MIN_INSYNC_REPLICAS,
+];
+
+/// Topic configuration, SPU defaults are used for settings which are not set
+#[derive(Decode, Encode, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "use_serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct TopicConfig {
+    configs: BTreeMap<String, String>,
+}
+
+impl fmt::Display for TopicConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let configs: Vec<String> = self
+            .configs
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        write!(f, "{}", configs.join(","))
+    }
+}
+
+impl From<BTreeMap<String, String>> for TopicConfig {
+    fn from(configs: BTreeMap<String, String>) -> Self {
+        Self { configs }
+    }
+}
+
+impl TopicConfig {
+    pub fn is_empty(&self) -> bool {
+        self.configs.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.configs.get(key).map(|value| value.as_str())
+    }
+
+    /// set config value, return previous value
+    pub fn set<K, V>(&mut self, key: K, value: V) -> Option<String>
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.configs.insert(key.into(), value.into())
+    }
+
+    pub fn with<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.set(key, value);
+        self
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.configs.iter()
+    }
+
+    pub fn retention_seconds(&self) -> Option<u32> {
+        self.get(RETENTION_SECONDS)?.parse().ok()
+    }
+
+    pub fn retention_bytes(&self) -> Option<u64> {
+        self.get(RETENTION_BYTES)?.parse().ok()
+    }
+
+    pub fn segment_bytes(&self) -> Option<u32> {
+        self.get(SEGMENT_BYTES)?.parse().ok()
+    }
+
+    pub fn max_message_bytes(&self) -> Option<u32> {
+        self.get(MAX_MESSAGE_BYTES)?.parse().ok()
+    }
+
+    pub fn compression_type(&self) -> Option<&str> {
+        self.get(COMPRESSION_TYPE)
+    }
+
+    pub fn cleanup_policy(&self) -> Option<&str> {
+        self.get(CLEANUP_POLICY)
+    }
+
+    pub fn timestamp_type(&self) -> Option<&str> {
+        self.get(TIMESTAMP_TYPE)
+    }
+
+    pub fn flush_messages(&self) -> Option<u32> {
+        self.get(FLUSH_MESSAGES)?.parse().ok()
+    }
+
+    pub fn flush_ms(&self) -> Option<u32> {
+        self.get(FLUSH_MS)?.parse().ok()
+    }
+
+    pub fn flush_before_commit(&self) -> Option<bool> {
+        self.get(FLUSH_BEFORE_COMMIT)?.parse().ok()
+    }
+
+    pub fn delete_retention_seconds(&self) -> Option<u32> {
+        self.get(DELETE_RETENTION_SECONDS)?.parse().ok()
+    }
+
+    pub fn min_insync_replicas(&self) -> Option<u16> {
+        self.get(MIN_INSYNC_REPLICAS)?.parse().ok()
+    }
+
+    /// check if only last record of each key is kept
+    pub fn is_compacted(&self) -> bool {
+        self.cleanup_policy() == Some("compact")
+    }
+
+    /// check if leader sets timestamp of records when they are written
+    pub fn is_log_append_time(&self) -> bool {
+        self.timestamp_type() == Some("LogAppendTime")
+    }
+
+    // -----------------------------------
+    //  Parameter validation
+    // -----------------------------------
+
+    /// Validate that all configs are known and have valid values
+    pub fn validate(&self) -> Result<(), Error> {
+        for (key, value) in self.configs.iter() {
+            let valid = match key.as_str() {
+                RETENTION_SECONDS
+                | SEGMENT_BYTES
+                | MAX_MESSAGE_BYTES
+                | FLUSH_MESSAGES
+                | FLUSH_MS
+                | DELETE_RETENTION_SECONDS => {
+                    matches!(value.parse::<u32>(), Ok(number) if number > 0)
+                }
+                RETENTION_BYTES => matches!(value.parse::<u64>(), Ok(number) if number > 0),
+                MIN_INSYNC_REPLICAS => matches!(value.parse::<u16>(), Ok(number) if number > 0),
+                COMPRESSION_TYPE => COMPRESSION_TYPES.contains(&value.as_str()),
+                CLEANUP_POLICY => CLEANUP_POLICIES.contains(&value.as_str()),
+                TIMESTAMP_TYPE => TIMESTAMP_TYPES.contains(&value.as_str()),
+                FLUSH_BEFORE_COMMIT => value.parse::<bool>().is_ok(),
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "unknown topic config: {}, valid configs are: {}",
+                            key,
+                            KNOWN_CONFIGS.join(", ")
+                        ),
+                    ))
+                }
+            };
+
+            if !valid {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid value: {} for topic config: {}", value, key),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// -----------------------------------
+// Unit Tests
+// -----------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    use dataplane::core::{Decoder, Encoder};
+
+    #[test]
+    fn test_typed_configs() {
+        let config = TopicConfig::default()
+            .with(RETENTION_SECONDS, "3600")
+            .with(SEGMENT_BYTES, "1000")
+            .with(COMPRESSION_TYPE, "gzip");
+
+        assert_eq!(config.retention_seconds(), Some(3600));
+        assert_eq!(config.segment_bytes(), Some(1000));
+        assert_eq!(config.compression_type(), Some("gzip"));
+        assert_eq!(config.retention_bytes(), None);
+        assert_eq!(config.cleanup_policy(), None);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_configs() {
+        assert!(TopicConfig::default().validate().is_ok());
+        assert!(TopicConfig::default()
+            .with("retention.ms", "100")
+            .validate()
+            .is_err());
+        assert!(TopicConfig::default()
+            .with(RETENTION_SECONDS, "-1")
+            .validate()
+            .is_err());
+        assert!(TopicConfig::default()
+            .with(RETENTION_BYTES, "10000000000")
+            .validate()
+            .is_ok());
+        assert!(TopicConfig::default()
+            .with(CLEANUP_POLICY, "remove")
+            .validate()
+            .is_err());
+        assert!(TopicConfig::default()
+            .with(TIMESTAMP_TYPE, "LogAppendTime")
+            .validate()
+            .is_ok());
+        assert!(TopicConfig::default()
+            .with(TIMESTAMP_TYPE, "AppendTime")
+            .validate()
+            .is_err());
+        assert!(TopicConfig::default()
+            .with(FLUSH_MESSAGES, "1")
+            .with(FLUSH_BEFORE_COMMIT, "true")
+            .validate()
+            .is_ok());
+        assert!(TopicConfig::default()
+            .with(FLUSH_MS, "0")
+            .validate()
+            .is_err());
+        assert!(TopicConfig::default()
+            .with(FLUSH_BEFORE_COMMIT, "yes")
+            .validate()
+            .is_err());
+        assert!(TopicConfig::default()
+            .with(CLEANUP_POLICY, "compact")
+            .with(DELETE_RETENTION_SECONDS, "3600")
+            .validate()
+            .is_ok());
+        assert!(TopicConfig::default()
+            .with(DELETE_RETENTION_SECONDS, "0")
+            .validate()
+            .is_err());
+        assert!(TopicConfig::default()
+            .with(MIN_INSYNC_REPLICAS, "2")
+            .validate()
+            .is_ok());
+        assert!(TopicConfig::default()
+            .with(MIN_INSYNC_REPLICAS, "0")
+            .validate()
+            .is_err());
+        assert!(TopicConfig::default()
+            .with(MIN_INSYNC_REPLICAS, "70000")
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_encode_decode_topic_config() {
+        let config = TopicConfig::default()
+            .with(RETENTION_SECONDS, "60")
+            .with(CLEANUP_POLICY, "compact");
+
+        let mut dest = vec![];
+        config.encode(&mut dest, 0).expect("encode");
+        assert_eq!(dest.len(), config.write_size(0));
+
+        let mut decoded = TopicConfig::default();
+        decoded.decode(&mut Cursor::new(&dest), 0).expect("decode");
+        assert_eq!(decoded, config);
+    }
+}
@@ -7,6 +7,8 @@
 //!  * Assigned
 //!  * Computed
 //!
+//! Both types carry topic config next to their replica parameters.
+//!
 use std::io::{Error, ErrorKind};
 use std::collections::BTreeMap;
 
@@ -19,6 +21,8 @@ use dataplane::bytes::{Buf, BufMut};
 use dataplane::derive::{Decode, Encode};
 use dataplane::core::{Decoder, Encoder};
 
+use super::TopicConfig;
+
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(
     feature = "use_serde",
@@ -33,9 +37,13 @@ pub enum TopicSpec {
 impl std::fmt::Display for TopicSpec {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            TopicSpec::Assigned(partition_map) => write!(f, "assigned::{}", partition_map),
-            TopicSpec::Computed(param) => write!(f, "computed::({})", param),
+            TopicSpec::Assigned(partition_map) => write!(f, "assigned::{}", partition_map)?,
+            TopicSpec::Computed(param) => write!(f, "computed::({})", param)?,
+        }
+        if !self.config().is_empty() {
+            write!(f, " config: {}", self.config())?;
         }
+        Ok(())
     }
 }
 
@@ -64,6 +72,21 @@ impl TopicSpec {
         TopicSpec::Computed((partitions, replication, ignore_rack.unwrap_or(false)).into())
     }
 
+    pub fn with_config(mut self, config: TopicConfig) -> Self {
+        match &mut self {
+            TopicSpec::Assigned(partition_map) => partition_map.config = config,
+            TopicSpec::Computed(param) => param.config = config,
+        }
+        self
+    }
+
+    pub fn config(&self) -> &TopicConfig {
+        match self {
+            TopicSpec::Assigned(partition_map) => &partition_map.config,
+            TopicSpec::Computed(param) => &param.config,
+        }
+    }
+
     pub fn is_computed(&self) -> bool {
         match self {
             TopicSpec::Computed(_) => true,
@@ -274,6 +297,12 @@ pub struct TopicReplicaParam {
     pub replication_factor: ReplicationFactor,
     #[cfg_attr(feature = "use_serde", serde(skip_serializing_if = "bool::clone"))]
     pub ignore_rack_assignment: IgnoreRackAssignment,
+    #[cfg_attr(
+        feature = "use_serde",
+        serde(default, skip_serializing_if = "TopicConfig::is_empty")
+    )]
+    #[fluvio(min_version = 1)]
+    pub config: TopicConfig,
 }
 
 #[allow(dead_code)]
@@ -291,6 +320,7 @@ impl TopicReplicaParam {
             partitions,
             replication_factor,
             ignore_rack_assignment,
+            config: TopicConfig::default(),
         }
     }
 }
@@ -317,11 +347,20 @@ impl std::fmt::Display for TopicReplicaParam {
 #[cfg_attr(feature = "use_serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartitionMaps {
     maps: Vec<PartitionMap>,
+    #[cfg_attr(
+        feature = "use_serde",
+        serde(default, skip_serializing_if = "TopicConfig::is_empty")
+    )]
+    #[fluvio(min_version = 1)]
+    config: TopicConfig,
 }
 
 impl From<Vec<PartitionMap>> for PartitionMaps {
     fn from(maps: Vec<PartitionMap>) -> Self {
-        Self { maps }
+        Self {
+            maps,
+            config: TopicConfig::default(),
+        }
     }
 }
 
@@ -792,6 +831,43 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn test_encode_decode_topic_spec_with_config() {
+        let topic_spec = TopicSpec::new_computed(2, 3, Some(true))
+            .with_config(TopicConfig::default().with("retention.seconds", "60"));
+        let mut dest = vec![];
+
+        // test encode
+        let result = topic_spec.encode(&mut dest, 1);
+        assert!(result.is_ok());
+
+        let expected_dest = [
+            0x01, // type
+            0x00, 0x00, 0x00, 0x02, // partition cnt
+            0x00, 0x00, 0x00, 0x03, // replica cnt
+            0x01, // ignore_rack_assignment
+            0x00, 0x01, // config cnt
+            0x00, 0x11, // key len
+            b'r', b'e', b't', b'e', b'n', b't', b'i', b'o', b'n', b'.', b's', b'e', b'c', b'o',
+            b'n', b'd', b's', // key
+            0x00, 0x02, // value len
+            b'6', b'0', // value
+        ];
+        assert_eq!(dest, expected_dest);
+
+        // test decode
+        let mut topic_spec_decoded = TopicSpec::default();
+        let result = topic_spec_decoded.decode(&mut Cursor::new(&expected_dest), 1);
+        assert!(result.is_ok());
+        assert_eq!(topic_spec_decoded, topic_spec);
+        assert_eq!(topic_spec_decoded.config().retention_seconds(), Some(60));
+
+        // config is not encoded for version 0
+        let mut dest = vec![];
+        assert!(topic_spec.encode(&mut dest, 0).is_ok());
+        assert_eq!(dest, expected_dest[0..10]);
+    }
+
     #[test]
     fn test_partition_map_str() {
         // Test multiple
@@ -1,8 +1,12 @@
 use std::io::Error;
+use std::io::Cursor;
 use std::mem::size_of;
 use std::fmt::Debug;
+use std::borrow::Cow;
+use std::convert::TryFrom;
 
 use log::trace;
+use once_cell::sync::OnceCell;
 
 use crate::core::bytes::Buf;
 use crate::core::bytes::BufMut;
@@ -15,7 +19,10 @@ use crate::derive::Encode;
 
 use crate::Offset;
 use crate::Size;
+use crate::Timestamp;
 use crate::record::DefaultRecord;
+use crate::compression::Compression;
+use crate::compression::COMPRESSION_CODEC_MASK;
 
 pub type DefaultBatchRecords = Vec<DefaultRecord>;
 pub type DefaultBatch = Batch<DefaultBatchRecords>;
@@ -29,11 +36,14 @@ pub trait BatchRecords: Default + Debug + Encoder + Decoder {
 
 impl BatchRecords for DefaultBatchRecords {}
 
+/// bit of batch attributes which is set when timestamp is log append time
+pub const TIMESTAMP_TYPE_MASK: i16 = 0x08;
+
 /// size of the offset and length
 pub const BATCH_PREAMBLE_SIZE: usize = size_of::<Offset>()     // Offset
         + size_of::<i32>(); // i32
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct Batch<R>
 where
     R: BatchRecords,
@@ -41,7 +51,9 @@ where
     pub base_offset: Offset,
     pub batch_len: i32, // only for decoding
     pub header: BatchHeader,
-    pub records: R,
+    records: R,
+    /// compressed records as received or first encoded, so records are compressed only once
+    compressed_records: OnceCell<Vec<u8>>,
 }
 
 impl<R> Batch<R>
@@ -56,6 +68,20 @@ where
         &self.header
     }
 
+    pub fn records(&self) -> &R {
+        &self.records
+    }
+
+    /// records which can be modified, batch will be encoded from them
+    pub fn records_mut(&mut self) -> &mut R {
+        self.compressed_records = OnceCell::new();
+        &mut self.records
+    }
+
+    pub fn into_records(self) -> R {
+        self.records
+    }
+
     pub fn get_base_offset(&self) -> Offset {
         self.base_offset
     }
@@ -82,6 +108,22 @@ where
         self.get_header().last_offset_delta as Size
     }
 
+    /// compression codec used for records
+    pub fn compression(&self) -> Result<Compression, Error> {
+        self.header.compression()
+    }
+
+    /// set compression codec used to encode records
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.header.set_compression(compression);
+        self.compressed_records = OnceCell::new();
+    }
+
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.set_compression(compression);
+        self
+    }
+
     /// decode from buf stored in the file
     /// read all excluding records
     pub fn decode_from_file_buf<T>(&mut self, src: &mut T, version: Version) -> Result<(), Error>
@@ -94,6 +136,100 @@ where
         self.header.decode(src, version)?;
         Ok(())
     }
+
+    /// decode records after header, decompressing them if needed
+    pub fn decode_records<T>(&mut self, src: &mut T, version: Version) -> Result<(), Error>
+    where
+        T: Buf,
+    {
+        let compression = self.compression()?;
+        if compression.is_none() {
+            return self.records.decode(src, version);
+        }
+
+        let len = (self.batch_len as usize).saturating_sub(BATCH_HEADER_SIZE);
+        if src.remaining() < len {
+            return Err(Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!(
+                    "not enough bytes for compressed records, expected: {}, remaining: {}",
+                    len,
+                    src.remaining()
+                ),
+            ));
+        }
+        let mut compressed = vec![0u8; len];
+        src.copy_to_slice(&mut compressed);
+        trace!("decompressing {} bytes of records: {}", len, compression);
+        let records = compression.decompress(&compressed)?;
+        self.records.decode(&mut Cursor::new(records), version)?;
+        self.compressed_records = OnceCell::from(compressed);
+        Ok(())
+    }
+
+    /// records as written after header, compressed with batch codec
+    ///
+    /// compressed records are kept, so size of batch and its encoding compress records once
+    fn records_bytes(&self, version: Version) -> Result<Cow<'_, [u8]>, Error> {
+        if let Some(compressed) = self.compressed_records.get() {
+            return Ok(Cow::Borrowed(compressed));
+        }
+
+        let compression = self.compression()?;
+        let encode_records = || -> Result<Vec<u8>, Error> {
+            let mut records = Vec::with_capacity(self.records.write_size(version));
+            self.records.encode(&mut records, version)?;
+            Ok(records)
+        };
+        if compression.is_none() {
+            Ok(Cow::Owned(encode_records()?))
+        } else {
+            let compressed = self
+                .compressed_records
+                .get_or_try_init(|| compression.compress(&encode_records()?))?;
+            Ok(Cow::Borrowed(compressed))
+        }
+    }
+
+    /// header fields after crc and records, these are covered by crc
+    fn crc_content(&self, records: &[u8], version: Version) -> Result<Vec<u8>, Error> {
+        let mut out: Vec<u8> = Vec::with_capacity(BATCH_HEADER_SIZE + records.len());
+        let buf = &mut out;
+        self.header.attributes.encode(buf, version)?;
+        self.header.last_offset_delta.encode(buf, version)?;
+        self.header.first_timestamp.encode(buf, version)?;
+        self.header.max_time_stamp.encode(buf, version)?;
+        self.header.producer_id.encode(buf, version)?;
+        self.header.producer_epoch.encode(buf, version)?;
+        self.header.first_sequence.encode(buf, version)?;
+        buf.extend_from_slice(records);
+        Ok(out)
+    }
+
+    /// compute crc of batch as it would be encoded
+    pub fn compute_crc(&self) -> Result<u32, Error> {
+        let records = self.records_bytes(0)?;
+        Ok(crc32c::crc32c(&self.crc_content(&records, 0)?))
+    }
+
+    /// check if crc in header of decoded batch matches its content
+    pub fn verify_crc(&self) -> bool {
+        match self.compute_crc() {
+            Ok(crc) => crc == self.header.crc,
+            Err(_) => false,
+        }
+    }
+
+    /// size of records as written after header
+    fn records_write_size(&self, version: Version) -> usize {
+        match self.compression() {
+            Ok(Compression::None) => self.records.write_size(version),
+            _ => match self.records_bytes(version) {
+                Ok(bytes) => bytes.len(),
+                Err(_) => self.records.write_size(version),
+            },
+        }
+    }
 }
 
 impl Batch<DefaultBatchRecords> {
@@ -106,8 +242,43 @@ impl Batch<DefaultBatchRecords> {
         };
         record.preamble.set_offset_delta(last_offset_delta);
         self.header.last_offset_delta = last_offset_delta as i32;
+        self.compressed_records = OnceCell::new();
         self.records.push(record)
     }
+
+    /// add new record created at timestamp, timestamp is stored relative to first record
+    pub fn add_record_with_timestamp(&mut self, mut record: DefaultRecord, timestamp: Timestamp) {
+        if self.records.is_empty() {
+            self.header.first_timestamp = timestamp;
+            self.header.max_time_stamp = timestamp;
+        } else if timestamp > self.header.max_time_stamp {
+            self.header.max_time_stamp = timestamp;
+        }
+        record
+            .preamble
+            .set_timestamp_delta(timestamp - self.header.first_timestamp);
+        self.add_record(record);
+    }
+
+    /// keep only records matching predicate, offsets of remaining records are not changed
+    pub fn retain_records<F>(&mut self, predicate: F)
+    where
+        F: FnMut(&DefaultRecord) -> bool,
+    {
+        self.records.retain(predicate);
+        self.compressed_records = OnceCell::new();
+    }
+
+    /// timestamp of record in this batch
+    ///
+    /// with log append time, all records have time when batch was written
+    pub fn get_record_timestamp(&self, record: &DefaultRecord) -> Timestamp {
+        if self.header.is_log_append_time() {
+            self.header.max_time_stamp
+        } else {
+            self.header.first_timestamp + record.preamble.get_timestamp_delta()
+        }
+    }
 }
 
 impl<R> Decoder for Batch<R>
@@ -120,7 +291,7 @@ where
     {
         trace!("decoding batch");
         self.decode_from_file_buf(src, version)?;
-        self.records.decode(src, version)?;
+        self.decode_records(src, version)?;
         Ok(())
     }
 }
@@ -131,7 +302,7 @@ where
     R: BatchRecords,
 {
     fn write_size(&self, version: Version) -> usize {
-        BATCH_PREAMBLE_SIZE + BATCH_HEADER_SIZE + self.records.write_size(version)
+        BATCH_PREAMBLE_SIZE + BATCH_HEADER_SIZE + self.records_write_size(version)
     }
 
     fn encode<T>(&self, dest: &mut T, version: Version) -> Result<(), Error>
@@ -139,25 +310,16 @@ where
         T: BufMut,
     {
         trace!("Encoding Batch");
+        let records = self.records_bytes(version)?;
         self.base_offset.encode(dest, version)?;
-        let batch_len: i32 = (BATCH_HEADER_SIZE + self.records.write_size(version)) as i32;
+        let batch_len: i32 = (BATCH_HEADER_SIZE + records.len()) as i32;
         batch_len.encode(dest, version)?;
 
         // encode parts of header
         self.header.partition_leader_epoch.encode(dest, version)?;
         self.header.magic.encode(dest, version)?;
 
-        let mut out: Vec<u8> = Vec::new();
-        let buf = &mut out;
-        self.header.attributes.encode(buf, version)?;
-        self.header.last_offset_delta.encode(buf, version)?;
-        self.header.first_timestamp.encode(buf, version)?;
-        self.header.max_time_stamp.encode(buf, version)?;
-        self.header.producer_id.encode(buf, version)?;
-        self.header.producer_epoch.encode(buf, version)?;
-        self.header.first_sequence.encode(buf, version)?;
-        self.records.encode(buf, version)?;
-
+        let out = self.crc_content(&records, version)?;
         let crc = crc32c::crc32c(&out);
         crc.encode(dest, version)?;
         dest.put_slice(&out);
@@ -165,7 +327,7 @@ where
     }
 }
 
-#[derive(Debug, Decode, Encode)]
+#[derive(Debug, Decode, Encode, Clone)]
 pub struct BatchHeader {
     pub partition_leader_epoch: i32,
     pub magic: i8,
@@ -179,6 +341,28 @@ pub struct BatchHeader {
     pub first_sequence: i32,
 }
 
+impl BatchHeader {
+    /// compression codec stored in attributes
+    pub fn compression(&self) -> Result<Compression, Error> {
+        Compression::try_from(self.attributes & COMPRESSION_CODEC_MASK)
+    }
+
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.attributes = (self.attributes & !COMPRESSION_CODEC_MASK) | compression as i16;
+    }
+
+    /// check if timestamp is set by the leader when batch is written
+    pub fn is_log_append_time(&self) -> bool {
+        self.attributes & TIMESTAMP_TYPE_MASK != 0
+    }
+
+    /// use log append time as timestamp of all records in the batch
+    pub fn set_log_append_time(&mut self, timestamp: Timestamp) {
+        self.attributes |= TIMESTAMP_TYPE_MASK;
+        self.max_time_stamp = timestamp;
+    }
+}
+
 impl Default for BatchHeader {
     fn default() -> Self {
         BatchHeader {
@@ -218,6 +402,7 @@ mod test {
     use crate::core::Encoder;
     use crate::record::DefaultRecord;
     use crate::batch::DefaultBatch;
+    use crate::compression::Compression;
     use super::BatchHeader;
     use super::BATCH_HEADER_SIZE;
 
@@ -231,7 +416,7 @@ mod test {
     fn test_encode_and_decode_batch() -> Result<(), IoError> {
         let record: DefaultRecord = vec![0x74, 0x65, 0x73, 0x74].into();
         let mut batch = DefaultBatch::default();
-        batch.records.push(record);
+        batch.records_mut().push(record);
         batch.header.first_timestamp = 1555478494747;
         batch.header.max_time_stamp = 1555478494747;
 
@@ -241,7 +426,7 @@ mod test {
         let batch = DefaultBatch::decode_from(&mut Cursor::new(bytes), 0)?;
         println!("batch: {:#?}", batch);
 
-        let decoded_record = batch.records.get(0).unwrap();
+        let decoded_record = batch.records().first().unwrap();
         println!("record crc: {}", batch.header.crc);
         assert_eq!(batch.header.crc, 1514417201);
         let b = decoded_record.value.inner_value_ref().as_deref().unwrap();
@@ -250,6 +435,117 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_encode_and_decode_compressed_batch() -> Result<(), IoError> {
+        let codecs = [
+            Compression::Gzip,
+            Compression::Snappy,
+            Compression::Lz4,
+            Compression::Zstd,
+        ];
+        for codec in codecs.iter() {
+            let mut batch = DefaultBatch::default().with_compression(*codec);
+            for _ in 0..10 {
+                batch.add_record(DefaultRecord::from(b"hello world".to_vec()));
+            }
+
+            let bytes = batch.as_bytes(0)?;
+            assert_eq!(bytes.len(), batch.write_size(0));
+
+            let decoded = DefaultBatch::decode_from(&mut Cursor::new(&bytes), 0)?;
+            assert_eq!(decoded.compression()?, *codec);
+            assert_eq!(decoded.records.len(), 10);
+            let value = decoded.records[9]
+                .value
+                .inner_value_ref()
+                .as_deref()
+                .unwrap();
+            assert_eq!(value, b"hello world");
+
+            // decoded batch is encoded again with same compressed bytes
+            assert_eq!(decoded.as_bytes(0)?, bytes);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compressed_records_cached() -> Result<(), IoError> {
+        let mut batch = DefaultBatch::default().with_compression(Compression::Gzip);
+        batch.add_record(DefaultRecord::from(b"hello world".to_vec()));
+        assert!(batch.compressed_records.get().is_none());
+
+        // records compressed for size are reused by encoding
+        let size = batch.write_size(0);
+        let compressed = batch.compressed_records.get().expect("compressed").clone();
+        let bytes = batch.as_bytes(0)?;
+        assert_eq!(bytes.len(), size);
+        assert_eq!(batch.compressed_records.get(), Some(&compressed));
+
+        // adding record compresses records again
+        batch.add_record(DefaultRecord::from(b"hello world".to_vec()));
+        assert!(batch.compressed_records.get().is_none());
+        assert!(batch.write_size(0) > 0);
+        assert_ne!(batch.compressed_records.get(), Some(&compressed));
+
+        // modified records are compressed again
+        let compressed = batch.compressed_records.get().expect("compressed").clone();
+        batch.records_mut()[0].value = b"modified".to_vec().into();
+        assert!(batch.compressed_records.get().is_none());
+        let decoded = DefaultBatch::decode_from(&mut Cursor::new(batch.as_bytes(0)?), 0)?;
+        assert_ne!(decoded.compressed_records.get(), Some(&compressed));
+        let value = decoded.records()[0].value.inner_value_ref().as_deref();
+        assert_eq!(value, Some(&b"modified"[..]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_crc() -> Result<(), IoError> {
+        let mut batch = DefaultBatch::default();
+        batch.add_record(DefaultRecord::from(b"hello world".to_vec()));
+        let mut bytes = batch.as_bytes(0)?.to_vec();
+
+        let decoded = DefaultBatch::decode_from(&mut Cursor::new(&bytes), 0)?;
+        assert!(decoded.verify_crc());
+
+        // flip last byte of record value, it is followed by header count
+        let pos = bytes.len() - 2;
+        bytes[pos] ^= 0xff;
+        let corrupted = DefaultBatch::decode_from(&mut Cursor::new(&bytes), 0)?;
+        assert!(!corrupted.verify_crc());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_timestamps() -> Result<(), IoError> {
+        let mut batch = DefaultBatch::default();
+        batch.add_record_with_timestamp(DefaultRecord::default(), 1000);
+        batch.add_record_with_timestamp(DefaultRecord::default(), 1500);
+        batch.add_record_with_timestamp(DefaultRecord::default(), 1200);
+        assert_eq!(batch.header.first_timestamp, 1000);
+        assert_eq!(batch.header.max_time_stamp, 1500);
+
+        let bytes = batch.as_bytes(0)?;
+        let mut batch = DefaultBatch::decode_from(&mut Cursor::new(bytes), 0)?;
+        let timestamps: Vec<i64> = batch
+            .records
+            .iter()
+            .map(|record| batch.get_record_timestamp(record))
+            .collect();
+        assert_eq!(timestamps, vec![1000, 1500, 1200]);
+
+        batch.header.set_log_append_time(2000);
+        assert!(batch.header.is_log_append_time());
+        assert!(batch
+            .records
+            .iter()
+            .all(|record| batch.get_record_timestamp(record) == 2000));
+
+        Ok(())
+    }
+
     /*  raw batch encoded
 
     0000   02 00 00 00 45 00 00 c7 00 00 40 00 40 06 00 00
@@ -278,7 +574,7 @@ mod test {
         assert_eq!(
             batch
                 .records
-                .get(0)
+                .first()
                 .expect("index 0 should exists")
                 .get_offset_delta(),
             0
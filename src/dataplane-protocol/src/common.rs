@@ -1,11 +1,22 @@
 use std::convert::TryFrom;
 use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::derive::Decode;
 use crate::derive::Encode;
 
 pub type Offset = i64;
 pub type Size = u32;
+/// milliseconds since unix epoch
+pub type Timestamp = i64;
+
+/// current time as timestamp
+pub fn current_timestamp() -> Timestamp {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as Timestamp)
+        .unwrap_or_default()
+}
 
 #[derive(Debug, Encode, Decode, Clone)]
 #[fluvio(encode_discriminant)]
@@ -0,0 +1,160 @@
+//!
+//! # Batch Compression
+//!
+//! Records of a batch can be compressed as a whole. Codec is stored in the lowest
+//! 3 bits of the batch attributes, using same codec ids as Kafka.
+//!
+use std::io::{Error, ErrorKind, Read, Write};
+use std::fmt;
+use std::str::FromStr;
+use std::convert::TryFrom;
+
+/// bits of batch attributes which holds the compression codec
+pub const COMPRESSION_CODEC_MASK: i16 = 0x07;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i16)]
+pub enum Compression {
+    None = 0,
+    Gzip = 1,
+    Snappy = 2,
+    Lz4 = 3,
+    Zstd = 4,
+}
+
+impl TryFrom<i16> for Compression {
+    type Error = Error;
+
+    fn try_from(codec: i16) -> Result<Self, Self::Error> {
+        match codec {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::Gzip),
+            2 => Ok(Compression::Snappy),
+            3 => Ok(Compression::Lz4),
+            4 => Ok(Compression::Zstd),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unknown compression codec: {}", codec),
+            )),
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Compression::None),
+            "gzip" => Ok(Compression::Gzip),
+            "snappy" => Ok(Compression::Snappy),
+            "lz4" => Ok(Compression::Lz4),
+            "zstd" => Ok(Compression::Zstd),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("unknown compression type: {}", s),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Snappy => "snappy",
+            Compression::Lz4 => "lz4",
+            Compression::Zstd => "zstd",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Compression {
+    pub fn is_none(&self) -> bool {
+        *self == Compression::None
+    }
+
+    /// compress bytes using this codec
+    pub fn compress(&self, src: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            Compression::None => Ok(src.to_vec()),
+            Compression::Gzip => {
+                use flate2::write::GzEncoder;
+
+                let mut encoder = GzEncoder::new(vec![], flate2::Compression::default());
+                encoder.write_all(src)?;
+                encoder.finish()
+            }
+            Compression::Snappy => Ok(snap::raw::Encoder::new().compress_vec(src)?),
+            Compression::Lz4 => {
+                let mut encoder = lz4::EncoderBuilder::new().build(vec![])?;
+                encoder.write_all(src)?;
+                let (out, result) = encoder.finish();
+                result.map(|_| out)
+            }
+            Compression::Zstd => zstd::stream::encode_all(src, 0),
+        }
+    }
+
+    /// decompress bytes which were compressed with this codec
+    pub fn decompress(&self, src: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut out = vec![];
+        match self {
+            Compression::None => out.extend_from_slice(src),
+            Compression::Gzip => {
+                flate2::read::GzDecoder::new(src).read_to_end(&mut out)?;
+            }
+            Compression::Snappy => {
+                out = snap::raw::Decoder::new().decompress_vec(src)?;
+            }
+            Compression::Lz4 => {
+                lz4::Decoder::new(src)?.read_to_end(&mut out)?;
+            }
+            Compression::Zstd => {
+                out = zstd::stream::decode_all(src)?;
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::convert::TryFrom;
+
+    use super::Compression;
+
+    const CODECS: [Compression; 5] = [
+        Compression::None,
+        Compression::Gzip,
+        Compression::Snappy,
+        Compression::Lz4,
+        Compression::Zstd,
+    ];
+
+    #[test]
+    fn test_compress_and_decompress() {
+        let data = b"hello world, hello world, hello world, hello world".repeat(10);
+        for codec in CODECS.iter() {
+            let compressed = codec.compress(&data).expect("compress");
+            if !codec.is_none() {
+                assert!(compressed.len() < data.len(), "{} not compressed", codec);
+            }
+            let decompressed = codec.decompress(&compressed).expect("decompress");
+            assert_eq!(decompressed, data, "{} round trip", codec);
+        }
+    }
+
+    #[test]
+    fn test_codec_names() {
+        for codec in CODECS.iter() {
+            assert_eq!(*codec, codec.to_string().parse().expect("parse"));
+            assert_eq!(*codec, Compression::try_from(*codec as i16).expect("codec"));
+        }
+        assert!("brotli".parse::<Compression>().is_err());
+        assert!(Compression::try_from(5).is_err());
+    }
+}
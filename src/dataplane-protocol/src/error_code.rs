@@ -22,8 +22,14 @@ pub enum ErrorCode {
     None = 0,
 
     OffsetOutOfRange = 1,
+    CorruptMessage = 2,
     NotLeaderForPartition = 6,
+    NotEnoughReplicas = 19,
+    NotEnoughReplicasAfterAppend = 20,
     PermissionDenied = 13,
+    UnsupportedVersion = 35,
+    OutOfOrderSequenceNumber = 45,
+    InvalidProducerEpoch = 47,
     StorageError = 56,
 
     // Spu errors
@@ -43,6 +49,21 @@ pub enum ErrorCode {
     // Partition errors
     PartitionPendingInitialization = 3000,
     PartitionNotLeader = 3001,
+
+    // Consumer group errors
+    ConsumerGroupError = 4000,
+    NotCoordinator = 4001,
+    UnknownMemberId = 4002,
+    IllegalGeneration = 4003,
+    InconsistentGroupTopic = 4004,
+    InvalidGroupId = 4005,
+
+    // SmartStream errors
+    SmartStreamInvalidModule = 5000,
+    SmartStreamRuntimeError = 5001,
+
+    // Stream fetch errors
+    StreamFetchNotFound = 6000,
 }
 
 impl Default for ErrorCode {
@@ -66,6 +87,24 @@ impl ErrorCode {
     pub fn is_error(&self) -> bool {
         !self.is_ok()
     }
+
+    /// check if request may succeed when it is sent again, for example
+    /// after partition leader has moved to another SPU
+    ///
+    /// `NotEnoughReplicasAfterAppend` is not retriable, records have been
+    /// written and sending them again would duplicate them
+    pub fn is_retriable(&self) -> bool {
+        matches!(
+            self,
+            ErrorCode::NotLeaderForPartition
+                | ErrorCode::NotEnoughReplicas
+                | ErrorCode::SpuOffline
+                | ErrorCode::TopicPendingInitialization
+                | ErrorCode::PartitionPendingInitialization
+                | ErrorCode::PartitionNotLeader
+                | ErrorCode::NotCoordinator
+        )
+    }
 }
 
 // -----------------------------------
@@ -84,4 +123,11 @@ mod test {
         let erro_code: ErrorCode = (1001 as i16).try_into().expect("convert");
         assert_eq!(erro_code, ErrorCode::SpuRegisterationFailed);
     }
+
+    #[test]
+    fn test_error_code_retriable() {
+        assert!(ErrorCode::NotLeaderForPartition.is_retriable());
+        assert!(!ErrorCode::StorageError.is_retriable());
+        assert!(!ErrorCode::None.is_retriable());
+    }
 }
@@ -5,6 +5,7 @@ mod common;
 mod error_code;
 
 pub mod batch;
+pub mod compression;
 pub mod record;
 pub mod fetch;
 pub mod produce;
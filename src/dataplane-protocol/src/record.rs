@@ -22,6 +22,7 @@ use crate::derive::Encode;
 
 use crate::batch::DefaultBatch;
 use crate::Offset;
+use crate::Timestamp;
 
 pub type DefaultRecord = Record<DefaultAsyncBuffer>;
 
@@ -34,7 +35,7 @@ pub trait AsyncBuffer {
 
 pub trait Records {}
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct DefaultAsyncBuffer(Option<Vec<u8>>);
 
 impl DefaultAsyncBuffer {
@@ -170,6 +171,11 @@ impl RecordSet {
         self.batches.push(batch);
         self
     }
+
+    /// first batch whose crc doesn't match its content
+    pub fn find_corrupt_batch(&self) -> Option<&DefaultBatch> {
+        self.batches.iter().find(|batch| !batch.verify_crc())
+    }
 }
 
 impl Decoder for RecordSet {
@@ -252,8 +258,8 @@ impl Encoder for RecordSet {
     }
 }
 
-#[derive(Decode, Encode, Default, Debug)]
-pub struct RecordHeader {
+#[derive(Decode, Encode, Default, Debug, Clone)]
+pub struct RecordPreamble {
     attributes: i8,
     #[varint]
     timestamp_delta: i64,
@@ -261,21 +267,91 @@ pub struct RecordHeader {
     offset_delta: Offset,
 }
 
-impl RecordHeader {
+impl RecordPreamble {
     pub fn set_offset_delta(&mut self, delta: Offset) {
         self.offset_delta = delta;
     }
+
+    pub fn get_timestamp_delta(&self) -> Timestamp {
+        self.timestamp_delta
+    }
+
+    pub fn set_timestamp_delta(&mut self, delta: Timestamp) {
+        self.timestamp_delta = delta;
+    }
 }
 
-#[derive(Default)]
+/// Key/value pair attached to record, encoded same as Kafka record header
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct RecordHeader {
+    pub key: String,
+    pub value: Option<Vec<u8>>,
+}
+
+impl RecordHeader {
+    pub fn new<K, V>(key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<Vec<u8>>,
+    {
+        Self {
+            key: key.into(),
+            value: Some(value.into()),
+        }
+    }
+}
+
+impl Encoder for RecordHeader {
+    fn write_size(&self, _version: Version) -> usize {
+        let key_len = self.key.len() as i64;
+        key_len.var_write_size() + self.key.len() + self.value.var_write_size()
+    }
+
+    fn encode<T>(&self, dest: &mut T, _version: Version) -> Result<(), Error>
+    where
+        T: BufMut,
+    {
+        let key_len = self.key.len() as i64;
+        key_len.encode_varint(dest)?;
+        if dest.remaining_mut() < self.key.len() {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "not enough capacity for header key",
+            ));
+        }
+        dest.put_slice(self.key.as_bytes());
+        self.value.encode_varint(dest)?;
+        Ok(())
+    }
+}
+
+impl Decoder for RecordHeader {
+    fn decode<T>(&mut self, src: &mut T, _version: Version) -> Result<(), Error>
+    where
+        T: Buf,
+    {
+        let mut key: Vec<u8> = Vec::new();
+        key.decode_varint(src)?;
+        self.key = String::from_utf8(key).map_err(|err| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("header key is not utf8: {}", err),
+            )
+        })?;
+        self.value.decode_varint(src)?;
+        Ok(())
+    }
+}
+
+#[derive(Default, Clone)]
 pub struct Record<B>
 where
     B: Default,
 {
-    pub preamble: RecordHeader,
+    pub preamble: RecordPreamble,
     pub key: B,
     pub value: B,
-    pub headers: i64,
+    pub headers: Vec<RecordHeader>,
 }
 
 impl<B> Record<B>
@@ -293,6 +369,14 @@ where
     pub fn value(self) -> B {
         self.value
     }
+
+    pub fn get_headers(&self) -> &[RecordHeader] {
+        &self.headers
+    }
+
+    pub fn add_header(&mut self, header: RecordHeader) {
+        self.headers.push(header);
+    }
 }
 
 impl<B> Debug for Record<B>
@@ -345,10 +429,16 @@ where
     B: Encoder + Default,
 {
     fn write_size(&self, version: Version) -> usize {
+        let header_count = self.headers.len() as i64;
         let inner_size = self.preamble.write_size(version)
             + self.key.write_size(version)
             + self.value.write_size(version)
-            + self.headers.var_write_size();
+            + header_count.var_write_size()
+            + self
+                .headers
+                .iter()
+                .map(|header| header.write_size(version))
+                .sum::<usize>();
         let len: i64 = inner_size as i64;
         len.var_write_size() + inner_size
     }
@@ -361,7 +451,11 @@ where
         self.preamble.encode(&mut out, version)?;
         self.key.encode(&mut out, version)?;
         self.value.encode(&mut out, version)?;
-        self.headers.encode_varint(&mut out)?;
+        let header_count = self.headers.len() as i64;
+        header_count.encode_varint(&mut out)?;
+        for header in &self.headers {
+            header.encode(&mut out, version)?;
+        }
         let len: i64 = out.len() as i64;
         trace!("record encode as {} bytes", len);
         len.encode_varint(dest)?;
@@ -394,7 +488,14 @@ where
         trace!("offset delta: {}", self.preamble.offset_delta);
         self.key.decode(src, version)?;
         self.value.decode(src, version)?;
-        self.headers.decode_varint(src)?;
+        let mut header_count: i64 = 0;
+        header_count.decode_varint(src)?;
+        self.headers.clear();
+        for _ in 0..header_count {
+            let mut header = RecordHeader::default();
+            header.decode(src, version)?;
+            self.headers.push(header);
+        }
 
         Ok(())
     }
@@ -409,6 +510,7 @@ mod test {
     use crate::core::Decoder;
     use crate::core::Encoder;
     use crate::record::DefaultRecord;
+    use crate::record::RecordHeader;
 
     #[test]
     fn test_decode_encode_record() -> Result<(), IoError> {
@@ -437,6 +539,39 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_decode_encode_record_headers() -> Result<(), IoError> {
+        let data = [
+            0x26, // record length of 19
+            0x00, // attributes
+            0x00, // timestamp
+            0x00, // offset delta
+            0x01, // null key
+            0x02, 0x61, // value 'a'
+            0x04, // 2 headers
+            0x06, 0x66, 0x6f, 0x6f, // key 'foo'
+            0x06, 0x62, 0x61, 0x72, // value 'bar'
+            0x04, 0x6e, 0x69, // key 'ni'
+            0x01, // null value
+        ];
+
+        let record = DefaultRecord::decode_from(&mut Cursor::new(&data), 0)?;
+        assert_eq!(
+            record.get_headers(),
+            &[
+                RecordHeader::new("foo", "bar"),
+                RecordHeader {
+                    key: "ni".to_owned(),
+                    value: None
+                }
+            ]
+        );
+        assert_eq!(record.write_size(0), data.len());
+        assert_eq!(record.as_bytes(0)?.as_ref(), &data[..]);
+
+        Ok(())
+    }
+
     /// test decoding of records when one of the batch was truncated
     #[test]
     fn test_decode_batch_truncation() {
@@ -447,7 +582,7 @@ mod test {
         fn create_batch() -> DefaultBatch {
             let record: DefaultRecord = vec![0x74, 0x65, 0x73, 0x74].into();
             let mut batch = DefaultBatch::default();
-            batch.records.push(record);
+            batch.records_mut().push(record);
             batch
         }
 
@@ -480,8 +615,10 @@ mod test {
 
 mod file {
 
+    use std::any::Any;
     use std::fmt;
     use std::io::Error as IoError;
+    use std::sync::Arc;
 
     use log::trace;
     use bytes::BufMut;
@@ -495,8 +632,22 @@ mod file {
     use crate::store::FileWrite;
     use crate::store::StoreValue;
 
-    #[derive(Default, Debug)]
-    pub struct FileRecordSet(AsyncFileSlice);
+    /// slice of records in file.
+    /// Owner of file can be attached, so file is not closed while slice is still to be sent
+    #[derive(Default)]
+    pub struct FileRecordSet {
+        slice: AsyncFileSlice,
+        owner: Option<Arc<dyn Any + Send + Sync>>,
+    }
+
+    impl fmt::Debug for FileRecordSet {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.debug_struct("FileRecordSet")
+                .field("slice", &self.slice)
+                .field("owned", &self.owner.is_some())
+                .finish()
+        }
+    }
 
     impl fmt::Display for FileRecordSet {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -505,22 +656,30 @@ mod file {
     }
 
     impl FileRecordSet {
+        /// slice which keeps `owner` of file alive until record set is dropped
+        pub fn with_owner(slice: AsyncFileSlice, owner: Arc<dyn Any + Send + Sync>) -> Self {
+            Self {
+                slice,
+                owner: Some(owner),
+            }
+        }
+
         pub fn position(&self) -> u64 {
-            self.0.position()
+            self.slice.position()
         }
 
         pub fn len(&self) -> usize {
-            self.0.len() as usize
+            self.slice.len() as usize
         }
 
         pub fn raw_slice(&self) -> AsyncFileSlice {
-            self.0.clone()
+            self.slice.clone()
         }
     }
 
     impl From<AsyncFileSlice> for FileRecordSet {
         fn from(slice: AsyncFileSlice) -> Self {
-            Self(slice)
+            Self { slice, owner: None }
         }
     }
 
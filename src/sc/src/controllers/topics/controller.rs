@@ -4,8 +4,12 @@
 //! Reconcile Topics
 
 use tracing::debug;
+use tracing::info;
 
 use fluvio_future::task::spawn;
+use fluvio_types::defaults::{
+    CONSUMER_OFFSETS_PARTITIONS, CONSUMER_OFFSETS_REPLICATION, CONSUMER_OFFSETS_TOPIC,
+};
 
 use crate::core::SharedContext;
 use crate::stores::topic::*;
@@ -14,6 +18,7 @@ use crate::stores::partition::*;
 use crate::stores::*;
 
 use super::reducer::TopicReducer;
+use super::TopicWSAction;
 
 #[derive(Debug)]
 pub struct TopicController {
@@ -57,6 +62,7 @@ impl TopicController {
         let mut timer = sleep(Duration::from_secs(60));
 
         loop {
+            self.ensure_consumer_offsets_topic().await;
             self.sync_topics().await;
 
             select! {
@@ -70,11 +76,45 @@ impl TopicController {
                 },
                 _ = self.topics.status_listen() => {
                     debug!("detected topic status changes, topic syncing");
+                },
+                _ = self.spus.status_listen() => {
+                    debug!("detected spu status changes");
                 }
             }
         }
     }
 
+    /// create compacted topic which stores committed offsets of consumer groups
+    /// once topics are loaded and there are online spus
+    async fn ensure_consumer_offsets_topic(&self) {
+        let topics = self.topics.store();
+        if topics.epoch().await <= topics.init_epoch().spec_epoch()
+            || topics.contains_key(CONSUMER_OFFSETS_TOPIC).await
+        {
+            return;
+        }
+
+        let online_spus = self.spus.store().online_spu_count().await;
+        if online_spus == 0 {
+            debug!("no online spus, delaying creation of consumer offsets topic");
+            return;
+        }
+
+        let replication = CONSUMER_OFFSETS_REPLICATION.min(online_spus);
+        info!(
+            "creating consumer offsets topic: {} with replication: {}",
+            CONSUMER_OFFSETS_TOPIC, replication
+        );
+        let spec = TopicSpec::new_computed(CONSUMER_OFFSETS_PARTITIONS, replication, None)
+            .with_config(TopicConfig::default().with(CLEANUP_POLICY, "compact"));
+        self.topics
+            .send_action(TopicWSAction::UpdateSpec((
+                CONSUMER_OFFSETS_TOPIC.to_owned(),
+                spec,
+            )))
+            .await;
+    }
+
     /// get list of topics we need to check
     async fn sync_topics(&mut self) {
         debug!("syncing topics");
@@ -26,12 +26,12 @@ pub async fn handle_api_versions_request(
     ));
     response.api_keys.push(make_version_key(
         AdminPublicApiKey::List,
-        ListRequest::DEFAULT_API_VERSION,
+        0,
         ListRequest::DEFAULT_API_VERSION,
     ));
     response.api_keys.push(make_version_key(
         AdminPublicApiKey::Watch,
-        WatchRequest::DEFAULT_API_VERSION,
+        0,
         WatchRequest::DEFAULT_API_VERSION,
     ));
 
@@ -1,6 +1,7 @@
 use std::io::Error as IoError;
 
-use dataplane::api::{RequestMessage, ResponseMessage};
+use dataplane::ErrorCode;
+use dataplane::api::{Request, RequestMessage, ResponseMessage};
 use fluvio_sc_schema::Status;
 use fluvio_sc_schema::objects::{CreateRequest, AllCreatableSpec};
 use fluvio_auth::AuthContext;
@@ -17,6 +18,20 @@ pub async fn handle_create_request<AC: AuthContext>(
     let dry_run = req.dry_run;
     let name = req.name;
 
+    // older versions don't carry topic config, so topic would silently be created without it
+    if header.api_version() < CreateRequest::DEFAULT_API_VERSION {
+        let status = Status::new(
+            name,
+            ErrorCode::UnsupportedVersion,
+            Some(format!(
+                "create request version: {} is not supported, min version is: {}",
+                header.api_version(),
+                CreateRequest::DEFAULT_API_VERSION
+            )),
+        );
+        return Ok(ResponseMessage::from_header(&header, status));
+    }
+
     let status = match req.spec {
         AllCreatableSpec::Topic(topic) => {
             super::topic::handle_create_topics_request(name, dry_run, topic, auth_context).await?
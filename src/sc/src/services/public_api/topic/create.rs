@@ -86,6 +86,14 @@ async fn validate_topic_request(name: &str, topic_spec: &TopicSpec, metadata: &C
         );
     }
 
+    if let Err(err) = topic_spec.config().validate() {
+        return Status::new(
+            name.to_string(),
+            ErrorCode::TopicInvalidConfiguration,
+            Some(err.to_string()),
+        );
+    }
+
     match topic_spec {
         TopicSpec::Computed(param) => {
             let next_state = validate_computed_topic_parameters(param);
@@ -16,6 +16,7 @@ use fluvio_controlplane_metadata::core::Spec;
 use fluvio_controlplane_metadata::store::Epoch;
 use fluvio_controlplane_metadata::partition::PartitionSpec;
 use fluvio_controlplane_metadata::spu::SpuSpec;
+use fluvio_controlplane_metadata::topic::TopicSpec;
 
 use crate::services::auth::AuthServiceContext;
 use crate::stores::StoreContext;
@@ -33,7 +34,13 @@ pub fn handle_watch_request<T, AC>(
     let (header, req) = request.get_header_request();
 
     match req {
-        WatchRequest::Topic(_) => unimplemented!(),
+        WatchRequest::Topic(epoch) => WatchController::<T, TopicSpec>::update(
+            epoch,
+            sink,
+            end_event,
+            auth_ctx.global_ctx.topics().clone(),
+            header,
+        ),
         WatchRequest::Spu(epoch) => WatchController::<T, SpuSpec>::update(
             epoch,
             sink,
@@ -22,6 +22,10 @@ use super::fetch_offset::FetchOffsetsRequest;
 use super::versions::ApiVersionsRequest;
 use super::register_replica::RegisterSyncReplicaRequest;
 use super::stream_fetch::FileStreamFetchRequest;
+use super::consumer_group::{JoinGroupRequest, HeartbeatRequest, LeaveGroupRequest};
+use super::consumer_group::{OffsetCommitRequest, OffsetFetchRequest};
+use super::init_producer_id::InitProducerIdRequest;
+use super::stream_credit::StreamFetchCreditRequest;
 
 /// Request to Spu Server
 #[derive(Debug, Encode)]
@@ -35,6 +39,19 @@ pub enum SpuServerRequest {
     FetchOffsetsRequest(RequestMessage<FetchOffsetsRequest>),
     FileStreamFetchRequest(RequestMessage<FileStreamFetchRequest>),
     RegisterSyncReplicaRequest(RequestMessage<RegisterSyncReplicaRequest>),
+
+    // consumer groups
+    JoinGroupRequest(RequestMessage<JoinGroupRequest>),
+    HeartbeatRequest(RequestMessage<HeartbeatRequest>),
+    LeaveGroupRequest(RequestMessage<LeaveGroupRequest>),
+    OffsetCommitRequest(RequestMessage<OffsetCommitRequest>),
+    OffsetFetchRequest(RequestMessage<OffsetFetchRequest>),
+
+    // idempotent producer
+    InitProducerIdRequest(RequestMessage<InitProducerIdRequest>),
+
+    // stream fetch flow control
+    StreamFetchCreditRequest(RequestMessage<StreamFetchCreditRequest>),
 }
 
 impl Default for SpuServerRequest {
@@ -67,6 +84,17 @@ impl ApiMessage for SpuServerRequest {
                 api_decode!(Self, RegisterSyncReplicaRequest, src, header)
             }
             SpuServerApiKey::StreamFetch => api_decode!(Self, FileStreamFetchRequest, src, header),
+            SpuServerApiKey::JoinGroup => api_decode!(Self, JoinGroupRequest, src, header),
+            SpuServerApiKey::Heartbeat => api_decode!(Self, HeartbeatRequest, src, header),
+            SpuServerApiKey::LeaveGroup => api_decode!(Self, LeaveGroupRequest, src, header),
+            SpuServerApiKey::OffsetCommit => api_decode!(Self, OffsetCommitRequest, src, header),
+            SpuServerApiKey::OffsetFetch => api_decode!(Self, OffsetFetchRequest, src, header),
+            SpuServerApiKey::InitProducerId => {
+                api_decode!(Self, InitProducerIdRequest, src, header)
+            }
+            SpuServerApiKey::StreamFetchCredit => {
+                api_decode!(Self, StreamFetchCreditRequest, src, header)
+            }
         }
     }
 }
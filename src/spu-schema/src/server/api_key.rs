@@ -16,6 +16,19 @@ pub enum SpuServerApiKey {
     FetchOffsets = 1002,
     StreamFetch = 1003,
     RegisterSyncReplicaRequest = 1004,
+
+    // Consumer groups
+    JoinGroup = 1005,
+    Heartbeat = 1006,
+    LeaveGroup = 1007,
+    OffsetCommit = 1008,
+    OffsetFetch = 1009,
+
+    // Idempotent producer
+    InitProducerId = 1010,
+
+    // Stream fetch flow control
+    StreamFetchCredit = 1011,
 }
 
 impl Default for SpuServerApiKey {
@@ -0,0 +1,183 @@
+//!
+//! # Consumer Groups
+//!
+//! APIs for consumer group membership and committed offsets.
+//! Group is coordinated by leader SPU of its partition of the consumer offsets topic,
+//! which also stores committed offsets of the group.
+//! Members join the group and send heartbeats, partitions of the topic are assigned
+//! across members and re-assigned whenever members join or leave.
+//! Re-assignment starts a new generation in which members first revoke their partitions.
+//! Once every member has confirmed the generation in a heartbeat, partitions of the new
+//! generation are handed out, so a partition is never consumed by two members at once.
+//!
+use dataplane::api::Request;
+use dataplane::derive::Decode;
+use dataplane::derive::Encode;
+use dataplane::Offset;
+use dataplane::ErrorCode;
+use fluvio_types::defaults::CONSUMER_OFFSETS_PARTITIONS;
+
+use super::SpuServerApiKey;
+
+/// partition of consumer offsets topic which coordinates the group
+///
+/// hash must be same for every client and SPU, so std hasher is not used
+pub fn coordinator_partition(group: &str) -> i32 {
+    // FNV-1a
+    let hash = group.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    });
+    (hash % CONSUMER_OFFSETS_PARTITIONS as u32) as i32
+}
+
+// -----------------------------------
+// JoinGroupRequest
+// -----------------------------------
+
+/// Join consumer group, existing member can re-join with its member id
+#[derive(Decode, Encode, Default, Debug)]
+pub struct JoinGroupRequest {
+    pub group: String,
+    pub topic: String,
+    /// number of partitions of topic known to member
+    pub partitions: i32,
+    /// empty for new member
+    pub member_id: String,
+    /// member is removed if no heartbeat is received within timeout
+    pub session_timeout_ms: u32,
+}
+
+impl Request for JoinGroupRequest {
+    const API_KEY: u16 = SpuServerApiKey::JoinGroup as u16;
+    const DEFAULT_API_VERSION: i16 = 0;
+    type Response = JoinGroupResponse;
+}
+
+#[derive(Encode, Decode, Default, Debug)]
+pub struct JoinGroupResponse {
+    pub error_code: ErrorCode,
+    pub member_id: String,
+    pub assignment: GroupAssignment,
+}
+
+/// partitions assigned to member for a group generation
+#[derive(Encode, Decode, Default, Debug, Clone, PartialEq)]
+pub struct GroupAssignment {
+    /// incremented whenever partitions are re-assigned
+    pub generation: i32,
+    pub partitions: Vec<i32>,
+    /// members must revoke their partitions and confirm generation,
+    /// partitions are empty until every member has confirmed
+    pub rebalancing: bool,
+}
+
+// -----------------------------------
+// HeartbeatRequest
+// -----------------------------------
+
+/// keep membership alive, returns current assignment of member
+#[derive(Decode, Encode, Default, Debug)]
+pub struct HeartbeatRequest {
+    pub group: String,
+    pub member_id: String,
+    /// generation of assignment member is consuming, confirms revoke of earlier generations
+    pub generation: i32,
+}
+
+impl Request for HeartbeatRequest {
+    const API_KEY: u16 = SpuServerApiKey::Heartbeat as u16;
+    const DEFAULT_API_VERSION: i16 = 0;
+    type Response = HeartbeatResponse;
+}
+
+#[derive(Encode, Decode, Default, Debug)]
+pub struct HeartbeatResponse {
+    pub error_code: ErrorCode,
+    pub assignment: GroupAssignment,
+}
+
+// -----------------------------------
+// LeaveGroupRequest
+// -----------------------------------
+
+#[derive(Decode, Encode, Default, Debug)]
+pub struct LeaveGroupRequest {
+    pub group: String,
+    pub member_id: String,
+}
+
+impl Request for LeaveGroupRequest {
+    const API_KEY: u16 = SpuServerApiKey::LeaveGroup as u16;
+    const DEFAULT_API_VERSION: i16 = 0;
+    type Response = LeaveGroupResponse;
+}
+
+#[derive(Encode, Decode, Default, Debug)]
+pub struct LeaveGroupResponse {
+    pub error_code: ErrorCode,
+}
+
+// -----------------------------------
+// OffsetCommitRequest
+// -----------------------------------
+
+/// commit offsets of member, only accepted for current generation
+#[derive(Decode, Encode, Default, Debug)]
+pub struct OffsetCommitRequest {
+    pub group: String,
+    pub member_id: String,
+    pub generation: i32,
+    pub topic: String,
+    pub partitions: Vec<PartitionOffset>,
+}
+
+impl Request for OffsetCommitRequest {
+    const API_KEY: u16 = SpuServerApiKey::OffsetCommit as u16;
+    const DEFAULT_API_VERSION: i16 = 0;
+    type Response = OffsetCommitResponse;
+}
+
+#[derive(Encode, Decode, Default, Debug)]
+pub struct OffsetCommitResponse {
+    pub error_code: ErrorCode,
+}
+
+/// offset of partition, committed offset is offset of next record to consume
+#[derive(Encode, Decode, Default, Debug, Clone, PartialEq)]
+pub struct PartitionOffset {
+    pub partition: i32,
+    pub offset: Offset,
+}
+
+// -----------------------------------
+// OffsetFetchRequest
+// -----------------------------------
+
+/// fetch committed offsets of group
+#[derive(Decode, Encode, Default, Debug)]
+pub struct OffsetFetchRequest {
+    pub group: String,
+    pub topic: String,
+}
+
+impl Request for OffsetFetchRequest {
+    const API_KEY: u16 = SpuServerApiKey::OffsetFetch as u16;
+    const DEFAULT_API_VERSION: i16 = 0;
+    type Response = OffsetFetchResponse;
+}
+
+#[derive(Encode, Decode, Default, Debug)]
+pub struct OffsetFetchResponse {
+    pub error_code: ErrorCode,
+    /// partitions without committed offset are not included
+    pub partitions: Vec<PartitionOffset>,
+}
+
+impl OffsetFetchResponse {
+    pub fn find_partition(&self, partition: i32) -> Option<Offset> {
+        self.partitions
+            .iter()
+            .find(|p| p.partition == partition)
+            .map(|p| p.offset)
+    }
+}
@@ -2,12 +2,14 @@
 //! # Fetch Topic Offsets
 //!
 //! API that allows CLI to fetch topic offsets.
+//! Since version 1, offset of records at or after timestamp can be also resolved.
 use std::fmt;
 
 use dataplane::api::Request;
 use dataplane::derive::Decode;
 use dataplane::derive::Encode;
 use dataplane::PartitionOffset;
+use dataplane::{Offset, Timestamp};
 use dataplane::ReplicaKey;
 
 use crate::errors::ErrorCode;
@@ -26,7 +28,9 @@ pub struct FetchOffsetsRequest {
 
 impl Request for FetchOffsetsRequest {
     const API_KEY: u16 = SpuServerApiKey::FetchOffsets as u16;
-    const DEFAULT_API_VERSION: i16 = 0;
+    const MIN_API_VERSION: i16 = 0;
+    const MAX_API_VERSION: i16 = 1;
+    const DEFAULT_API_VERSION: i16 = 1;
     type Response = FetchOffsetsResponse;
 }
 
@@ -38,6 +42,21 @@ impl FetchOffsetsRequest {
                 name: topic,
                 partitions: vec![FetchOffsetPartition {
                     partition_index: partition,
+                    timestamp: None,
+                }],
+            }],
+        }
+    }
+
+    /// create request with a single topic and partition which also resolves
+    /// offset of first record at or after timestamp
+    pub fn new_with_timestamp(topic: String, partition: i32, timestamp: Timestamp) -> Self {
+        Self {
+            topics: vec![FetchOffsetTopic {
+                name: topic,
+                partitions: vec![FetchOffsetPartition {
+                    partition_index: partition,
+                    timestamp: Some(timestamp),
                 }],
             }],
         }
@@ -57,6 +76,10 @@ pub struct FetchOffsetTopic {
 pub struct FetchOffsetPartition {
     /// The partition index.
     pub partition_index: i32,
+
+    /// Timestamp (ms since epoch) to resolve into offset
+    #[fluvio(min_version = 1)]
+    pub timestamp: Option<Timestamp>,
 }
 
 // -----------------------------------
@@ -107,6 +130,11 @@ pub struct FetchOffsetPartitionResponse {
 
     /// Last readable offset
     pub last_stable_offset: i64,
+
+    /// Offset of first record at or after requested timestamp.
+    /// If all records are older, this is last readable offset.
+    #[fluvio(min_version = 1)]
+    pub timestamp_offset: Option<Offset>,
 }
 
 impl fmt::Display for FetchOffsetPartitionResponse {
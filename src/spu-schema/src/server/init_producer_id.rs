@@ -0,0 +1,30 @@
+//!
+//! # Idempotent Producer Id
+//!
+//! API to allocate producer id. Idempotent producer tags each batch with its producer id
+//! and sequence of the first record, so partition leader can detect batches which were
+//! sent again after a retry.
+//!
+use dataplane::api::Request;
+use dataplane::derive::Decode;
+use dataplane::derive::Encode;
+use dataplane::ErrorCode;
+
+use super::SpuServerApiKey;
+
+/// allocate new producer id, ids are unique across SPUs
+#[derive(Decode, Encode, Default, Debug)]
+pub struct InitProducerIdRequest {}
+
+impl Request for InitProducerIdRequest {
+    const API_KEY: u16 = SpuServerApiKey::InitProducerId as u16;
+    const DEFAULT_API_VERSION: i16 = 0;
+    type Response = InitProducerIdResponse;
+}
+
+#[derive(Encode, Decode, Default, Debug)]
+pub struct InitProducerIdResponse {
+    pub error_code: ErrorCode,
+    pub producer_id: i64,
+    pub producer_epoch: i16,
+}
@@ -4,6 +4,11 @@ pub mod versions;
 pub mod fetch_offset;
 pub mod register_replica;
 pub mod stream_fetch;
+pub mod record_filter;
+pub mod smart_stream;
+pub mod stream_credit;
+pub mod consumer_group;
+pub mod init_producer_id;
 
 pub use self::api_key::*;
 pub use self::api::SpuServerRequest;
@@ -0,0 +1,212 @@
+//!
+//! # Record Filter
+//!
+//! Predicate on records evaluated by SPU before records are streamed back
+//!
+use dataplane::derive::Decode;
+use dataplane::derive::Encode;
+use dataplane::record::DefaultRecord;
+use dataplane::record::RecordSet;
+
+/// What part of record is matched by filter
+#[derive(Encode, Decode, Default, PartialEq, Debug, Clone, Copy)]
+#[fluvio(encode_discriminant)]
+#[repr(u8)]
+pub enum FilterKind {
+    #[default]
+    KeyEquals = 0,
+    KeyPrefix = 1,
+    ValueContains = 2,
+    JsonFieldEquals = 3,
+}
+
+/// Filter on records of stream fetch
+///
+/// Only records which match the filter are sent back to consumer.
+#[derive(Encode, Decode, Default, Debug, Clone, PartialEq)]
+pub struct RecordFilter {
+    pub kind: FilterKind,
+    /// path of JSON field separated by `.`, only used by `JsonFieldEquals`
+    pub field: String,
+    pub value: Vec<u8>,
+}
+
+impl RecordFilter {
+    /// match records whose key is equal to `key`
+    pub fn key_equals<K: Into<Vec<u8>>>(key: K) -> Self {
+        Self {
+            kind: FilterKind::KeyEquals,
+            value: key.into(),
+            ..Default::default()
+        }
+    }
+
+    /// match records whose key starts with `prefix`
+    pub fn key_prefix<K: Into<Vec<u8>>>(prefix: K) -> Self {
+        Self {
+            kind: FilterKind::KeyPrefix,
+            value: prefix.into(),
+            ..Default::default()
+        }
+    }
+
+    /// match records whose value contains `pattern`
+    pub fn value_contains<V: Into<Vec<u8>>>(pattern: V) -> Self {
+        Self {
+            kind: FilterKind::ValueContains,
+            value: pattern.into(),
+            ..Default::default()
+        }
+    }
+
+    /// match records whose value is JSON object with `field` equal to JSON `value`
+    ///
+    /// Nested fields are separated by `.`, for example `user.name`. If `value`
+    /// is not valid JSON, it is compared as string.
+    pub fn json_field_equals<F: Into<String>, V: Into<Vec<u8>>>(field: F, value: V) -> Self {
+        Self {
+            kind: FilterKind::JsonFieldEquals,
+            field: field.into(),
+            value: value.into(),
+        }
+    }
+
+    /// check if record matches the filter
+    pub fn matches(&self, record: &DefaultRecord) -> bool {
+        let key = record.key.inner_value_ref().as_deref();
+        let value = record.value.inner_value_ref().as_deref();
+        match self.kind {
+            FilterKind::KeyEquals => key == Some(self.value.as_slice()),
+            FilterKind::KeyPrefix => matches!(key, Some(key) if key.starts_with(&self.value)),
+            FilterKind::ValueContains => {
+                matches!(value, Some(value) if contains(value, &self.value))
+            }
+            FilterKind::JsonFieldEquals => matches!(value, Some(value) if self.json_matches(value)),
+        }
+    }
+
+    fn json_matches(&self, value: &[u8]) -> bool {
+        use serde_json::Value;
+
+        let document: Value = match serde_json::from_slice(value) {
+            Ok(document) => document,
+            Err(_) => return false,
+        };
+        let field = self
+            .field
+            .split('.')
+            .try_fold(&document, |value, name| value.get(name));
+        let expected = serde_json::from_slice(&self.value)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&self.value).into_owned()));
+        field == Some(&expected)
+    }
+}
+
+/// keep only records which match any of filters
+pub fn filter_records(filters: &[RecordFilter], records: &mut RecordSet) {
+    if filters.is_empty() {
+        return;
+    }
+
+    for batch in records.batches.iter_mut() {
+        batch.retain_records(|record| filters.iter().any(|filter| filter.matches(record)));
+    }
+    drop_empty_batches(records);
+}
+
+/// drop batches left without records, except the last one
+///
+/// last batch is kept so consumer knows offsets up to its end were read
+pub fn drop_empty_batches(records: &mut RecordSet) {
+    let last = records.batches.len().saturating_sub(1);
+    let mut index = 0;
+    records.batches.retain(|batch| {
+        let keep = index == last || !batch.records().is_empty();
+        index += 1;
+        keep
+    });
+}
+
+fn contains(value: &[u8], pattern: &[u8]) -> bool {
+    pattern.is_empty() || value.windows(pattern.len()).any(|window| window == pattern)
+}
+
+#[cfg(test)]
+mod test {
+
+    use dataplane::record::DefaultRecord;
+    use dataplane::record::DefaultAsyncBuffer;
+
+    use dataplane::batch::DefaultBatch;
+    use dataplane::record::RecordSet;
+
+    use super::RecordFilter;
+    use super::filter_records;
+
+    fn create_record(key: Option<&str>, value: &str) -> DefaultRecord {
+        let mut record = DefaultRecord::from(value.as_bytes().to_vec());
+        if let Some(key) = key {
+            record.key = DefaultAsyncBuffer::new(Some(key.as_bytes().to_vec()));
+        }
+        record
+    }
+
+    #[test]
+    fn test_key_filters() {
+        let record = create_record(Some("user-1"), "hello");
+        assert!(RecordFilter::key_equals("user-1").matches(&record));
+        assert!(!RecordFilter::key_equals("user").matches(&record));
+        assert!(RecordFilter::key_prefix("user").matches(&record));
+        assert!(!RecordFilter::key_prefix("order").matches(&record));
+
+        let no_key = create_record(None, "hello");
+        assert!(!RecordFilter::key_prefix("").matches(&no_key));
+    }
+
+    #[test]
+    fn test_value_contains() {
+        let record = create_record(None, "hello world");
+        assert!(RecordFilter::value_contains("o w").matches(&record));
+        assert!(!RecordFilter::value_contains("world!").matches(&record));
+    }
+
+    #[test]
+    fn test_json_field_equals() {
+        let record = create_record(None, r#"{"user":{"name":"alice","age":30}}"#);
+        assert!(RecordFilter::json_field_equals("user.name", "alice").matches(&record));
+        assert!(RecordFilter::json_field_equals("user.name", r#""alice""#).matches(&record));
+        assert!(RecordFilter::json_field_equals("user.age", "30").matches(&record));
+        assert!(!RecordFilter::json_field_equals("user.age", "31").matches(&record));
+        assert!(!RecordFilter::json_field_equals("user.email", "alice").matches(&record));
+
+        let not_json = create_record(None, "alice");
+        assert!(!RecordFilter::json_field_equals("user.name", "alice").matches(&not_json));
+    }
+
+    #[test]
+    fn test_filter_records() {
+        let mut first = DefaultBatch::default();
+        first.add_record(create_record(Some("a"), "1"));
+        first.add_record(create_record(Some("b"), "2"));
+        first.add_record(create_record(Some("a"), "3"));
+        let mut second = DefaultBatch::default().base_offset(3);
+        second.add_record(create_record(Some("b"), "4"));
+        let mut third = DefaultBatch::default().base_offset(4);
+        third.add_record(create_record(Some("b"), "5"));
+        let mut records = RecordSet::default().add(first).add(second).add(third);
+
+        filter_records(&[RecordFilter::key_equals("a")], &mut records);
+
+        assert_eq!(records.batches.len(), 2);
+        let offsets: Vec<i64> = records.batches[0]
+            .records()
+            .iter()
+            .map(|record| record.get_offset_delta())
+            .collect();
+        assert_eq!(offsets, vec![0, 2]);
+        assert_eq!(records.batches[0].get_last_offset(), 2);
+        // last batch is kept even without records
+        assert!(records.batches[1].records().is_empty());
+        assert_eq!(records.batches[1].get_last_offset(), 4);
+    }
+}
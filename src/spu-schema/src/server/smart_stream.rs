@@ -0,0 +1,75 @@
+//!
+//! # SmartStream
+//!
+//! WebAssembly module executed by SPU over records of stream fetch.
+//!
+//! Module must export its `memory`, `alloc(len: i32) -> i32`, which returns pointer
+//! to `len` bytes of memory SPU can write input to, and `dealloc(ptr: i32, len: i32)`,
+//! which releases memory returned by `alloc`. Depending on the kind, module
+//! exports one of:
+//!
+//! * `filter(ptr: i32, len: i32) -> i32`: called with record value, record is kept
+//!   if non zero is returned
+//! * `map(ptr: i32, len: i32) -> i64`: called with record value, returns new value
+//! * `aggregate(acc_ptr: i32, acc_len: i32, ptr: i32, len: i32) -> i64`: called with
+//!   current accumulator and record value, returns new accumulator which is also
+//!   the new value of the record
+//!
+//! Values are returned as pointer in upper 32 bits and length in lower 32 bits.
+//! Negative return value is reported as error.
+//!
+//! After each call, SPU releases memory of returned value, unless it is within
+//! input, and then memory of inputs, in reverse order of allocation.
+//!
+use dataplane::derive::Decode;
+use dataplane::derive::Encode;
+
+/// How records are transformed by SmartStream
+#[derive(Encode, Decode, Default, PartialEq, Debug, Clone, Copy)]
+#[fluvio(encode_discriminant)]
+#[repr(u8)]
+pub enum SmartStreamKind {
+    #[default]
+    Filter = 0,
+    Map = 1,
+    Aggregate = 2,
+}
+
+/// WebAssembly module and how it is applied to records
+#[derive(Encode, Decode, Default, Debug, Clone, PartialEq)]
+pub struct SmartStreamPayload {
+    pub kind: SmartStreamKind,
+    /// binary of WebAssembly module
+    pub wasm: Vec<u8>,
+    /// initial value of accumulator, only used by `Aggregate`
+    pub accumulator: Vec<u8>,
+}
+
+impl SmartStreamPayload {
+    /// keep only records for which module returns true
+    pub fn filter<W: Into<Vec<u8>>>(wasm: W) -> Self {
+        Self {
+            kind: SmartStreamKind::Filter,
+            wasm: wasm.into(),
+            ..Default::default()
+        }
+    }
+
+    /// replace value of every record with value returned by module
+    pub fn map<W: Into<Vec<u8>>>(wasm: W) -> Self {
+        Self {
+            kind: SmartStreamKind::Map,
+            wasm: wasm.into(),
+            ..Default::default()
+        }
+    }
+
+    /// combine records into accumulator, starting with `accumulator`
+    pub fn aggregate<W: Into<Vec<u8>>, A: Into<Vec<u8>>>(wasm: W, accumulator: A) -> Self {
+        Self {
+            kind: SmartStreamKind::Aggregate,
+            wasm: wasm.into(),
+            accumulator: accumulator.into(),
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//!
+//! # Stream Fetch Credit
+//!
+//! Flow control of stream fetch. SPU sends a response of stream only when stream
+//! has credit left, each response uses one credit. Consumer grants more credit
+//! as it processes responses, so slow consumer is not flooded by responses.
+//!
+use dataplane::api::Request;
+use dataplane::derive::Decode;
+use dataplane::derive::Encode;
+use dataplane::ErrorCode;
+
+use super::SpuServerApiKey;
+
+/// Flow control of stream fetch, sent with stream fetch request
+#[derive(Decode, Encode, Default, Debug, Clone, PartialEq)]
+pub struct StreamFetchFlowControl {
+    /// id chosen by consumer, unique within its connection
+    pub stream_id: i32,
+    /// number of responses SPU can send before more credit is granted
+    pub credit: i32,
+}
+
+/// grant more credit to stream fetch on same connection
+#[derive(Decode, Encode, Default, Debug)]
+pub struct StreamFetchCreditRequest {
+    pub stream_id: i32,
+    pub credit: i32,
+}
+
+impl Request for StreamFetchCreditRequest {
+    const API_KEY: u16 = SpuServerApiKey::StreamFetchCredit as u16;
+    const DEFAULT_API_VERSION: i16 = 0;
+    type Response = StreamFetchCreditResponse;
+}
+
+#[derive(Encode, Decode, Default, Debug)]
+pub struct StreamFetchCreditResponse {
+    pub error_code: ErrorCode,
+}
@@ -28,9 +28,16 @@ pub type FileStreamFetchRequest = StreamFetchRequest<FileRecordSet>;
 pub type DefaultStreamFetchRequest = StreamFetchRequest<RecordSet>;
 
 use super::SpuServerApiKey;
+use super::record_filter::RecordFilter;
+use super::smart_stream::SmartStreamPayload;
+use super::stream_credit::StreamFetchFlowControl;
 
 /// Fetch records continuously
 /// After initial fetch, update to same replica will stream to client
+/// If filters are given, only records matching any of them are sent back
+/// If smart stream is given, records are transformed by it before they are sent back
+/// If end offset or max records is given, stream ends once that bound is reached
+/// If flow control is given, responses are sent only while stream has credit
 #[derive(Decode, Encode, Default, Debug)]
 pub struct StreamFetchRequest<R>
 where
@@ -41,6 +48,18 @@ where
     pub fetch_offset: i64,
     pub max_bytes: i32,
     pub isolation: Isolation,
+    #[fluvio(min_version = 11)]
+    pub filters: Vec<RecordFilter>,
+    #[fluvio(min_version = 12)]
+    pub smart_stream: Option<SmartStreamPayload>,
+    /// offset where stream ends, records at or after it are not sent
+    #[fluvio(min_version = 13)]
+    pub end_offset: Option<i64>,
+    /// number of records after which stream ends
+    #[fluvio(min_version = 13)]
+    pub max_records: Option<u32>,
+    #[fluvio(min_version = 14)]
+    pub flow_control: Option<StreamFetchFlowControl>,
     pub data: PhantomData<R>,
 }
 
@@ -49,10 +68,13 @@ where
     R: Debug + Decoder + Encoder,
 {
     const API_KEY: u16 = SpuServerApiKey::StreamFetch as u16;
-    const DEFAULT_API_VERSION: i16 = 10;
+    const DEFAULT_API_VERSION: i16 = 15;
     type Response = StreamFetchResponse<R>;
 }
 
+/// Each response carries offset where stream continues, so consumer can resume
+/// after records which were scanned but filtered out. Once bound is reached,
+/// a last response without records marks end of stream.
 #[derive(Encode, Decode, Default, Debug)]
 pub struct StreamFetchResponse<R>
 where
@@ -60,6 +82,12 @@ where
 {
     pub topic: String,
     pub partition: FetchablePartitionResponse<R>,
+    /// offset where stream continues after this response
+    #[fluvio(min_version = 15)]
+    pub next_offset: i64,
+    /// no more responses follow, bound of stream is reached
+    #[fluvio(min_version = 15)]
+    pub end_of_stream: bool,
 }
 
 impl FileWrite for StreamFetchResponse<FileRecordSet> {
@@ -73,6 +101,112 @@ impl FileWrite for StreamFetchResponse<FileRecordSet> {
         trace!("topic {}", self.topic);
         self.topic.encode(src, version)?;
         self.partition.file_encode(src, data, version)?;
+        if version >= 15 {
+            self.next_offset.encode(src, version)?;
+            self.end_of_stream.encode(src, version)?;
+        }
         Ok(())
     }
 }
+
+/// drop records at or after `end_offset` and records after first `max_records`,
+/// returns number of records kept
+///
+/// last offset of batches is moved back to last record kept, so offset after last
+/// batch is where stream should continue.
+pub fn bound_records(
+    records: &mut RecordSet,
+    end_offset: Option<i64>,
+    max_records: Option<u32>,
+) -> u32 {
+    if end_offset.is_none() && max_records.is_none() {
+        return records
+            .batches
+            .iter()
+            .map(|batch| batch.records().len() as u32)
+            .sum();
+    }
+
+    let mut count: u32 = 0;
+    for mut batch in std::mem::take(&mut records.batches) {
+        let base_offset = batch.get_base_offset();
+        if matches!(end_offset, Some(end) if base_offset >= end) {
+            break;
+        }
+
+        let total = batch.records().len();
+        batch.retain_records(|record| {
+            let keep = !matches!(end_offset, Some(end) if base_offset + record.get_offset_delta() >= end)
+                && !matches!(max_records, Some(max) if count >= max);
+            if keep {
+                count += 1;
+            }
+            keep
+        });
+
+        if batch.records().len() < total {
+            // bound is reached in this batch
+            if let Some(record) = batch.records().last() {
+                let last_delta = record.get_offset_delta() as i32;
+                batch.set_offset_delta(last_delta);
+                records.batches.push(batch);
+            }
+            break;
+        }
+
+        if let Some(end) = end_offset {
+            if batch.get_last_offset() >= end {
+                batch.set_offset_delta((end - 1 - base_offset) as i32);
+            }
+        }
+        records.batches.push(batch);
+    }
+    count
+}
+
+#[cfg(test)]
+mod test {
+
+    use dataplane::batch::DefaultBatch;
+    use dataplane::record::{DefaultRecord, RecordSet};
+
+    use super::bound_records;
+
+    fn create_records() -> RecordSet {
+        let mut records = RecordSet::default();
+        for base_offset in &[0, 3, 6] {
+            let mut batch = DefaultBatch::default().base_offset(*base_offset);
+            for i in 0..3 {
+                batch.add_record(DefaultRecord::from(vec![*base_offset as u8 + i]));
+            }
+            records = records.add(batch);
+        }
+        records
+    }
+
+    #[test]
+    fn test_bound_records_end_offset() {
+        let mut records = create_records();
+        assert_eq!(bound_records(&mut records, Some(5), None), 5);
+        assert_eq!(records.batches.len(), 2);
+        assert_eq!(records.batches[1].get_last_offset(), 4);
+
+        let mut records = create_records();
+        assert_eq!(bound_records(&mut records, Some(6), None), 6);
+        assert_eq!(records.batches.len(), 2);
+        assert_eq!(records.batches[1].get_last_offset(), 5);
+    }
+
+    #[test]
+    fn test_bound_records_max_records() {
+        let mut records = create_records();
+        assert_eq!(bound_records(&mut records, None, Some(4)), 4);
+        assert_eq!(records.batches.len(), 2);
+        assert_eq!(records.batches[1].records().len(), 1);
+        assert_eq!(records.batches[1].get_last_offset(), 3);
+
+        let mut records = create_records();
+        assert_eq!(bound_records(&mut records, Some(8), Some(20)), 8);
+        assert_eq!(bound_records(&mut records, None, None), 8);
+    }
+}
@@ -16,6 +16,7 @@ use fluvio_types::print_cli_err;
 use fluvio_types::SpuId;
 use fluvio_future::rust_tls::TlsAcceptor;
 use fluvio_future::rust_tls::AcceptorBuilder;
+use fluvio_storage::FlushPolicy;
 
 use super::SpuConfig;
 
@@ -51,6 +52,34 @@ pub struct SpuOpt {
     #[structopt(long, value_name = "integer", env = "FLV_LOG_INDEX_MAX_INTERVAL_BYTES")]
     pub index_max_interval_bytes: Option<u32>,
 
+    /// remove log segments older than this, by default they are kept forever
+    #[structopt(long, value_name = "seconds", env = "FLV_LOG_RETENTION_SECONDS")]
+    pub retention_seconds: Option<u32>,
+
+    /// remove oldest log segments while replica is bigger than this, by default there is no limit
+    #[structopt(long, value_name = "bytes", env = "FLV_LOG_RETENTION_BYTES")]
+    pub retention_bytes: Option<u64>,
+
+    /// check crc of stored records when they are read, corrupted records are not sent
+    #[structopt(long, env = "FLV_LOG_VERIFY_CRC_ON_READ")]
+    pub verify_crc_on_read: bool,
+
+    /// scan leader replicas for corrupted records at this interval, by default they are not scanned
+    #[structopt(long, value_name = "seconds", env = "FLV_LOG_SCRUB_INTERVAL_SECONDS")]
+    pub scrub_interval_seconds: Option<u32>,
+
+    /// sync records to disk once this many records are written, takes precedence over flush ms
+    #[structopt(long, value_name = "integer", env = "FLV_LOG_FLUSH_MESSAGES")]
+    pub flush_messages: Option<u32>,
+
+    /// sync records to disk when they are written this many milliseconds after last sync
+    #[structopt(long, value_name = "milliseconds", env = "FLV_LOG_FLUSH_MS")]
+    pub flush_ms: Option<u32>,
+
+    /// sync records to disk before high watermark is moved past them
+    #[structopt(long, env = "FLV_LOG_FLUSH_BEFORE_COMMIT")]
+    pub flush_before_commit: bool,
+
     /// max bytes to transfer between leader and follower
     #[structopt(
         long,
@@ -116,6 +145,42 @@ impl SpuOpt {
             config.log.index_max_interval_bytes = index_max_interval_bytes;
         }
 
+        if let Some(retention_seconds) = self.retention_seconds {
+            info!("overriding retention seconds: {}", retention_seconds);
+            config.log.retention_seconds = Some(retention_seconds);
+        }
+
+        if let Some(retention_bytes) = self.retention_bytes {
+            info!("overriding retention bytes: {}", retention_bytes);
+            config.log.retention_bytes = Some(retention_bytes);
+        }
+
+        if self.verify_crc_on_read {
+            info!("verifying crc of records on read");
+            config.log.verify_crc_on_read = true;
+        }
+
+        if let Some(scrub_interval_seconds) = self.scrub_interval_seconds {
+            info!(
+                "overriding scrub interval seconds: {}",
+                scrub_interval_seconds
+            );
+            config.log.scrub_interval_seconds = Some(scrub_interval_seconds);
+        }
+
+        if let Some(flush_messages) = self.flush_messages {
+            info!("overriding flush messages: {}", flush_messages);
+            config.log.flush_policy = FlushPolicy::EveryRecords(flush_messages);
+        } else if let Some(flush_ms) = self.flush_ms {
+            info!("overriding flush ms: {}", flush_ms);
+            config.log.flush_policy = FlushPolicy::Interval(flush_ms);
+        }
+
+        if self.flush_before_commit {
+            info!("syncing records before commit");
+            config.log.flush_before_commit = true;
+        }
+
         if let Some(public_addr) = self.bind_public {
             info!("overriding public addr: {}", public_addr);
             config.public_endpoint = public_addr;
@@ -29,6 +29,7 @@ use fluvio_types::defaults::FLV_LOG_BASE_DIR;
 use fluvio_types::defaults::FLV_LOG_SIZE;
 use fluvio_types::SpuId;
 use fluvio_storage::ConfigOption;
+use fluvio_storage::FlushPolicy;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Replication {
@@ -50,6 +51,13 @@ pub struct Log {
     pub index_max_bytes: u32,
     pub index_max_interval_bytes: u32,
     pub segment_max_bytes: u32,
+    pub retention_seconds: Option<u32>,
+    pub retention_bytes: Option<u64>,
+    pub verify_crc_on_read: bool,
+    /// interval to scan leader replicas for corrupted records, disabled if not set
+    pub scrub_interval_seconds: Option<u32>,
+    pub flush_policy: FlushPolicy,
+    pub flush_before_commit: bool,
 }
 
 impl Default for Log {
@@ -62,6 +70,12 @@ impl Default for Log {
             index_max_bytes: SPU_LOG_INDEX_MAX_BYTES,
             index_max_interval_bytes: SPU_LOG_INDEX_MAX_INTERVAL_BYTES,
             segment_max_bytes: SPU_LOG_SEGMENT_MAX_BYTES,
+            retention_seconds: None,
+            retention_bytes: None,
+            verify_crc_on_read: false,
+            scrub_interval_seconds: None,
+            flush_policy: FlushPolicy::default(),
+            flush_before_commit: false,
         }
     }
 }
@@ -75,6 +89,11 @@ impl Log {
             self.index_max_interval_bytes,
             self.segment_max_bytes,
         )
+        .retention_seconds(self.retention_seconds)
+        .retention_bytes(self.retention_bytes)
+        .verify_crc_on_read(self.verify_crc_on_read)
+        .flush_policy(self.flush_policy)
+        .flush_before_commit(self.flush_before_commit)
     }
 }
 
@@ -17,6 +17,7 @@ use fluvio_socket::FlvSocketError;
 use dataplane::api::RequestMessage;
 use fluvio_controlplane_metadata::partition::Replica;
 use fluvio_types::SpuId;
+use fluvio_types::defaults::SPU_RETENTION_CHECK_INTERVAL_SEC;
 use flv_util::log_on_err;
 use fluvio_storage::FileReplica;
 use fluvio_controlplane_metadata::spu::SpuSpec;
@@ -145,6 +146,8 @@ impl ReplicaFollowerController<FileReplica> {
         // sync offsets
         self.sync_all_offsets_to_leader(&mut sink).await;
 
+        let mut retention_timer = sleep(Duration::from_secs(SPU_RETENTION_CHECK_INTERVAL_SEC));
+
         loop {
             follower_debug!(self, "waiting request from leader");
 
@@ -154,6 +157,13 @@ impl ReplicaFollowerController<FileReplica> {
                     self.sync_all_offsets_to_leader(&mut sink).await;
                 },
 
+                _ = &mut retention_timer => {
+                    follower_debug!(self,"retention timer fired - removing old segments");
+                    self.followers_state.enforce_retention(&self.leader_id).await;
+                    self.followers_state.compact(&self.leader_id).await;
+                    retention_timer = sleep(Duration::from_secs(SPU_RETENTION_CHECK_INTERVAL_SEC));
+                },
+
                 cmd_msg = self.receiver.next() => {
                     if let Some(cmd) = cmd_msg {
                         match cmd {
@@ -316,6 +326,7 @@ impl ReplicaFollowerController<FileReplica> {
                 self.config.id(),
                 replica_msg.leader,
                 &replica_key,
+                &replica_msg.config,
                 &log,
             )
             .await
@@ -15,6 +15,7 @@ use chashmap::ReadGuard;
 use chashmap::WriteGuard;
 
 use fluvio_controlplane_metadata::partition::ReplicaKey;
+use fluvio_controlplane_metadata::topic::TopicConfig;
 use dataplane::record::RecordSet;
 use fluvio_storage::FileReplica;
 use fluvio_storage::ConfigOption;
@@ -182,6 +183,16 @@ impl FollowersState<FileReplica> {
                 let rep_id = partition_request.partition_index;
                 let replica_key = ReplicaKey::new(topic.clone(), rep_id);
                 trace!("sync request for replica: {}", replica_key);
+                if let Some(batch) = partition_request.records.find_corrupt_batch() {
+                    // records are not written, current offsets are reported so leader sends them again
+                    error!(
+                        "batch with base offset: {} from leader has crc mismatch, skipping sync of replica: {}",
+                        batch.get_base_offset(),
+                        replica_key
+                    );
+                    self.add_replica_offset_to(&replica_key, &mut offsets);
+                    continue;
+                }
                 if let Some(mut replica) = self.get_mut_replica(&replica_key) {
                     match replica.send_records(partition_request.records).await {
                         Ok(_) => {
@@ -236,6 +247,60 @@ impl FollowersState<FileReplica> {
         offsets
     }
 
+    /// remove old segments of all replicas followed from leader
+    pub(crate) async fn enforce_retention(&self, leader: &SpuId) {
+        let keys: Vec<ReplicaKey> = match self.replica_keys.read().unwrap().get(leader) {
+            Some(keys) => keys.iter().cloned().collect(),
+            None => return,
+        };
+
+        for replica_id in keys {
+            if let Some(mut replica) = self.get_mut_replica(&replica_id) {
+                if let Err(err) = replica.mut_storage().enforce_retention().await {
+                    error!(
+                        "error enforcing retention for follower replica: {}, {}",
+                        replica_id, err
+                    );
+                }
+            }
+        }
+    }
+
+    /// remove superseded records of compacted replicas followed from leader
+    pub(crate) async fn compact(&self, leader: &SpuId) {
+        let keys: Vec<ReplicaKey> = match self.replica_keys.read().unwrap().get(leader) {
+            Some(keys) => keys.iter().cloned().collect(),
+            None => return,
+        };
+
+        for replica_id in keys {
+            // compacted segments are written without locking replica
+            let compaction = match self.get_replica(&replica_id) {
+                Some(replica) => replica.storage().compaction(),
+                None => None,
+            };
+            let compacted = match compaction {
+                Some(compaction) => match compaction.compact().await {
+                    Ok(compacted) => compacted,
+                    Err(err) => {
+                        error!("error compacting follower replica: {}, {}", replica_id, err);
+                        continue;
+                    }
+                },
+                None => continue,
+            };
+
+            if let Some(mut replica) = self.get_mut_replica(&replica_id) {
+                if let Err(err) = replica.mut_storage().swap_compacted(compacted).await {
+                    error!(
+                        "error swapping compacted segments of follower replica: {}, {}",
+                        replica_id, err
+                    );
+                }
+            }
+        }
+    }
+
     fn add_replica_offset_to(&self, replica_id: &ReplicaKey, offsets: &mut UpdateOffsetRequest) {
         if let Some(replica) = self.get_replica(replica_id) {
             let storage = replica.storage();
@@ -282,6 +347,7 @@ impl FollowerReplicaState<FileReplica> {
         local_spu: SpuId,
         leader: SpuId,
         replica: &'a ReplicaKey,
+        replica_config: &'a TopicConfig,
         config: &'a ConfigOption,
     ) -> Result<Self, StorageError> {
         debug!(
@@ -292,7 +358,7 @@ impl FollowerReplicaState<FileReplica> {
             config.base_dir.display()
         );
 
-        let storage = create_replica_storage(local_spu, replica, &config).await?;
+        let storage = create_replica_storage(local_spu, replica, replica_config, config).await?;
 
         Ok(Self {
             leader,
@@ -17,7 +17,6 @@ use dataplane::ErrorCode;
 use dataplane::store::StoreValue;
 use dataplane::store::FileWrite;
 use fluvio_storage::SlicePartitionResponse;
-use fluvio_future::file_slice::AsyncFileSlice;
 
 use super::FollowerPeerApiEnum;
 
@@ -162,8 +161,8 @@ impl SlicePartitionResponse for PeerFilePartitionResponse {
         self.high_watermark = offset;
     }
 
-    fn set_slice(&mut self, slice: AsyncFileSlice) {
-        self.records = slice.into();
+    fn set_slice(&mut self, slice: FileRecordSet) {
+        self.records = slice;
     }
 
     fn set_error_code(&mut self, error: ErrorCode) {
@@ -3,7 +3,6 @@ use std::time::Duration;
 
 use tracing::debug;
 use tracing::warn;
-use tracing::error;
 use async_channel::Receiver;
 use futures_util::future::join3;
 use futures_util::future::join;
@@ -14,11 +13,11 @@ use fluvio_future::timer::sleep;
 use fluvio_controlplane_metadata::partition::ReplicaKey;
 use fluvio_storage::FileReplica;
 use fluvio_types::SpuId;
+use fluvio_types::defaults::SPU_RETENTION_CHECK_INTERVAL_SEC;
 use fluvio_socket::ExclusiveFlvSink;
-use tokio::sync::broadcast::Sender;
 
 use crate::core::SharedSpuSinks;
-use crate::core::OffsetUpdateEvent;
+use crate::core::OffsetPublisher;
 
 use super::LeaderReplicaControllerCommand;
 use super::FollowerOffsetUpdate;
@@ -37,8 +36,9 @@ pub struct ReplicaLeaderController<S> {
     leaders_state: SharedReplicaLeadersState<S>,
     follower_sinks: SharedSpuSinks,
     sc_sink: Arc<ExclusiveFlvSink>,
-    offset_sender: Sender<OffsetUpdateEvent>,
+    offset_publisher: Arc<OffsetPublisher>,
     max_bytes: u32,
+    scrub_interval: Option<Duration>,
 }
 
 impl<S> ReplicaLeaderController<S> {
@@ -50,8 +50,9 @@ impl<S> ReplicaLeaderController<S> {
         leaders_state: SharedReplicaLeadersState<S>,
         follower_sinks: SharedSpuSinks,
         sc_sink: Arc<ExclusiveFlvSink>,
-        offset_sender: Sender<OffsetUpdateEvent>,
+        offset_publisher: Arc<OffsetPublisher>,
         max_bytes: u32,
+        scrub_interval: Option<Duration>,
     ) -> Self {
         Self {
             local_spu,
@@ -60,8 +61,9 @@ impl<S> ReplicaLeaderController<S> {
             leaders_state,
             follower_sinks,
             sc_sink,
-            offset_sender,
+            offset_publisher,
             max_bytes,
+            scrub_interval,
         }
     }
 }
@@ -93,6 +95,13 @@ impl ReplicaLeaderController<FileReplica> {
         self.sync_followers().await;
 
         let mut timer = sleep(Duration::from_secs(FOLLOWER_RECONCILIATION_INTERVAL_SEC));
+        let mut retention_timer = sleep(Duration::from_secs(SPU_RETENTION_CHECK_INTERVAL_SEC));
+        // scrub timer is only polled when scrub interval is set
+        let scrub_interval = self.scrub_interval.unwrap_or_default();
+        let mut scrub_timer = sleep(scrub_interval);
+        // flush timer is only polled when replica is synced by interval
+        let flush_interval = self.flush_interval();
+        let mut flush_timer = sleep(flush_interval.unwrap_or_default());
         loop {
             leader_debug!(self, "waiting for next command");
 
@@ -103,6 +112,22 @@ impl ReplicaLeaderController<FileReplica> {
                     self.sync_followers().await;
                 },
 
+                _ = &mut retention_timer => {
+                    self.enforce_retention().await;
+                    self.compact().await;
+                    retention_timer = sleep(Duration::from_secs(SPU_RETENTION_CHECK_INTERVAL_SEC));
+                },
+
+                _ = &mut scrub_timer, if self.scrub_interval.is_some() => {
+                    self.scrub().await;
+                    scrub_timer = sleep(scrub_interval);
+                },
+
+                _ = &mut flush_timer, if flush_interval.is_some() => {
+                    self.sync_records().await;
+                    flush_timer = sleep(flush_interval.unwrap_or_default());
+                },
+
                 controller_req = self.controller_receiver.next() => {
                     if let Some(command) = controller_req {
                         match command {
@@ -162,6 +187,7 @@ impl ReplicaLeaderController<FileReplica> {
                 self.id
             );
         }
+        self.leaders_state.notify_offset_update();
     }
 
     /// go thru each of follower and sync replicas
@@ -175,6 +201,86 @@ impl ReplicaLeaderController<FileReplica> {
         }
     }
 
+    /// remove old segments of replica
+    async fn enforce_retention(&self) {
+        if let Some(mut leader_replica) = self.leaders_state.get_mut_replica(&self.id) {
+            if let Err(err) = leader_replica.mut_storage().enforce_retention().await {
+                leader_warn!(self, "error enforcing retention: {}", err);
+            }
+        } else {
+            leader_warn!(self, "enforce retention: no replica is found");
+        }
+    }
+
+    /// remove superseded records of compacted replica
+    /// compacted segments are written without locking replica, it is only locked to swap them
+    async fn compact(&self) {
+        let compaction = match self.leaders_state.get_replica(&self.id) {
+            Some(leader_replica) => leader_replica.storage().compaction(),
+            None => {
+                leader_warn!(self, "compact: no replica is found");
+                return;
+            }
+        };
+        let compacted = match compaction {
+            Some(compaction) => match compaction.compact().await {
+                Ok(compacted) => compacted,
+                Err(err) => {
+                    leader_warn!(self, "error compacting replica: {}", err);
+                    return;
+                }
+            },
+            None => return,
+        };
+
+        if let Some(mut leader_replica) = self.leaders_state.get_mut_replica(&self.id) {
+            if let Err(err) = leader_replica.mut_storage().swap_compacted(compacted).await {
+                leader_warn!(self, "error swapping compacted segments: {}", err);
+            }
+        } else {
+            leader_warn!(self, "compact: no replica is found");
+        }
+    }
+
+    /// interval of syncing records, if replica has interval flush policy
+    fn flush_interval(&self) -> Option<Duration> {
+        self.leaders_state
+            .get_replica(&self.id)
+            .and_then(|leader_replica| leader_replica.storage().flush_interval())
+    }
+
+    /// sync written records to disk, so they are not left unsynced when no more records are written
+    async fn sync_records(&self) {
+        if let Some(mut leader_replica) = self.leaders_state.get_mut_replica(&self.id) {
+            if let Err(err) = leader_replica.mut_storage().sync().await {
+                leader_warn!(self, "error syncing records: {}", err);
+            }
+        } else {
+            leader_warn!(self, "sync records: no replica is found");
+        }
+    }
+
+    /// scan replica for records which are corrupted on disk
+    ///
+    /// logs are scanned from snapshot, so replica is not held while reading them
+    async fn scrub(&self) {
+        let snapshot = match self.leaders_state.get_replica(&self.id) {
+            Some(leader_replica) => leader_replica.storage().log_snapshot(),
+            None => {
+                leader_warn!(self, "scrub: no replica is found");
+                return;
+            }
+        };
+        match snapshot.scrub().await {
+            Ok(corrupted) => {
+                for batch in corrupted {
+                    leader_warn!(self, "corrupted records: {:?}", batch);
+                }
+            }
+            Err(err) => leader_warn!(self, "error scrubbing replica: {}", err),
+        }
+    }
+
     /// send status back to sc
     async fn send_status_to_sc(&self) {
         if let Some(leader_replica) = self.leaders_state.get_replica(&self.id) {
@@ -187,15 +293,8 @@ impl ReplicaLeaderController<FileReplica> {
     /// update the clients that we have offset changed
     async fn update_offset_to_clients(&self) {
         if let Some(leader_replica) = self.leaders_state.get_replica(&self.id) {
-            let event = OffsetUpdateEvent {
-                hw: leader_replica.hw(),
-                leo: leader_replica.leo(),
-                replica_id: self.id.clone(),
-            };
-
-            if let Err(err) = self.offset_sender.send(event) {
-                error!("error sending offset {:#?}", err);
-            }
+            self.offset_publisher
+                .update(leader_replica.hw(), leader_replica.leo());
         } else {
             leader_warn!(self, "no replica is found");
         }
@@ -1,5 +1,8 @@
 use std::sync::Arc;
 use std::collections::HashMap;
+use std::fs::File;
+use std::os::unix::io::BorrowedFd;
+use std::time::Duration;
 
 use chashmap::CHashMap;
 use chashmap::ReadGuard;
@@ -7,6 +10,7 @@ use chashmap::WriteGuard;
 use async_channel::Sender;
 use async_channel::SendError;
 use async_rwlock::RwLock;
+use event_listener::Event;
 use tracing::debug;
 use tracing::warn;
 use tracing::trace;
@@ -31,6 +35,7 @@ pub type SharedReplicaLeadersState<S> = Arc<ReplicaLeadersState<S>>;
 pub struct ReplicaLeadersState<S> {
     replicas: CHashMap<ReplicaKey, LeaderReplicaState<S>>,
     mailboxes: RwLock<HashMap<ReplicaKey, Sender<LeaderReplicaControllerCommand>>>,
+    offset_event: Event,
 }
 
 impl<S> Default for ReplicaLeadersState<S> {
@@ -38,6 +43,7 @@ impl<S> Default for ReplicaLeadersState<S> {
         ReplicaLeadersState {
             replicas: CHashMap::default(),
             mailboxes: RwLock::new(HashMap::new()),
+            offset_event: Event::new(),
         }
     }
 }
@@ -89,6 +95,7 @@ impl<S> ReplicaLeadersState<S> {
             } else {
                 error!("no mailbox found for removing: {}", key);
             }
+            self.notify_offset_update();
             Some(replica)
         } else {
             error!("leader replica: {} is not founded", key);
@@ -122,6 +129,11 @@ impl<S> ReplicaLeadersState<S> {
     ) -> Option<Sender<LeaderReplicaControllerCommand>> {
         self.mailboxes.read().await.get(key).cloned()
     }
+
+    /// wake up produce requests waiting for records to be replicated
+    pub fn notify_offset_update(&self) {
+        self.offset_event.notify(usize::MAX);
+    }
 }
 
 impl ReplicaLeadersState<FileReplica> {
@@ -156,22 +168,109 @@ impl ReplicaLeadersState<FileReplica> {
         }
     }
 
+    /// read records as `read_records` and duplicate file of records while replica is held,
+    /// so records can be read from returned file even after their segment is closed
+    /// return associated hw, leo and file, file is none if there are no records
+    pub async fn read_records_file(
+        &self,
+        rep_id: &ReplicaKey,
+        offset: Offset,
+        max_len: u32,
+        isolation: Isolation,
+        response: &mut FilePartitionResponse,
+    ) -> Option<(Offset, Offset, Option<File>)> {
+        let leader_replica = match self.get_replica(rep_id) {
+            Some(leader_replica) => leader_replica,
+            None => {
+                warn!("no replica is found: {}", rep_id);
+                response.error_code = ErrorCode::NotLeaderForPartition;
+                return None;
+            }
+        };
+
+        let (hw, leo) = leader_replica
+            .read_records(offset, max_len, isolation, response)
+            .await;
+        if response.records.len() == 0 {
+            return Some((hw, leo, None));
+        }
+
+        // segment can't be closed while replica is held, so descriptor is valid
+        let fd = unsafe { BorrowedFd::borrow_raw(response.records.raw_slice().fd()) };
+        match fd.try_clone_to_owned() {
+            Ok(fd) => Some((hw, leo, Some(File::from(fd)))),
+            Err(err) => {
+                error!("unable to duplicate records file of: {}, {}", rep_id, err);
+                response.error_code = ErrorCode::StorageError;
+                None
+            }
+        }
+    }
+
     /// write new record and notify the leader replica controller
+    /// return end offset after records are written, none if replica is not found
     /// TODO: may replica should be moved it's own map
     pub async fn send_records(
         &self,
         rep_id: &ReplicaKey,
         records: RecordSet,
         update_hw: bool,
-    ) -> Result<bool, InternalServerError> {
+    ) -> Result<Option<Offset>, InternalServerError> {
         if let Some(mut leader_replica) = self.get_mut_replica(rep_id) {
             leader_replica.send_records(records, update_hw).await?;
-            self.send_message(rep_id, LeaderReplicaControllerCommand::EndOffsetUpdated)
-                .await
-                .map_err(|err| err.into())
+            let leo = leader_replica.leo();
+            let found = self
+                .send_message(rep_id, LeaderReplicaControllerCommand::EndOffsetUpdated)
+                .await?;
+            Ok(if found { Some(leo) } else { None })
         } else {
             warn!("no replica is found: {}", rep_id);
-            Ok(false)
+            Ok(None)
+        }
+    }
+
+    /// wait until records before `offset` are replicated to min in sync replicas of topic,
+    /// or `default_min_in_sync_replicas` if topic doesn't set it
+    /// return false if this doesn't happen within `timeout` or replica is no longer leader
+    pub async fn wait_for_replication(
+        &self,
+        rep_id: &ReplicaKey,
+        offset: Offset,
+        default_min_in_sync_replicas: u16,
+        timeout: Duration,
+    ) -> bool {
+        use tokio::select;
+        use fluvio_future::timer::sleep;
+
+        let mut timer = sleep(timeout);
+        loop {
+            let listener = self.offset_event.listen();
+
+            match self.get_replica(rep_id) {
+                Some(leader_replica) => {
+                    if leader_replica.is_replicated(offset, default_min_in_sync_replicas) {
+                        return true;
+                    }
+                }
+                None => {
+                    warn!("no replica is found: {}", rep_id);
+                    return false;
+                }
+            }
+
+            trace!(
+                "waiting for replica: {} to be replicated up to: {}",
+                rep_id,
+                offset
+            );
+
+            select! {
+                _ = &mut timer => {
+                    debug!("timeout waiting for replica: {} to be replicated up to: {}", rep_id, offset);
+                    return false;
+                },
+                _ = listener => {}
+            }
         }
     }
 }
@@ -6,6 +6,7 @@ mod api_key;
 mod peer_api;
 mod update_offsets;
 mod actions;
+mod producer_sequences;
 
 pub use self::leader_controller::ReplicaLeaderController;
 pub use leaders_state::ReplicaLeadersState;
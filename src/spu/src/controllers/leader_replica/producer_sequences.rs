@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use tracing::debug;
+
+use dataplane::ErrorCode;
+use dataplane::batch::BatchHeader;
+use dataplane::record::RecordSet;
+
+/// last batch written by idempotent producer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProducerSequence {
+    epoch: i16,
+    last_sequence: i32,
+}
+
+/// Sequences written to leader replica by each idempotent producer
+///
+/// Batch of idempotent producer carries producer id and sequence of its first record.
+/// Batch is only accepted if it follows last batch written by the producer. Batch which
+/// was already written is dropped, so producer can safely retry when response is lost.
+///
+/// Sequences are kept in memory. When replica becomes leader, they are restored from
+/// batches at end of its log, so producers which wrote there are still checked.
+#[derive(Debug, Default)]
+pub struct ProducerSequences {
+    producers: HashMap<i64, ProducerSequence>,
+}
+
+impl ProducerSequences {
+    /// drop batches which were already written, error if batch doesn't follow previous batch
+    ///
+    /// returns sequences to be updated once records are written
+    pub fn check_batches(
+        &self,
+        records: &mut RecordSet,
+    ) -> Result<HashMap<i64, ProducerSequence>, ErrorCode> {
+        let mut updated: HashMap<i64, ProducerSequence> = HashMap::new();
+        let mut accepted = Vec::with_capacity(records.batches.len());
+
+        for batch in records.batches.drain(..) {
+            let header = batch.get_header();
+            let producer_id = header.producer_id;
+            if producer_id < 0 {
+                accepted.push(batch);
+                continue;
+            }
+
+            let last_sequence = header.first_sequence.wrapping_add(header.last_offset_delta);
+            let current = updated
+                .get(&producer_id)
+                .or_else(|| self.producers.get(&producer_id));
+            if let Some(current) = current {
+                if header.producer_epoch < current.epoch {
+                    return Err(ErrorCode::InvalidProducerEpoch);
+                }
+                if header.producer_epoch == current.epoch
+                    && header.first_sequence != current.last_sequence.wrapping_add(1)
+                {
+                    if !is_after(last_sequence, current.last_sequence) {
+                        debug!(
+                            producer_id,
+                            sequence = header.first_sequence,
+                            "dropping batch which was already written"
+                        );
+                        continue;
+                    }
+                    return Err(ErrorCode::OutOfOrderSequenceNumber);
+                }
+            }
+
+            updated.insert(
+                producer_id,
+                ProducerSequence {
+                    epoch: header.producer_epoch,
+                    last_sequence,
+                },
+            );
+            accepted.push(batch);
+        }
+
+        records.batches = accepted;
+        Ok(updated)
+    }
+
+    /// record sequences of batches which were written
+    pub fn update(&mut self, sequences: HashMap<i64, ProducerSequence>) {
+        self.producers.extend(sequences);
+    }
+
+    /// restore sequences from headers of batches which were written, in offset order
+    pub fn restore<'a>(&mut self, headers: impl IntoIterator<Item = &'a BatchHeader>) {
+        for header in headers {
+            if header.producer_id < 0 {
+                continue;
+            }
+            let last_sequence = header.first_sequence.wrapping_add(header.last_offset_delta);
+            let sequence = ProducerSequence {
+                epoch: header.producer_epoch,
+                last_sequence,
+            };
+            match self.producers.get(&header.producer_id) {
+                Some(current) if current.epoch > header.producer_epoch => {}
+                _ => {
+                    self.producers.insert(header.producer_id, sequence);
+                }
+            }
+        }
+        debug!("restored sequences of {} producers", self.producers.len());
+    }
+}
+
+/// sequences wrap around, so `sequence` is after `other` if it is ahead by less than half of range
+fn is_after(sequence: i32, other: i32) -> bool {
+    sequence.wrapping_sub(other) > 0
+}
+
+#[cfg(test)]
+mod test {
+
+    use dataplane::ErrorCode;
+    use dataplane::batch::DefaultBatch;
+    use dataplane::record::{DefaultRecord, RecordSet};
+
+    use super::ProducerSequences;
+
+    fn create_batch(
+        producer_id: i64,
+        epoch: i16,
+        first_sequence: i32,
+        records: u16,
+    ) -> DefaultBatch {
+        let mut batch = DefaultBatch::default();
+        for _ in 0..records {
+            batch.add_record(DefaultRecord::from(vec![0u8]));
+        }
+        let header = batch.get_mut_header();
+        header.producer_id = producer_id;
+        header.producer_epoch = epoch;
+        header.first_sequence = first_sequence;
+        batch
+    }
+
+    fn check(sequences: &mut ProducerSequences, batch: DefaultBatch) -> Result<usize, ErrorCode> {
+        let mut records = RecordSet::default().add(batch);
+        let updated = sequences.check_batches(&mut records)?;
+        sequences.update(updated);
+        Ok(records.batches.len())
+    }
+
+    #[test]
+    fn test_producer_sequences() {
+        let mut sequences = ProducerSequences::default();
+
+        assert_eq!(check(&mut sequences, create_batch(1, 0, 0, 3)), Ok(1));
+        assert_eq!(check(&mut sequences, create_batch(1, 0, 3, 2)), Ok(1));
+
+        // retry of batch which was already written
+        assert_eq!(check(&mut sequences, create_batch(1, 0, 3, 2)), Ok(0));
+        assert_eq!(check(&mut sequences, create_batch(1, 0, 0, 3)), Ok(0));
+
+        // batch is missing before this one
+        assert_eq!(
+            check(&mut sequences, create_batch(1, 0, 10, 1)),
+            Err(ErrorCode::OutOfOrderSequenceNumber)
+        );
+        assert_eq!(check(&mut sequences, create_batch(1, 0, 5, 1)), Ok(1));
+
+        // other producers and batches without producer are independent
+        assert_eq!(check(&mut sequences, create_batch(2, 0, 7, 1)), Ok(1));
+        assert_eq!(check(&mut sequences, create_batch(-1, -1, -1, 1)), Ok(1));
+        assert_eq!(check(&mut sequences, create_batch(-1, -1, -1, 1)), Ok(1));
+
+        // new epoch restarts sequences, old epoch is fenced
+        assert_eq!(check(&mut sequences, create_batch(1, 1, 0, 1)), Ok(1));
+        assert_eq!(
+            check(&mut sequences, create_batch(1, 0, 6, 1)),
+            Err(ErrorCode::InvalidProducerEpoch)
+        );
+    }
+
+    #[test]
+    fn test_restore_producer_sequences() {
+        let headers: Vec<_> = vec![
+            create_batch(1, 0, 0, 3),
+            create_batch(2, 1, 0, 1),
+            create_batch(-1, -1, -1, 1),
+            create_batch(1, 0, 3, 2),
+            create_batch(2, 0, 5, 1),
+        ]
+        .into_iter()
+        .map(|batch| batch.get_header().clone())
+        .collect();
+        let mut sequences = ProducerSequences::default();
+        sequences.restore(&headers);
+
+        // retry of batch written by previous leader is dropped
+        assert_eq!(check(&mut sequences, create_batch(1, 0, 3, 2)), Ok(0));
+        assert_eq!(
+            check(&mut sequences, create_batch(1, 0, 10, 1)),
+            Err(ErrorCode::OutOfOrderSequenceNumber)
+        );
+        assert_eq!(check(&mut sequences, create_batch(1, 0, 5, 1)), Ok(1));
+
+        // old epoch in log doesn't replace newer one
+        assert_eq!(
+            check(&mut sequences, create_batch(2, 0, 6, 1)),
+            Err(ErrorCode::InvalidProducerEpoch)
+        );
+        assert_eq!(check(&mut sequences, create_batch(2, 1, 1, 1)), Ok(1));
+    }
+
+    #[test]
+    fn test_producer_sequences_wrap() {
+        let mut sequences = ProducerSequences::default();
+
+        assert_eq!(
+            check(&mut sequences, create_batch(1, 0, i32::MAX - 2, 2)),
+            Ok(1)
+        );
+        // batch crossing end of range
+        assert_eq!(
+            check(&mut sequences, create_batch(1, 0, i32::MAX, 2)),
+            Ok(1)
+        );
+        assert_eq!(
+            check(&mut sequences, create_batch(1, 0, i32::MIN + 1, 1)),
+            Ok(1)
+        );
+
+        // retries from before wrap are still detected
+        assert_eq!(
+            check(&mut sequences, create_batch(1, 0, i32::MAX, 2)),
+            Ok(0)
+        );
+        assert_eq!(
+            check(&mut sequences, create_batch(1, 0, i32::MAX - 2, 2)),
+            Ok(0)
+        );
+        assert_eq!(
+            check(&mut sequences, create_batch(1, 0, i32::MIN + 5, 1)),
+            Err(ErrorCode::OutOfOrderSequenceNumber)
+        );
+        assert_eq!(
+            check(&mut sequences, create_batch(1, 0, i32::MIN + 2, 1)),
+            Ok(1)
+        );
+    }
+}
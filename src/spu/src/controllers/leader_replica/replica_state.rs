@@ -8,6 +8,7 @@ use tracing::warn;
 use fluvio_socket::SinkPool;
 use dataplane::record::RecordSet;
 use dataplane::{Offset, Isolation};
+use dataplane::current_timestamp;
 use dataplane::api::RequestMessage;
 
 use fluvio_controlplane_metadata::partition::ReplicaKey;
@@ -27,7 +28,12 @@ use crate::controllers::follower_replica::FileSyncRequest;
 use crate::controllers::follower_replica::PeerFileTopicResponse;
 use crate::controllers::follower_replica::PeerFilePartitionResponse;
 
+use crate::InternalServerError;
 use super::FollowerOffsetUpdate;
+use super::producer_sequences::ProducerSequences;
+
+/// segments at end of log whose batches restore producer sequences, when replica becomes leader
+const PRODUCER_SEQUENCE_SEGMENTS: usize = 2;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FollowerReplicaInfo {
@@ -78,6 +84,9 @@ pub struct LeaderReplicaState<S> {
     leader_id: SpuId,
     followers: BTreeMap<SpuId, FollowerReplicaInfo>,
     storage: S,
+    log_append_time: bool,
+    min_in_sync_replicas: Option<u16>,
+    producers: ProducerSequences,
 }
 
 impl<S> LeaderReplicaState<S> {
@@ -91,11 +100,26 @@ impl<S> LeaderReplicaState<S> {
             leader_id,
             followers: BTreeMap::new(),
             storage,
+            log_append_time: false,
+            min_in_sync_replicas: None,
+            producers: ProducerSequences::default(),
         };
         state.add_follower_replica(follower_ids);
         state
     }
 
+    /// set timestamp of records to time when they are written
+    pub fn with_log_append_time(mut self, log_append_time: bool) -> Self {
+        self.log_append_time = log_append_time;
+        self
+    }
+
+    /// min in sync replicas of topic, SPU default is used if not set
+    pub fn with_min_in_sync_replicas(mut self, min_in_sync_replicas: Option<u16>) -> Self {
+        self.min_in_sync_replicas = min_in_sync_replicas;
+        self
+    }
+
     pub fn replica_id(&self) -> &ReplicaKey {
         &self.replica_id
     }
@@ -150,6 +174,29 @@ where
         self.storage.get_hw()
     }
 
+    /// number of replicas, including leader, whose end offset has reached `offset`
+    pub fn in_sync_replica_count(&self, offset: Offset) -> usize {
+        1 + self
+            .followers
+            .values()
+            .filter(|follower_info| follower_info.leo >= offset)
+            .count()
+    }
+
+    /// min number of replicas which must have records before they are acknowledged,
+    /// topic setting takes precedence over `default` of SPU
+    pub fn min_in_sync_replicas(&self, default: u16) -> u16 {
+        self.min_in_sync_replicas.unwrap_or(default)
+    }
+
+    /// check if records before `offset` has been committed:
+    /// high watermark has passed them and at least min in sync replicas have them
+    pub fn is_replicated(&self, offset: Offset, default_min_in_sync_replicas: u16) -> bool {
+        self.hw() >= offset
+            && self.in_sync_replica_count(offset)
+                >= self.min_in_sync_replicas(default_min_in_sync_replicas) as usize
+    }
+
     /// update followers offset, return (status_needs_to_changed,follower to be synced)
     ///
     /// // case 1:  follower offset has same value as leader
@@ -277,14 +324,32 @@ impl LeaderReplicaState<FileReplica> {
             leader
         );
 
-        let storage = create_replica_storage(leader.leader, &leader.id, &config).await?;
+        let storage =
+            create_replica_storage(leader.leader, &leader.id, &leader.config, config).await?;
 
-        Ok(Self::new(
-            leader.id,
-            leader.leader,
-            storage,
-            leader.replicas,
-        ))
+        let log_append_time = leader.config.is_log_append_time();
+        let min_in_sync_replicas = leader.config.min_insync_replicas();
+        let mut state = Self::new(leader.id, leader.leader, storage, leader.replicas)
+            .with_log_append_time(log_append_time)
+            .with_min_in_sync_replicas(min_in_sync_replicas);
+        state.restore_producer_sequences().await;
+        Ok(state)
+    }
+
+    /// restore sequences of idempotent producers from batches at end of log,
+    /// so batches written through previous leader are not written again
+    pub async fn restore_producer_sequences(&mut self) {
+        let snapshot = self.storage.log_snapshot();
+        match snapshot
+            .read_batch_headers(PRODUCER_SEQUENCE_SEGMENTS)
+            .await
+        {
+            Ok(headers) => self.producers.restore(&headers),
+            Err(err) => warn!(
+                "replica: {}, error restoring producer sequences: {}",
+                self.replica_id, err
+            ),
+        }
     }
 
     /// sync specific follower
@@ -374,19 +439,37 @@ impl LeaderReplicaState<FileReplica> {
         (self.hw(), self.leo())
     }
 
+    /// write records, batches of idempotent producers which were already written are skipped
     pub async fn send_records(
         &mut self,
-        records: RecordSet,
+        mut records: RecordSet,
         update_highwatermark: bool,
-    ) -> Result<(), StorageError> {
+    ) -> Result<(), InternalServerError> {
         trace!(
             "writing records to leader: {} replica: {}, ",
             self.leader_id,
             self.replica_id
         );
+        let sequences = self
+            .producers
+            .check_batches(&mut records)
+            .map_err(InternalServerError::SequenceError)?;
+        if records.batches.is_empty() {
+            debug!("replica: {}, all batches already written", self.replica_id);
+            return Ok(());
+        }
+
+        if self.log_append_time {
+            let now = current_timestamp();
+            for batch in records.batches.iter_mut() {
+                batch.header.set_log_append_time(now);
+            }
+        }
         self.storage
             .send_records(records, update_highwatermark)
-            .await
+            .await?;
+        self.producers.update(sequences);
+        Ok(())
     }
 
     #[allow(dead_code)]
@@ -497,4 +580,47 @@ mod test {
         );
         assert_eq!(replica_state.need_follower_updates().len(), 0);
     }
+
+    #[test]
+    fn test_replicated() {
+        fluvio_future::subscriber::init_logger();
+        let mock_replica = MockReplica::new(20, 20); // eof, hw
+
+        let mut replica_state =
+            LeaderReplicaState::new(("test", 1), 5000, mock_replica, vec![5001, 5002]);
+
+        // only leader has records
+        assert_eq!(replica_state.in_sync_replica_count(20), 1);
+        assert!(replica_state.is_replicated(20, 1));
+        assert!(!replica_state.is_replicated(20, 2));
+
+        replica_state.update_follower_offsets((5001, 10, 10));
+        assert_eq!(replica_state.in_sync_replica_count(10), 2);
+        assert_eq!(replica_state.in_sync_replica_count(20), 1);
+        assert!(!replica_state.is_replicated(20, 2));
+
+        replica_state.update_follower_offsets((5001, 20, 20));
+        replica_state.update_follower_offsets((5002, 20, 20));
+        assert_eq!(replica_state.in_sync_replica_count(20), 3);
+        assert!(replica_state.is_replicated(20, 3));
+
+        // high watermark has not passed records
+        replica_state.mut_storage().hw = 10;
+        assert!(!replica_state.is_replicated(20, 1));
+    }
+
+    #[test]
+    fn test_topic_min_in_sync_replicas() {
+        let mock_replica = MockReplica::new(20, 20); // eof, hw
+
+        let mut replica_state =
+            LeaderReplicaState::new(("test", 1), 5000, mock_replica, vec![5001, 5002])
+                .with_min_in_sync_replicas(Some(2));
+        assert_eq!(replica_state.min_in_sync_replicas(1), 2);
+
+        // topic setting takes precedence over SPU default
+        assert!(!replica_state.is_replicated(20, 1));
+        replica_state.update_follower_offsets((5001, 20, 20));
+        assert!(replica_state.is_replicated(20, 3));
+    }
 }
@@ -31,6 +31,7 @@ use fluvio_socket::ExclusiveFlvSink;
 use fluvio_storage::FileReplica;
 use fluvio_controlplane_metadata::partition::ReplicaKey;
 use fluvio_types::log_on_err;
+use fluvio_types::defaults::CONSUMER_OFFSETS_TOPIC;
 use flv_util::actions::Actions;
 
 use crate::core::SharedGlobalContext;
@@ -489,7 +490,15 @@ impl ScDispatcher<FileReplica> {
                 old_replica.replica_id()
             );
         }
+        self.leadership_changed(&replica_id).await;
 
+        let offset_publisher = self.ctx.offset_publishers().publisher(&replica_id);
+        let scrub_interval = self
+            .ctx
+            .config()
+            .log
+            .scrub_interval_seconds
+            .map(|seconds| Duration::from_secs(seconds as u64));
         let leader_controller = ReplicaLeaderController::new(
             self.ctx.local_spu_id(),
             replica_id,
@@ -497,8 +506,9 @@ impl ScDispatcher<FileReplica> {
             self.ctx.leader_state_owned(),
             self.ctx.followers_sink_owned(),
             shared_sc_sink,
-            self.ctx.offset_channel().sender(),
+            offset_publisher,
             self.max_bytes,
+            scrub_interval,
         );
         leader_controller.run();
     }
@@ -513,6 +523,18 @@ impl ScDispatcher<FileReplica> {
         if self.ctx.leaders_state().remove_replica(id).await.is_none() {
             error!("failed to find leader replica when removing");
         }
+        self.leadership_changed(id).await;
+    }
+
+    /// drop state which was built while this SPU was leader of replica
+    async fn leadership_changed(&self, id: &ReplicaKey) {
+        self.ctx.offset_publishers().remove(id);
+        if id.topic == CONSUMER_OFFSETS_TOPIC {
+            self.ctx
+                .group_coordinator()
+                .reset_offsets(id.partition)
+                .await;
+        }
     }
 
     /// Promote follower replica as leader,
@@ -538,12 +560,15 @@ impl ScDispatcher<FileReplica> {
                 old_replica.id
             );
 
-            let leader_state = LeaderReplicaState::new(
+            let mut leader_state = LeaderReplicaState::new(
                 new_replica.id.clone(),
                 new_replica.leader,
                 follower_replica.storage_owned(),
                 new_replica.replicas,
-            );
+            )
+            .with_log_append_time(new_replica.config.is_log_append_time())
+            .with_min_in_sync_replicas(new_replica.config.min_insync_replicas());
+            leader_state.restore_producer_sequences().await;
 
             self.spawn_leader_controller(new_replica.id, leader_state, shared_sc_sink)
                 .await;
@@ -559,6 +584,7 @@ impl ScDispatcher<FileReplica> {
             self.ctx.leaders_state().remove_replica(&replica.id).await
         {
             drop(leader_replica_state);
+            self.leadership_changed(&replica.id).await;
             // for now, we re-scan file replica
             self.add_follower_replica(replica).await;
         } else {
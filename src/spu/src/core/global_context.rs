@@ -11,7 +11,6 @@ use fluvio_socket::SinkPool;
 use fluvio_types::SpuId;
 use fluvio_storage::ReplicaStorage;
 
-use crate::core::broadcast::Channel;
 use crate::config::SpuConfig;
 use crate::controllers::leader_replica::SharedReplicaLeadersState;
 use crate::controllers::follower_replica::FollowersState;
@@ -22,7 +21,9 @@ use super::SharedReplicaLocalStore;
 use super::spus::SpuLocalStore;
 use super::replica::ReplicaStore;
 use super::SharedSpuConfig;
-use super::OffsetUpdateEvent;
+use super::ReplicaOffsetPublishers;
+use super::GroupCoordinator;
+use super::ProducerIdAllocator;
 
 #[derive(Debug)]
 pub struct GlobalContext<S> {
@@ -32,7 +33,9 @@ pub struct GlobalContext<S> {
     leaders_state: SharedReplicaLeadersState<S>,
     followers_state: SharedFollowersState<S>,
     follower_sinks: SharedSinkPool<SpuId>,
-    offset_channel: Channel<OffsetUpdateEvent>,
+    offset_publishers: ReplicaOffsetPublishers,
+    group_coordinator: GroupCoordinator,
+    producer_ids: ProducerIdAllocator,
 }
 
 // -----------------------------------
@@ -48,6 +51,8 @@ where
     }
 
     pub fn new(spu_config: SpuConfig) -> Self {
+        let group_coordinator = GroupCoordinator::new();
+        let producer_ids = ProducerIdAllocator::new(spu_config.id);
         GlobalContext {
             spu_localstore: SpuLocalStore::new_shared(),
             replica_localstore: ReplicaStore::new_shared(),
@@ -55,7 +60,9 @@ where
             follower_sinks: SinkPool::new_shared(),
             leaders_state: ReplicaLeadersState::new_shared(),
             followers_state: FollowersState::new_shared(),
-            offset_channel: Channel::new(100),
+            offset_publishers: ReplicaOffsetPublishers::default(),
+            group_coordinator,
+            producer_ids,
         }
     }
 
@@ -107,7 +114,16 @@ where
         self.config.clone()
     }
 
-    pub fn offset_channel(&self) -> &Channel<OffsetUpdateEvent> {
-        &self.offset_channel
+    /// notifications of offset changes of leader replicas
+    pub fn offset_publishers(&self) -> &ReplicaOffsetPublishers {
+        &self.offset_publishers
+    }
+
+    pub fn group_coordinator(&self) -> &GroupCoordinator {
+        &self.group_coordinator
+    }
+
+    pub fn producer_ids(&self) -> &ProducerIdAllocator {
+        &self.producer_ids
     }
 }
@@ -0,0 +1,747 @@
+//!
+//! # Consumer Group Coordinator
+//!
+//! Keeps membership of consumer groups and encodes their committed offsets.
+//! Membership lives in memory and is re-built by members re-joining.
+//! Committed offsets are written as records to the group's partition of consumer
+//! offsets topic, so they are replicated as any other records. Record key is group,
+//! topic and partition, so compaction of the topic keeps only last committed offsets.
+//!
+//! Committed offsets are also cached per partition of consumer offsets topic. Cache is
+//! loaded from log of partition once after SPU becomes its leader, then updated by commits.
+//!
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::io::Error as IoError;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use async_rwlock::RwLock;
+use tracing::debug;
+use tracing::trace;
+use tracing::warn;
+
+use dataplane::batch::DefaultBatch;
+use dataplane::core::Decoder;
+use dataplane::core::Encoder;
+use dataplane::current_timestamp;
+use dataplane::derive::Decode;
+use dataplane::derive::Encode;
+use dataplane::record::{DefaultRecord, RecordSet};
+use dataplane::ErrorCode;
+use dataplane::Offset;
+use fluvio_spu_schema::server::consumer_group::GroupAssignment;
+use fluvio_spu_schema::server::consumer_group::PartitionOffset;
+
+const MAX_GROUP_NAME_LEN: usize = 255;
+
+/// key of committed offset record, value is the offset
+#[derive(Encode, Decode, Default, Debug)]
+struct OffsetKey {
+    group: String,
+    topic: String,
+    partition: i32,
+}
+
+#[derive(Debug)]
+struct GroupMember {
+    session_timeout: Duration,
+    last_heartbeat: Instant,
+}
+
+impl GroupMember {
+    fn is_expired(&self, now: Instant) -> bool {
+        now.duration_since(self.last_heartbeat) > self.session_timeout
+    }
+}
+
+#[derive(Debug, Default)]
+struct ConsumerGroup {
+    topic: String,
+    partitions: i32,
+    generation: i32,
+    members: BTreeMap<String, GroupMember>,
+    assignments: HashMap<String, Vec<i32>>,
+    /// members which have not confirmed current generation yet,
+    /// assignments are handed out once every member has confirmed
+    pending: BTreeSet<String>,
+}
+
+impl ConsumerGroup {
+    fn assignment(&self, member_id: &str) -> GroupAssignment {
+        if !self.pending.is_empty() {
+            // members revoke partitions of previous generation first
+            return GroupAssignment {
+                generation: self.generation,
+                partitions: vec![],
+                rebalancing: true,
+            };
+        }
+        GroupAssignment {
+            generation: self.generation,
+            partitions: self.assignments.get(member_id).cloned().unwrap_or_default(),
+            rebalancing: false,
+        }
+    }
+
+    /// member has revoked partitions of earlier generations once it consumes `generation`
+    fn confirm_generation(&mut self, member_id: &str, generation: i32) {
+        if generation == self.generation && self.pending.remove(member_id) {
+            trace!("member: {} confirmed generation: {}", member_id, generation);
+            if self.pending.is_empty() {
+                debug!(
+                    "consumer group generation: {} confirmed by all members",
+                    generation
+                );
+            }
+        }
+    }
+
+    /// remove members without heartbeat within session timeout
+    /// return true if any member was removed
+    fn expire_members(&mut self, now: Instant) -> bool {
+        let before = self.members.len();
+        self.members.retain(|member_id, member| {
+            let expired = member.is_expired(now);
+            if expired {
+                debug!("consumer group member: {} expired", member_id);
+            }
+            !expired
+        });
+        before != self.members.len()
+    }
+
+    /// assign partitions to members in round robin fashion and start new generation
+    ///
+    /// assignments are held back until every member confirms new generation
+    fn rebalance(&mut self) {
+        self.generation += 1;
+        self.pending = self.members.keys().cloned().collect();
+        self.assignments.clear();
+        let members: Vec<&String> = self.members.keys().collect();
+        if !members.is_empty() {
+            for partition in 0..self.partitions {
+                let member = members[partition as usize % members.len()];
+                self.assignments
+                    .entry(member.clone())
+                    .or_default()
+                    .push(partition);
+            }
+        }
+        debug!(
+            "consumer group rebalanced, generation: {}, assignments: {:?}",
+            self.generation, self.assignments
+        );
+    }
+
+    /// validate member is current member of group
+    fn check_member(&mut self, member_id: &str, now: Instant) -> Result<(), ErrorCode> {
+        if self.expire_members(now) {
+            self.rebalance();
+        }
+        match self.members.get_mut(member_id) {
+            Some(member) => {
+                member.last_heartbeat = now;
+                Ok(())
+            }
+            None => Err(ErrorCode::UnknownMemberId),
+        }
+    }
+}
+
+/// committed offsets stored in a partition of consumer offsets topic
+#[derive(Debug, Default)]
+struct PartitionOffsets {
+    /// log of partition has been read since SPU became its leader
+    loaded: bool,
+    /// incremented whenever cache is reset, loads started before are discarded
+    epoch: u64,
+    /// committed offsets of group and topic by partition, with end offset
+    /// of their record in log, so earlier commits don't replace later ones
+    offsets: HashMap<(String, String), BTreeMap<i32, (Offset, Offset)>>,
+}
+
+impl PartitionOffsets {
+    fn insert(&mut self, key: OffsetKey, offset: Offset, end_offset: Offset) {
+        let committed = self
+            .offsets
+            .entry((key.group, key.topic))
+            .or_default()
+            .entry(key.partition)
+            .or_insert((offset, end_offset));
+        if committed.1 < end_offset {
+            *committed = (offset, end_offset);
+        }
+    }
+}
+
+/// Coordinates consumer groups whose consumer offsets partition is led by this SPU
+#[derive(Debug, Default)]
+pub struct GroupCoordinator {
+    groups: RwLock<HashMap<String, ConsumerGroup>>,
+    member_seq: AtomicU64,
+    /// committed offsets by partition of consumer offsets topic
+    offsets: RwLock<HashMap<i32, PartitionOffsets>>,
+}
+
+impl GroupCoordinator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn next_member_id(&self, group: &str) -> String {
+        let seq = self.member_seq.fetch_add(1, Ordering::SeqCst);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_millis())
+            .unwrap_or_default();
+        format!("{}-{}-{}", group, now, seq)
+    }
+
+    /// join group, new member id is generated if member is not known
+    /// return member id and its assignment
+    pub async fn join(
+        &self,
+        group: &str,
+        topic: &str,
+        partitions: i32,
+        member_id: &str,
+        session_timeout: Duration,
+    ) -> Result<(String, GroupAssignment), ErrorCode> {
+        validate_group_name(group)?;
+        let now = Instant::now();
+        let mut groups = self.groups.write().await;
+        let consumer_group = groups.entry(group.to_owned()).or_default();
+        consumer_group.expire_members(now);
+
+        if consumer_group.members.is_empty() {
+            consumer_group.topic = topic.to_owned();
+        } else if consumer_group.topic != topic {
+            debug!(
+                "group: {} is consuming topic: {}, rejecting join for: {}",
+                group, consumer_group.topic, topic
+            );
+            return Err(ErrorCode::InconsistentGroupTopic);
+        }
+
+        let member_id = if consumer_group.members.contains_key(member_id) {
+            member_id.to_owned()
+        } else {
+            self.next_member_id(group)
+        };
+        consumer_group.members.insert(
+            member_id.clone(),
+            GroupMember {
+                session_timeout,
+                last_heartbeat: now,
+            },
+        );
+        consumer_group.partitions = consumer_group.partitions.max(partitions);
+        consumer_group.rebalance();
+        debug!("member: {} joined group: {}", member_id, group);
+
+        let assignment = consumer_group.assignment(&member_id);
+        Ok((member_id, assignment))
+    }
+
+    /// refresh membership and return current assignment of member
+    ///
+    /// `generation` is generation of assignment member is consuming
+    pub async fn heartbeat(
+        &self,
+        group: &str,
+        member_id: &str,
+        generation: i32,
+    ) -> Result<GroupAssignment, ErrorCode> {
+        let mut groups = self.groups.write().await;
+        let consumer_group = groups.get_mut(group).ok_or(ErrorCode::UnknownMemberId)?;
+        consumer_group.check_member(member_id, Instant::now())?;
+        consumer_group.confirm_generation(member_id, generation);
+        trace!("heartbeat from member: {} of group: {}", member_id, group);
+        Ok(consumer_group.assignment(member_id))
+    }
+
+    /// remove member from group, partitions are re-assigned to remaining members
+    pub async fn leave(&self, group: &str, member_id: &str) -> Result<(), ErrorCode> {
+        let mut groups = self.groups.write().await;
+        let consumer_group = groups.get_mut(group).ok_or(ErrorCode::UnknownMemberId)?;
+        if consumer_group.members.remove(member_id).is_none() {
+            return Err(ErrorCode::UnknownMemberId);
+        }
+        debug!("member: {} left group: {}", member_id, group);
+        consumer_group.expire_members(Instant::now());
+        consumer_group.rebalance();
+        Ok(())
+    }
+
+    /// check that member can commit offsets of topic for its generation
+    pub async fn validate_commit(
+        &self,
+        group: &str,
+        member_id: &str,
+        generation: i32,
+        topic: &str,
+    ) -> Result<(), ErrorCode> {
+        let mut groups = self.groups.write().await;
+        let consumer_group = groups.get_mut(group).ok_or(ErrorCode::UnknownMemberId)?;
+        consumer_group.check_member(member_id, Instant::now())?;
+        if consumer_group.generation != generation {
+            debug!(
+                "member: {} committed with generation: {}, current: {}",
+                member_id, generation, consumer_group.generation
+            );
+            return Err(ErrorCode::IllegalGeneration);
+        }
+        if consumer_group.topic != topic {
+            return Err(ErrorCode::InconsistentGroupTopic);
+        }
+        Ok(())
+    }
+
+    /// committed offsets of group for topic, stored in `partition` of consumer offsets topic
+    ///
+    /// if log of partition has not been loaded, return epoch of cache to be passed to `load_offsets`
+    pub async fn fetch_offsets(
+        &self,
+        partition: i32,
+        group: &str,
+        topic: &str,
+    ) -> Result<Vec<PartitionOffset>, u64> {
+        let offsets = self.offsets.read().await;
+        let partition_offsets = match offsets.get(&partition) {
+            Some(partition_offsets) if partition_offsets.loaded => partition_offsets,
+            Some(partition_offsets) => return Err(partition_offsets.epoch),
+            None => return Err(0),
+        };
+        let committed = partition_offsets
+            .offsets
+            .get(&(group.to_owned(), topic.to_owned()));
+        trace!("committed offsets of group: {}: {:?}", group, committed);
+        Ok(committed
+            .map(|committed| {
+                committed
+                    .iter()
+                    .map(|(partition, (offset, _))| PartitionOffset {
+                        partition: *partition,
+                        offset: *offset,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// load committed offsets from batches of log of `partition`
+    ///
+    /// batches are ignored if cache was reset after `epoch`
+    pub async fn load_offsets(&self, partition: i32, epoch: u64, batches: &[DefaultBatch]) {
+        let mut offsets = self.offsets.write().await;
+        let partition_offsets = offsets.entry(partition).or_default();
+        if partition_offsets.epoch != epoch {
+            debug!(
+                "committed offsets of partition: {} were reset while loading",
+                partition
+            );
+            return;
+        }
+        for batch in batches {
+            for record in batch.records() {
+                let end_offset = batch.get_base_offset() + record.get_offset_delta() + 1;
+                if let Some((key, offset)) = decode_offset_record(record) {
+                    partition_offsets.insert(key, offset, end_offset);
+                }
+            }
+        }
+        partition_offsets.loaded = true;
+        debug!("committed offsets of partition: {} loaded", partition);
+    }
+
+    /// cache offsets committed to `partition`, their records end at `end_offset` of its log
+    pub async fn update_offsets(
+        &self,
+        partition: i32,
+        group: &str,
+        topic: &str,
+        partitions: &[PartitionOffset],
+        end_offset: Offset,
+    ) {
+        let mut offsets = self.offsets.write().await;
+        let partition_offsets = offsets.entry(partition).or_default();
+        for committed in partitions {
+            let key = OffsetKey {
+                group: group.to_owned(),
+                topic: topic.to_owned(),
+                partition: committed.partition,
+            };
+            partition_offsets.insert(key, committed.offset, end_offset);
+        }
+    }
+
+    /// drop cached offsets of `partition`, when SPU becomes or is no longer its leader
+    pub async fn reset_offsets(&self, partition: i32) {
+        let mut offsets = self.offsets.write().await;
+        let partition_offsets = offsets.entry(partition).or_default();
+        partition_offsets.loaded = false;
+        partition_offsets.epoch += 1;
+        partition_offsets.offsets.clear();
+    }
+}
+
+/// group name must be non empty and only contain ascii alphanumerics, '.', '_' and '-'
+pub fn validate_group_name(group: &str) -> Result<(), ErrorCode> {
+    let valid = !group.is_empty()
+        && group.len() <= MAX_GROUP_NAME_LEN
+        && group
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        debug!("invalid consumer group name: {:?}", group);
+        Err(ErrorCode::InvalidGroupId)
+    }
+}
+
+/// records of committed offsets to be written to consumer offsets topic
+pub fn offset_records(
+    group: &str,
+    topic: &str,
+    partitions: &[PartitionOffset],
+) -> Result<RecordSet, IoError> {
+    let now = current_timestamp();
+    let mut batch = DefaultBatch::default();
+    for partition in partitions {
+        let key = OffsetKey {
+            group: group.to_owned(),
+            topic: topic.to_owned(),
+            partition: partition.partition,
+        };
+        let mut key_bytes = vec![];
+        key.encode(&mut key_bytes, 0)?;
+        let mut value_bytes = vec![];
+        partition.offset.encode(&mut value_bytes, 0)?;
+
+        let mut record = DefaultRecord::from(value_bytes);
+        record.key = key_bytes.into();
+        batch.add_record_with_timestamp(record, now);
+    }
+    Ok(RecordSet::default().add(batch))
+}
+
+/// key and committed offset of record of consumer offsets topic
+fn decode_offset_record(record: &DefaultRecord) -> Option<(OffsetKey, Offset)> {
+    let (key_bytes, value_bytes) =
+        match (record.key.inner_value_ref(), record.value.inner_value_ref()) {
+            (Some(key), Some(value)) => (key, value),
+            _ => return None,
+        };
+    let key = match OffsetKey::decode_from(&mut Cursor::new(key_bytes), 0) {
+        Ok(key) => key,
+        Err(err) => {
+            warn!("skipping committed offset with invalid key: {}", err);
+            return None;
+        }
+    };
+    match Offset::decode_from(&mut Cursor::new(value_bytes), 0) {
+        Ok(offset) => Some((key, offset)),
+        Err(err) => {
+            warn!("skipping committed offset with invalid value: {}", err);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::time::Duration;
+
+    use fluvio_future::test_async;
+    use dataplane::ErrorCode;
+    use fluvio_spu_schema::server::consumer_group::PartitionOffset;
+
+    use super::GroupCoordinator;
+    use super::{offset_records, validate_group_name, MAX_GROUP_NAME_LEN};
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    #[test_async]
+    async fn test_group_membership() -> Result<(), ()> {
+        let coordinator = GroupCoordinator::new();
+
+        let (member1, assignment) = coordinator
+            .join("group", "topic", 3, "", TIMEOUT)
+            .await
+            .expect("join");
+        assert_eq!(assignment.generation, 1);
+        assert!(assignment.rebalancing);
+        assert!(assignment.partitions.is_empty());
+
+        // partitions are handed out once generation is confirmed
+        let assignment1 = coordinator
+            .heartbeat("group", &member1, 1)
+            .await
+            .expect("hb");
+        assert!(!assignment1.rebalancing);
+        assert_eq!(assignment1.partitions, vec![0, 1, 2]);
+
+        let (member2, assignment) = coordinator
+            .join("group", "topic", 3, "", TIMEOUT)
+            .await
+            .expect("join");
+        assert_ne!(member1, member2);
+        assert_eq!(assignment.generation, 2);
+
+        // partitions are split between members
+        coordinator
+            .heartbeat("group", &member1, 2)
+            .await
+            .expect("hb");
+        let assignment2 = coordinator
+            .heartbeat("group", &member2, 2)
+            .await
+            .expect("hb");
+        let assignment1 = coordinator
+            .heartbeat("group", &member1, 2)
+            .await
+            .expect("hb");
+        assert_eq!(assignment1.generation, 2);
+        let mut all = assignment1.partitions.clone();
+        all.extend(assignment2.partitions.iter());
+        all.sort_unstable();
+        assert_eq!(all, vec![0, 1, 2]);
+
+        assert_eq!(
+            coordinator
+                .join("group", "other", 3, "", TIMEOUT)
+                .await
+                .unwrap_err(),
+            ErrorCode::InconsistentGroupTopic
+        );
+
+        coordinator.leave("group", &member2).await.expect("leave");
+        let assignment1 = coordinator
+            .heartbeat("group", &member1, 3)
+            .await
+            .expect("hb");
+        assert_eq!(assignment1.generation, 3);
+        assert_eq!(assignment1.partitions, vec![0, 1, 2]);
+        assert_eq!(
+            coordinator
+                .heartbeat("group", &member2, 3)
+                .await
+                .unwrap_err(),
+            ErrorCode::UnknownMemberId
+        );
+
+        // member without heartbeat is removed
+        let (member3, _) = coordinator
+            .join("group", "topic", 3, "", Duration::from_millis(0))
+            .await
+            .expect("join");
+        fluvio_future::timer::sleep(Duration::from_millis(5)).await;
+        let assignment1 = coordinator
+            .heartbeat("group", &member1, 4)
+            .await
+            .expect("hb");
+        assert_eq!(assignment1.generation, 5);
+        let assignment1 = coordinator
+            .heartbeat("group", &member1, 5)
+            .await
+            .expect("hb");
+        assert_eq!(assignment1.partitions, vec![0, 1, 2]);
+        assert_eq!(
+            coordinator
+                .heartbeat("group", &member3, 5)
+                .await
+                .unwrap_err(),
+            ErrorCode::UnknownMemberId
+        );
+        Ok(())
+    }
+
+    #[test_async]
+    async fn test_group_revoke_before_assign() -> Result<(), ()> {
+        let coordinator = GroupCoordinator::new();
+
+        let (member1, _) = coordinator
+            .join("group", "topic", 2, "", TIMEOUT)
+            .await
+            .expect("join");
+        let assignment1 = coordinator
+            .heartbeat("group", &member1, 1)
+            .await
+            .expect("hb");
+        assert_eq!(assignment1.partitions, vec![0, 1]);
+
+        // new member gets no partitions while member1 may still consume them
+        let (member2, _) = coordinator
+            .join("group", "topic", 2, "", TIMEOUT)
+            .await
+            .expect("join");
+        let assignment2 = coordinator
+            .heartbeat("group", &member2, 2)
+            .await
+            .expect("hb");
+        assert!(assignment2.rebalancing);
+        assert!(assignment2.partitions.is_empty());
+
+        // member1 still consuming generation 1 is told to revoke
+        let assignment1 = coordinator
+            .heartbeat("group", &member1, 1)
+            .await
+            .expect("hb");
+        assert!(assignment1.rebalancing);
+        assert!(assignment1.partitions.is_empty());
+        let assignment2 = coordinator
+            .heartbeat("group", &member2, 2)
+            .await
+            .expect("hb");
+        assert!(assignment2.partitions.is_empty());
+
+        // once member1 confirms, partitions are handed out
+        let assignment1 = coordinator
+            .heartbeat("group", &member1, 2)
+            .await
+            .expect("hb");
+        let assignment2 = coordinator
+            .heartbeat("group", &member2, 2)
+            .await
+            .expect("hb");
+        assert!(!assignment1.rebalancing);
+        assert_eq!(assignment1.partitions.len(), 1);
+        assert_eq!(assignment2.partitions.len(), 1);
+        assert_ne!(assignment1.partitions, assignment2.partitions);
+        Ok(())
+    }
+
+    #[test_async]
+    async fn test_group_validate_commit() -> Result<(), ()> {
+        let coordinator = GroupCoordinator::new();
+
+        let (member, assignment) = coordinator
+            .join("group", "topic", 2, "", TIMEOUT)
+            .await
+            .expect("join");
+        assert_eq!(
+            coordinator
+                .validate_commit("group", &member, assignment.generation - 1, "topic")
+                .await
+                .unwrap_err(),
+            ErrorCode::IllegalGeneration
+        );
+        assert_eq!(
+            coordinator
+                .validate_commit("group", &member, assignment.generation, "other")
+                .await
+                .unwrap_err(),
+            ErrorCode::InconsistentGroupTopic
+        );
+        assert_eq!(
+            coordinator
+                .validate_commit("group", "unknown", assignment.generation, "topic")
+                .await
+                .unwrap_err(),
+            ErrorCode::UnknownMemberId
+        );
+        coordinator
+            .validate_commit("group", &member, assignment.generation, "topic")
+            .await
+            .expect("commit");
+        Ok(())
+    }
+
+    #[test_async]
+    async fn test_committed_offsets_cache() -> Result<(), ()> {
+        let offsets = |list: &[(i32, i64)]| -> Vec<PartitionOffset> {
+            list.iter()
+                .map(|(partition, offset)| PartitionOffset {
+                    partition: *partition,
+                    offset: *offset,
+                })
+                .collect()
+        };
+        let coordinator = GroupCoordinator::new();
+
+        // log of partition must be loaded first
+        let epoch = coordinator
+            .fetch_offsets(0, "group", "topic")
+            .await
+            .unwrap_err();
+
+        let mut batches = vec![];
+        let mut end_offset = 0;
+        for (group, topic, partitions) in [
+            ("group", "topic", offsets(&[(0, 10), (1, 20)])),
+            ("other", "topic", offsets(&[(0, 100)])),
+            ("group", "other", offsets(&[(1, 200)])),
+            ("group", "topic", offsets(&[(1, 25)])),
+        ] {
+            let records = offset_records(group, topic, &partitions).expect("records");
+            for mut batch in records.batches {
+                batch.set_base_offset(end_offset);
+                end_offset += batch.records().len() as i64;
+                batches.push(batch);
+            }
+        }
+        coordinator.load_offsets(0, epoch, &batches).await;
+
+        // last commit of partition wins
+        assert_eq!(
+            coordinator.fetch_offsets(0, "group", "topic").await,
+            Ok(offsets(&[(0, 10), (1, 25)]))
+        );
+        assert_eq!(
+            coordinator.fetch_offsets(0, "other", "topic").await,
+            Ok(offsets(&[(0, 100)]))
+        );
+        assert_eq!(
+            coordinator.fetch_offsets(0, "none", "topic").await,
+            Ok(vec![])
+        );
+
+        // earlier records don't replace later commit
+        coordinator
+            .update_offsets(0, "group", "topic", &offsets(&[(0, 30)]), end_offset + 1)
+            .await;
+        coordinator.load_offsets(0, epoch, &batches).await;
+        assert_eq!(
+            coordinator.fetch_offsets(0, "group", "topic").await,
+            Ok(offsets(&[(0, 30), (1, 25)]))
+        );
+
+        // reset cache discards loads started before
+        coordinator.reset_offsets(0).await;
+        let new_epoch = coordinator
+            .fetch_offsets(0, "group", "topic")
+            .await
+            .unwrap_err();
+        assert_ne!(new_epoch, epoch);
+        coordinator.load_offsets(0, epoch, &batches).await;
+        assert!(coordinator
+            .fetch_offsets(0, "group", "topic")
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_group_name() {
+        assert!(validate_group_name("my-group_1.x").is_ok());
+        for invalid in &["", "../x", "a/b", "a b"] {
+            assert_eq!(
+                validate_group_name(invalid).unwrap_err(),
+                ErrorCode::InvalidGroupId
+            );
+        }
+        assert_eq!(
+            validate_group_name(&"a".repeat(MAX_GROUP_NAME_LEN + 1)).unwrap_err(),
+            ErrorCode::InvalidGroupId
+        );
+    }
+}
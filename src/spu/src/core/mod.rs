@@ -1,6 +1,9 @@
 mod global_context;
 mod store;
 pub(crate) mod storage;
+pub mod group_coordinator;
+mod producer_ids;
+mod offset_publisher;
 
 pub mod spus;
 pub mod replica;
@@ -12,6 +15,9 @@ pub use self::store::SpecChange;
 
 pub use self::spus::SpuLocalStore;
 pub use self::replica::SharedReplicaLocalStore;
+pub use self::group_coordinator::GroupCoordinator;
+pub use self::producer_ids::ProducerIdAllocator;
+pub use self::offset_publisher::{OffsetPublisher, OffsetChangeListener, ReplicaOffsetPublishers};
 
 use std::sync::Arc;
 use ::fluvio_storage::FileReplica;
@@ -39,33 +45,3 @@ mod event {
         pub hw: Offset,
     }
 }
-
-mod broadcast {
-
-    pub use tokio::sync::broadcast::Receiver;
-    pub use tokio::sync::broadcast::Sender;
-    pub use tokio::sync::broadcast::channel;
-    pub use tokio::sync::broadcast::RecvError;
-
-    #[derive(Debug)]
-    pub struct Channel<T> {
-        receiver: Receiver<T>,
-        sender: Sender<T>,
-    }
-
-    impl<T> Channel<T> {
-        pub fn new(capacity: usize) -> Self {
-            let (sender, receiver) = channel(capacity);
-            Self { receiver, sender }
-        }
-
-        /// create new clone of sender
-        pub fn receiver(&self) -> Receiver<T> {
-            self.sender.subscribe()
-        }
-
-        pub fn sender(&self) -> Sender<T> {
-            self.sender.clone()
-        }
-    }
-}
@@ -0,0 +1,230 @@
+//!
+//! # Offset Publisher
+//!
+//! Notifies listeners of a replica when its high watermark or end offset changes.
+//! Publishers exist only for replicas led by this SPU, listeners of a replica
+//! are closed when its leadership changes.
+//!
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use event_listener::Event;
+
+use dataplane::Offset;
+use fluvio_controlplane_metadata::partition::ReplicaKey;
+
+use super::OffsetUpdateEvent;
+
+/// latest offsets of a replica
+///
+/// listeners only keep track of the offsets they have seen, so they can't fall
+/// behind; a listener which misses several updates gets the latest offsets.
+#[derive(Debug, Default)]
+pub struct OffsetPublisher {
+    /// high watermark and end offset, so they are always seen together
+    offsets: Mutex<(Offset, Offset)>,
+    /// replica is no longer led by this SPU
+    closed: AtomicBool,
+    event: Event,
+}
+
+impl OffsetPublisher {
+    /// update offsets and wake up listeners
+    pub fn update(&self, hw: Offset, leo: Offset) {
+        *self.offsets.lock().expect("offsets lock") = (hw, leo);
+        self.event.notify(usize::MAX);
+    }
+
+    fn current(&self) -> (Offset, Offset) {
+        *self.offsets.lock().expect("offsets lock")
+    }
+
+    /// stop publishing and wake up listeners, so they can find out
+    fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.event.notify(usize::MAX);
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+}
+
+/// Listens to offset changes of a single replica
+#[derive(Debug)]
+pub struct OffsetChangeListener {
+    replica_id: ReplicaKey,
+    publisher: Arc<OffsetPublisher>,
+    last: (Offset, Offset),
+}
+
+impl OffsetChangeListener {
+    pub fn replica_id(&self) -> &ReplicaKey {
+        &self.replica_id
+    }
+
+    /// wait until offsets are different from offsets last returned by this listener,
+    /// return None if replica is no longer led by this SPU
+    pub async fn listen(&mut self) -> Option<OffsetUpdateEvent> {
+        loop {
+            if self.publisher.is_closed() {
+                return None;
+            }
+            if let Some(event) = self.changed() {
+                return Some(event);
+            }
+
+            let listener = self.publisher.event.listen();
+            // offsets may have changed before listener was registered
+            if self.publisher.is_closed() {
+                return None;
+            }
+            if let Some(event) = self.changed() {
+                return Some(event);
+            }
+            listener.await;
+        }
+    }
+
+    fn changed(&mut self) -> Option<OffsetUpdateEvent> {
+        let current = self.publisher.current();
+        if current == self.last {
+            return None;
+        }
+
+        self.last = current;
+        Some(OffsetUpdateEvent {
+            replica_id: self.replica_id.clone(),
+            hw: current.0,
+            leo: current.1,
+        })
+    }
+}
+
+/// Offset publishers of all replicas led by this SPU
+#[derive(Debug, Default)]
+pub struct ReplicaOffsetPublishers {
+    publishers: RwLock<HashMap<ReplicaKey, Arc<OffsetPublisher>>>,
+}
+
+impl ReplicaOffsetPublishers {
+    /// publisher of replica led by this SPU, created if it doesn't exist yet
+    pub fn publisher(&self, replica_id: &ReplicaKey) -> Arc<OffsetPublisher> {
+        if let Some(publisher) = self
+            .publishers
+            .read()
+            .expect("offset publishers lock")
+            .get(replica_id)
+        {
+            return publisher.clone();
+        }
+
+        self.publishers
+            .write()
+            .expect("offset publishers lock")
+            .entry(replica_id.clone())
+            .or_default()
+            .clone()
+    }
+
+    /// listener of replica, which is notified of changes after it is created.
+    /// None if replica is not led by this SPU
+    pub fn listener(&self, replica_id: &ReplicaKey) -> Option<OffsetChangeListener> {
+        let publisher = self
+            .publishers
+            .read()
+            .expect("offset publishers lock")
+            .get(replica_id)?
+            .clone();
+        let last = publisher.current();
+        Some(OffsetChangeListener {
+            replica_id: replica_id.clone(),
+            publisher,
+            last,
+        })
+    }
+
+    /// remove publisher of replica whose leadership has changed, its listeners are closed
+    pub fn remove(&self, replica_id: &ReplicaKey) {
+        if let Some(publisher) = self
+            .publishers
+            .write()
+            .expect("offset publishers lock")
+            .remove(replica_id)
+        {
+            publisher.close();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::time::Duration;
+
+    use futures_util::future::join;
+    use fluvio_future::test_async;
+    use fluvio_future::timer::sleep;
+    use fluvio_controlplane_metadata::partition::ReplicaKey;
+
+    use super::ReplicaOffsetPublishers;
+
+    #[test_async]
+    async fn test_offset_listener() -> Result<(), ()> {
+        let publishers = ReplicaOffsetPublishers::default();
+        let replica = ReplicaKey::new("test", 0);
+        let other = ReplicaKey::new("test", 1);
+        let publisher = publishers.publisher(&replica);
+        let other_publisher = publishers.publisher(&other);
+        let mut listener = publishers.listener(&replica).expect("listener");
+        let mut other_listener = publishers.listener(&other).expect("listener");
+
+        let update = async {
+            sleep(Duration::from_millis(10)).await;
+            publisher.update(1, 2);
+        };
+        let (_, event) = join(update, listener.listen()).await;
+        let event = event.expect("event");
+        assert_eq!(event.replica_id, replica);
+        assert_eq!((event.hw, event.leo), (1, 2));
+
+        // several updates are seen as latest offsets
+        publisher.update(2, 3);
+        publisher.update(3, 5);
+        let event = listener.listen().await.expect("event");
+        assert_eq!((event.hw, event.leo), (3, 5));
+
+        // listener of other replica is not notified
+        other_publisher.update(0, 1);
+        let event = other_listener.listen().await.expect("event");
+        assert_eq!(event.replica_id, other);
+        assert_eq!((event.hw, event.leo), (0, 1));
+
+        Ok(())
+    }
+
+    #[test_async]
+    async fn test_offset_listener_leadership() -> Result<(), ()> {
+        let publishers = ReplicaOffsetPublishers::default();
+        let replica = ReplicaKey::new("test", 0);
+
+        // no listener of replica which is not led
+        assert!(publishers.listener(&replica).is_none());
+
+        publishers.publisher(&replica);
+        let mut listener = publishers.listener(&replica).expect("listener");
+
+        let remove = async {
+            sleep(Duration::from_millis(10)).await;
+            publishers.remove(&replica);
+        };
+        let (_, event) = join(remove, listener.listen()).await;
+        assert!(event.is_none());
+        assert!(publishers.listener(&replica).is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use dataplane::current_timestamp;
+use fluvio_types::SpuId;
+
+/// bits of producer id used by per spu counter
+const COUNTER_BITS: u32 = 48;
+const COUNTER_MASK: i64 = (1 << COUNTER_BITS) - 1;
+
+/// Allocates ids of idempotent producers
+///
+/// Id is made of spu id in high bits and a counter in low bits, so ids
+/// allocated by different SPUs never collide. Counter starts at the time
+/// SPU is started in milliseconds, so ids are not reused after restart
+/// unless more than one id per millisecond was allocated.
+#[derive(Debug)]
+pub struct ProducerIdAllocator {
+    spu_id: SpuId,
+    counter: AtomicI64,
+}
+
+impl ProducerIdAllocator {
+    pub fn new(spu_id: SpuId) -> Self {
+        Self {
+            spu_id,
+            counter: AtomicI64::new(current_timestamp()),
+        }
+    }
+
+    pub fn next_id(&self) -> i64 {
+        let counter = self.counter.fetch_add(1, Ordering::SeqCst) & COUNTER_MASK;
+        ((self.spu_id as i64) << COUNTER_BITS) | counter
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::ProducerIdAllocator;
+
+    #[test]
+    fn test_producer_ids_are_unique_across_spus() {
+        let spu1 = ProducerIdAllocator::new(5001);
+        let spu2 = ProducerIdAllocator::new(5002);
+
+        let first = spu1.next_id();
+        assert_eq!(spu1.next_id(), first + 1);
+        assert!(first > 0);
+        assert_ne!(spu2.next_id() >> 48, first >> 48);
+    }
+}
@@ -1,7 +1,10 @@
+use fluvio_storage::CleanupPolicy;
 use fluvio_storage::ConfigOption;
 use fluvio_storage::FileReplica;
+use fluvio_storage::FlushPolicy;
 use fluvio_storage::StorageError;
 use fluvio_controlplane_metadata::partition::ReplicaKey;
+use fluvio_controlplane_metadata::topic::TopicConfig;
 use fluvio_types::SpuId;
 
 fn default_config(spu_id: SpuId, config: &ConfigOption) -> ConfigOption {
@@ -10,12 +13,42 @@ fn default_config(spu_id: SpuId, config: &ConfigOption) -> ConfigOption {
     new_config.base_dir(base_dir)
 }
 
+/// override spu defaults with settings from topic config
+fn topic_config(topic_config: &TopicConfig, config: ConfigOption) -> ConfigOption {
+    let mut config = config;
+    if let Some(segment_bytes) = topic_config.segment_bytes() {
+        config = config.segment_max_bytes(segment_bytes);
+    }
+    if let Some(seconds) = topic_config.retention_seconds() {
+        config = config.retention_seconds(Some(seconds));
+    }
+    if let Some(bytes) = topic_config.retention_bytes() {
+        config = config.retention_bytes(Some(bytes));
+    }
+    if let Some(records) = topic_config.flush_messages() {
+        config = config.flush_policy(FlushPolicy::EveryRecords(records));
+    } else if let Some(ms) = topic_config.flush_ms() {
+        config = config.flush_policy(FlushPolicy::Interval(ms));
+    }
+    if let Some(flush) = topic_config.flush_before_commit() {
+        config = config.flush_before_commit(flush);
+    }
+    if topic_config.is_compacted() {
+        config = config.cleanup_policy(CleanupPolicy::Compact);
+    }
+    if let Some(seconds) = topic_config.delete_retention_seconds() {
+        config = config.delete_retention_seconds(seconds);
+    }
+    config
+}
+
 /// Create new replica storage.  Each replica is stored with 'spu' prefix
 pub(crate) async fn create_replica_storage(
     local_spu: SpuId,
     replica: &ReplicaKey,
+    replica_config: &TopicConfig,
     base_config: &ConfigOption,
 ) -> Result<FileReplica, StorageError> {
-    let config = default_config(local_spu, base_config);
+    let config = topic_config(replica_config, default_config(local_spu, base_config));
     FileReplica::create(replica.topic.clone(), replica.partition as u32, 0, &config).await
 }
@@ -4,6 +4,7 @@ use async_channel::SendError;
 use fluvio_types::PartitionError;
 use fluvio_storage::StorageError;
 use fluvio_socket::FlvSocketError;
+use dataplane::ErrorCode;
 
 #[derive(Debug)]
 pub enum InternalServerError {
@@ -11,6 +12,8 @@ pub enum InternalServerError {
     PartitionError(PartitionError),
     SendError(String),
     SocketError(FlvSocketError),
+    /// batch of idempotent producer was rejected
+    SequenceError(ErrorCode),
 }
 
 impl fmt::Display for InternalServerError {
@@ -20,6 +23,7 @@ impl fmt::Display for InternalServerError {
             Self::PartitionError(err) => write!(f, "{}", err),
             Self::SendError(err) => write!(f, "{}", err),
             Self::SocketError(err) => write!(f, "{}", err),
+            Self::SequenceError(code) => write!(f, "{}", code.to_sentence()),
         }
     }
 }
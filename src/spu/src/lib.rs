@@ -7,6 +7,7 @@ mod config;
 mod core;
 mod services;
 mod controllers;
+mod smart_stream;
 
 //#[cfg(test)]
 //mod tests;
@@ -30,10 +30,28 @@ pub async fn handle_kf_lookup_version_request(
     ));
     response.api_keys.push(make_version_key(
         SpuServerApiKey::FetchOffsets,
-        FetchOffsetsRequest::DEFAULT_API_VERSION,
-        FetchOffsetsRequest::DEFAULT_API_VERSION,
+        FetchOffsetsRequest::MIN_API_VERSION,
+        FetchOffsetsRequest::MAX_API_VERSION,
     ));
 
+    // consumer groups
+    for api_key in &[
+        SpuServerApiKey::JoinGroup,
+        SpuServerApiKey::Heartbeat,
+        SpuServerApiKey::LeaveGroup,
+        SpuServerApiKey::OffsetCommit,
+        SpuServerApiKey::OffsetFetch,
+    ] {
+        response.api_keys.push(make_version_key(*api_key, 0, 0));
+    }
+
+    response
+        .api_keys
+        .push(make_version_key(SpuServerApiKey::InitProducerId, 0, 0));
+    response
+        .api_keys
+        .push(make_version_key(SpuServerApiKey::StreamFetchCredit, 0, 0));
+
     Ok(request.new_response(response))
 }
 
@@ -0,0 +1,245 @@
+use std::io::Error as IoError;
+use std::time::Duration;
+
+use tracing::debug;
+use tracing::warn;
+
+use dataplane::api::{RequestMessage, ResponseMessage};
+use dataplane::ErrorCode;
+use dataplane::ReplicaKey;
+use fluvio_spu_schema::server::consumer_group::*;
+use fluvio_types::defaults::CONSUMER_OFFSETS_TOPIC;
+
+use crate::core::DefaultSharedGlobalContext;
+use crate::core::group_coordinator::{offset_records, validate_group_name};
+
+/// max time to wait for committed offsets to be replicated
+const COMMIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// partition of consumer offsets topic which stores offsets of group
+fn coordinator_replica(group: &str) -> ReplicaKey {
+    ReplicaKey::new(CONSUMER_OFFSETS_TOPIC, coordinator_partition(group))
+}
+
+/// group is coordinated by leader of the group's partition of consumer offsets topic
+fn is_coordinator(ctx: &DefaultSharedGlobalContext, group: &str) -> bool {
+    ctx.leaders_state().has_replica(&coordinator_replica(group))
+}
+
+/// write committed offsets to consumer offsets topic and wait until they are replicated
+async fn commit_offsets(
+    ctx: &DefaultSharedGlobalContext,
+    request: &OffsetCommitRequest,
+) -> Result<(), ErrorCode> {
+    ctx.group_coordinator()
+        .validate_commit(
+            &request.group,
+            &request.member_id,
+            request.generation,
+            &request.topic,
+        )
+        .await?;
+
+    let replica = coordinator_replica(&request.group);
+    let records =
+        offset_records(&request.group, &request.topic, &request.partitions).map_err(|err| {
+            warn!("failed to encode committed offsets: {}", err);
+            ErrorCode::StorageError
+        })?;
+    let leo = match ctx
+        .leaders_state()
+        .send_records(&replica, records, true)
+        .await
+    {
+        Ok(Some(leo)) => leo,
+        Ok(None) => return Err(ErrorCode::NotCoordinator),
+        Err(err) => {
+            warn!(
+                "failed to write committed offsets to: {}, {:?}",
+                replica, err
+            );
+            return Err(ErrorCode::StorageError);
+        }
+    };
+
+    let default_min_in_sync_replicas = ctx.config().replication.min_in_sync_replicas;
+    if ctx
+        .leaders_state()
+        .wait_for_replication(&replica, leo, default_min_in_sync_replicas, COMMIT_TIMEOUT)
+        .await
+    {
+        ctx.group_coordinator()
+            .update_offsets(
+                replica.partition,
+                &request.group,
+                &request.topic,
+                &request.partitions,
+                leo,
+            )
+            .await;
+        Ok(())
+    } else {
+        warn!(
+            "committed offsets are not replicated to min in sync replicas: {}",
+            replica
+        );
+        Err(ErrorCode::NotEnoughReplicas)
+    }
+}
+
+/// last committed offsets of group, cache of coordinator is loaded
+/// from consumer offsets topic if it wasn't since SPU became leader
+async fn fetch_offsets(
+    ctx: &DefaultSharedGlobalContext,
+    request: &OffsetFetchRequest,
+) -> Result<Vec<PartitionOffset>, ErrorCode> {
+    validate_group_name(&request.group)?;
+    let replica = coordinator_replica(&request.group);
+    let coordinator = ctx.group_coordinator();
+    let epoch = match coordinator
+        .fetch_offsets(replica.partition, &request.group, &request.topic)
+        .await
+    {
+        Ok(offsets) => return Ok(offsets),
+        Err(epoch) => epoch,
+    };
+
+    // log is read without holding replica, so commits are not blocked
+    let snapshot = match ctx.leaders_state().get_replica(&replica) {
+        Some(leader_replica) => leader_replica.storage().log_snapshot(),
+        None => return Err(ErrorCode::NotCoordinator),
+    };
+    let batches = snapshot.read_committed_batches().await.map_err(|err| {
+        warn!(
+            "failed to read committed offsets from: {}, {}",
+            replica, err
+        );
+        ErrorCode::StorageError
+    })?;
+    coordinator
+        .load_offsets(replica.partition, epoch, &batches)
+        .await;
+
+    // cache is reset if leadership changed while loading
+    coordinator
+        .fetch_offsets(replica.partition, &request.group, &request.topic)
+        .await
+        .map_err(|_| ErrorCode::NotCoordinator)
+}
+
+pub async fn handle_join_group_request(
+    req_msg: RequestMessage<JoinGroupRequest>,
+    ctx: DefaultSharedGlobalContext,
+) -> Result<ResponseMessage<JoinGroupResponse>, IoError> {
+    let request = req_msg.request();
+    debug!("handling join group request: {:#?}", request);
+
+    let mut response = JoinGroupResponse::default();
+    if !is_coordinator(&ctx, &request.group) {
+        response.error_code = ErrorCode::NotCoordinator;
+        return Ok(req_msg.new_response(response));
+    }
+
+    match ctx
+        .group_coordinator()
+        .join(
+            &request.group,
+            &request.topic,
+            request.partitions,
+            &request.member_id,
+            Duration::from_millis(request.session_timeout_ms as u64),
+        )
+        .await
+    {
+        Ok((member_id, assignment)) => {
+            response.member_id = member_id;
+            response.assignment = assignment;
+        }
+        Err(error_code) => response.error_code = error_code,
+    }
+
+    Ok(req_msg.new_response(response))
+}
+
+pub async fn handle_heartbeat_request(
+    req_msg: RequestMessage<HeartbeatRequest>,
+    ctx: DefaultSharedGlobalContext,
+) -> Result<ResponseMessage<HeartbeatResponse>, IoError> {
+    let request = req_msg.request();
+
+    let mut response = HeartbeatResponse::default();
+    if !is_coordinator(&ctx, &request.group) {
+        response.error_code = ErrorCode::NotCoordinator;
+        return Ok(req_msg.new_response(response));
+    }
+
+    match ctx
+        .group_coordinator()
+        .heartbeat(&request.group, &request.member_id, request.generation)
+        .await
+    {
+        Ok(assignment) => response.assignment = assignment,
+        Err(error_code) => response.error_code = error_code,
+    }
+
+    Ok(req_msg.new_response(response))
+}
+
+pub async fn handle_leave_group_request(
+    req_msg: RequestMessage<LeaveGroupRequest>,
+    ctx: DefaultSharedGlobalContext,
+) -> Result<ResponseMessage<LeaveGroupResponse>, IoError> {
+    let request = req_msg.request();
+    debug!("handling leave group request: {:#?}", request);
+
+    let mut response = LeaveGroupResponse::default();
+    if !is_coordinator(&ctx, &request.group) {
+        response.error_code = ErrorCode::NotCoordinator;
+    } else if let Err(error_code) = ctx
+        .group_coordinator()
+        .leave(&request.group, &request.member_id)
+        .await
+    {
+        response.error_code = error_code;
+    }
+
+    Ok(req_msg.new_response(response))
+}
+
+pub async fn handle_offset_commit_request(
+    req_msg: RequestMessage<OffsetCommitRequest>,
+    ctx: DefaultSharedGlobalContext,
+) -> Result<ResponseMessage<OffsetCommitResponse>, IoError> {
+    let request = req_msg.request();
+    debug!("handling offset commit request: {:#?}", request);
+
+    let mut response = OffsetCommitResponse::default();
+    if !is_coordinator(&ctx, &request.group) {
+        response.error_code = ErrorCode::NotCoordinator;
+    } else if let Err(error_code) = commit_offsets(&ctx, request).await {
+        response.error_code = error_code;
+    }
+
+    Ok(req_msg.new_response(response))
+}
+
+pub async fn handle_offset_fetch_request(
+    req_msg: RequestMessage<OffsetFetchRequest>,
+    ctx: DefaultSharedGlobalContext,
+) -> Result<ResponseMessage<OffsetFetchResponse>, IoError> {
+    let request = req_msg.request();
+    debug!("handling offset fetch request: {:#?}", request);
+
+    let mut response = OffsetFetchResponse::default();
+    if !is_coordinator(&ctx, &request.group) {
+        response.error_code = ErrorCode::NotCoordinator;
+        return Ok(req_msg.new_response(response));
+    }
+
+    match fetch_offsets(&ctx, request).await {
+        Ok(partitions) => response.partitions = partitions,
+        Err(error_code) => response.error_code = error_code,
+    }
+
+    Ok(req_msg.new_response(response))
+}
@@ -4,6 +4,8 @@ mod produce_handler;
 mod fetch_handler;
 mod offset_request;
 mod stream_fetch;
+mod group_handler;
+mod stream_credit;
 
 use tracing::info;
 
@@ -11,12 +13,9 @@ use fluvio_service::FlvApiServer;
 use service_impl::PublicService;
 use fluvio_spu_schema::server::SpuServerRequest;
 use fluvio_spu_schema::server::SpuServerApiKey;
-use dataplane::ReplicaKey;
 
 use crate::core::DefaultSharedGlobalContext;
 
-pub type OffsetReplicaList = std::collections::HashSet<ReplicaKey>;
-
 pub(crate) type PublicApiServer =
     FlvApiServer<SpuServerRequest, SpuServerApiKey, DefaultSharedGlobalContext, PublicService>;
 
@@ -1,6 +1,6 @@
 use std::io::Error as IoError;
 
-use tracing::trace;
+use tracing::{error, trace};
 
 use dataplane::api::{RequestMessage, ResponseMessage};
 use fluvio_spu_schema::server::fetch_offset::FetchOffsetsRequest;
@@ -39,6 +39,18 @@ pub async fn handle_offset_request(
                 partition_response.error_code = ErrorCode::None;
                 partition_response.start_offset = storage.get_log_start_offset();
                 partition_response.last_stable_offset = storage.get_hw();
+                if let Some(timestamp) = partition_req.timestamp {
+                    match storage.find_offset_by_timestamp(timestamp).await {
+                        Ok(offset) => {
+                            partition_response.timestamp_offset =
+                                Some(offset.unwrap_or_else(|| storage.get_hw()));
+                        }
+                        Err(err) => {
+                            error!("offset by timestamp: {} failed: {}", rep_id, err);
+                            partition_response.error_code = ErrorCode::StorageError;
+                        }
+                    }
+                }
             } else {
                 trace!("offset fetch request is not found: {}", rep_id);
                 partition_response.error_code = ErrorCode::PartitionNotLeader;
@@ -1,5 +1,7 @@
 use std::io::Error;
+use std::time::{Duration, Instant};
 
+use tracing::debug;
 use tracing::warn;
 use tracing::trace;
 use tracing::error;
@@ -11,9 +13,24 @@ use dataplane::produce::{
 use dataplane::api::RequestMessage;
 use dataplane::api::ResponseMessage;
 use fluvio_controlplane_metadata::partition::ReplicaKey;
+use fluvio_spu_schema::server::init_producer_id::{InitProducerIdRequest, InitProducerIdResponse};
 
 use crate::core::DefaultSharedGlobalContext;
-
+use crate::InternalServerError;
+
+/// producer doesn't need acknowledgement, errors are not reported back
+const ACKS_NONE: i16 = 0;
+/// producer is acknowledged once leader has written the records
+const ACKS_LEADER: i16 = 1;
+
+/// handle produce request
+///
+/// acks of the request controls when records are acknowledged:
+/// * `0` - no acknowledgement, any error is only logged
+/// * `1` - records are written by leader
+/// * `-1` - records are written by leader and replicated to at least min in sync replicas
+///   of topic (including leader), waiting up to `timeout_ms`. If this doesn't happen,
+///   records stay written and `NotEnoughReplicasAfterAppend` is returned
 pub async fn handle_produce_request(
     request: RequestMessage<DefaultProduceRequest>,
     ctx: DefaultSharedGlobalContext,
@@ -23,7 +40,9 @@ pub async fn handle_produce_request(
 
     let mut response = ProduceResponse::default();
 
-    //let ack = produce_request.acks;
+    let acks = produce_request.acks;
+    let default_min_in_sync_replicas = ctx.config().replication.min_in_sync_replicas;
+    let deadline = Instant::now() + Duration::from_millis(produce_request.timeout_ms.max(0) as u64);
 
     for topic_request in produce_request.topics {
         let topic = &topic_request.name;
@@ -40,24 +59,63 @@ pub async fn handle_produce_request(
             let mut partition_response = PartitionProduceResponse::default();
             partition_response.partition_index = rep_id.partition;
 
-            match ctx
+            if partition_request.records.find_corrupt_batch().is_some() {
+                warn!("rejected records with crc mismatch for: {}", rep_id);
+                if acks != ACKS_NONE {
+                    partition_response.error_code = ErrorCode::CorruptMessage;
+                }
+                topic_response.partitions.push(partition_response);
+                continue;
+            }
+
+            let error_code = match ctx
                 .leaders_state()
                 .send_records(&rep_id, partition_request.records, true)
                 .await
             {
-                Ok(found_flag) => {
-                    if found_flag {
-                        trace!("records has successfull processed for: {}", rep_id);
-                        partition_response.error_code = ErrorCode::None;
+                Ok(Some(leo)) => {
+                    trace!("records has successfull processed for: {}", rep_id);
+                    if acks == ACKS_NONE || acks == ACKS_LEADER {
+                        ErrorCode::None
+                    } else if ctx
+                        .leaders_state()
+                        .wait_for_replication(
+                            &rep_id,
+                            leo,
+                            default_min_in_sync_replicas,
+                            deadline.saturating_duration_since(Instant::now()),
+                        )
+                        .await
+                    {
+                        trace!("records are replicated for: {}", rep_id);
+                        ErrorCode::None
                     } else {
-                        warn!("no replica found: {}", rep_id);
-                        partition_response.error_code = ErrorCode::NotLeaderForPartition;
+                        warn!(
+                            "records are not replicated to min in sync replicas: {}",
+                            rep_id
+                        );
+                        ErrorCode::NotEnoughReplicasAfterAppend
                     }
                 }
+                Ok(None) => {
+                    warn!("no replica found: {}", rep_id);
+                    ErrorCode::NotLeaderForPartition
+                }
+                Err(InternalServerError::SequenceError(error_code)) => {
+                    warn!(
+                        "rejected records of producer for: {}, {:?}",
+                        rep_id, error_code
+                    );
+                    error_code
+                }
                 Err(err) => {
                     error!("error: {:#?} writing to replica: {}", err, rep_id);
-                    partition_response.error_code = ErrorCode::StorageError;
+                    ErrorCode::StorageError
                 }
+            };
+
+            if acks != ACKS_NONE {
+                partition_response.error_code = error_code;
             }
 
             topic_response.partitions.push(partition_response);
@@ -70,3 +128,17 @@ pub async fn handle_produce_request(
 
     Ok(RequestMessage::<DefaultProduceRequest>::response_with_header(&header, response))
 }
+
+/// allocate id for idempotent producer
+pub async fn handle_init_producer_id_request(
+    request: RequestMessage<InitProducerIdRequest>,
+    ctx: DefaultSharedGlobalContext,
+) -> Result<ResponseMessage<InitProducerIdResponse>, Error> {
+    let response = InitProducerIdResponse {
+        producer_id: ctx.producer_ids().next_id(),
+        producer_epoch: 0,
+        ..Default::default()
+    };
+    debug!("allocated producer id: {}", response.producer_id);
+    Ok(request.new_response(response))
+}
@@ -1,13 +1,12 @@
 use std::sync::Arc;
-use std::collections::HashSet;
 
 use tracing::debug;
 use tracing::trace;
-use tracing::warn;
 use async_trait::async_trait;
 use futures_util::io::AsyncRead;
 use futures_util::io::AsyncWrite;
 use futures_util::stream::StreamExt;
+use futures_util::future::{pending, select_all, FutureExt};
 use tokio::select;
 use event_listener::Event;
 
@@ -22,12 +21,16 @@ use fluvio_spu_schema::server::SpuServerRequest;
 use fluvio_future::zero_copy::ZeroCopyWrite;
 
 use crate::core::DefaultSharedGlobalContext;
+use crate::core::OffsetChangeListener;
+use crate::core::OffsetUpdateEvent;
 use super::api_versions::handle_kf_lookup_version_request;
 use super::produce_handler::handle_produce_request;
+use super::produce_handler::handle_init_producer_id_request;
 use super::fetch_handler::handle_fetch_request;
 use super::offset_request::handle_offset_request;
 use super::stream_fetch::StreamFetchHandler;
-use super::OffsetReplicaList;
+use super::stream_credit::{StreamCredits, handle_stream_credit_request};
+use super::group_handler::*;
 
 #[derive(Debug)]
 pub struct PublicService {}
@@ -59,62 +62,36 @@ where
         let mut s_sink = sink.as_shared();
         let mut api_stream = stream.api_stream::<SpuServerRequest, SpuServerApiKey>();
 
-        let mut offset_replica_list: OffsetReplicaList = HashSet::new();
-
-        let mut receiver = context.offset_channel().receiver();
+        // listeners of replicas registered by sync request
+        let mut offset_listeners: Vec<OffsetChangeListener> = vec![];
 
         let end_event = Arc::new(Event::new());
+        let stream_credits = Arc::new(StreamCredits::default());
 
         loop {
             select! {
-                offset_event_res = receiver.recv() => {
-
-                    match offset_event_res {
-
-                        Ok(offset_event) => {
-                            trace!("conn: {}, offset event from leader {:#?}", s_sink.id(),offset_event);
-                            if offset_replica_list.contains(&offset_event.replica_id) {
-
-                                use fluvio_spu_schema::client::offset::ReplicaOffsetUpdateRequest;
-                                use fluvio_spu_schema::client::offset::ReplicaOffsetUpdate;
-                                use dataplane::ErrorCode;
-
-                                debug!("conn: {}, sending replica: {} hw: {}, leo: {}",s_sink.id(),
-                                    offset_event.replica_id,
-                                    offset_event.hw,
-                                    offset_event.leo);
-
-                                let req = ReplicaOffsetUpdateRequest {
-                                    offsets: vec![ReplicaOffsetUpdate {
-                                        replica: offset_event.replica_id,
-                                        error_code: ErrorCode::None,
-                                        start_offset: 0,
-                                        leo: offset_event.leo,
-                                        hw: offset_event.hw
-                                    }]
-                                };
-                                s_sink.send_request(&RequestMessage::new_request(req)).await?;
-
-                            }
-                        },
-
-                        Err(err) => {
-
-                            use tokio::sync::broadcast::RecvError;
-
-                            match err {
-                                RecvError::Closed => {
-                                    warn!("conn: {}, lost connection to event channel, closing conn",s_sink.id());
-                                    break;
-                                },
-                                RecvError::Lagged(lag) => {
-                                    warn!("conn: {}, lagging: {}",s_sink.id(),lag);
-                                }
-                            }
-
-                        }
-
-                    }
+                offset_event = next_offset_change(&mut offset_listeners) => {
+
+                    use fluvio_spu_schema::client::offset::ReplicaOffsetUpdateRequest;
+                    use fluvio_spu_schema::client::offset::ReplicaOffsetUpdate;
+                    use dataplane::ErrorCode;
+
+                    trace!("conn: {}, offset event from leader {:#?}", s_sink.id(),offset_event);
+                    debug!("conn: {}, sending replica: {} hw: {}, leo: {}",s_sink.id(),
+                        offset_event.replica_id,
+                        offset_event.hw,
+                        offset_event.leo);
+
+                    let req = ReplicaOffsetUpdateRequest {
+                        offsets: vec![ReplicaOffsetUpdate {
+                            replica: offset_event.replica_id,
+                            error_code: ErrorCode::None,
+                            start_offset: 0,
+                            leo: offset_event.leo,
+                            hw: offset_event.hw
+                        }]
+                    };
+                    s_sink.send_request(&RequestMessage::new_request(req)).await?;
                 },
 
 
@@ -149,13 +126,58 @@ where
                                 ),
 
                                 SpuServerRequest::RegisterSyncReplicaRequest(request) => {
-                                    use std::iter::FromIterator;
-
                                     let (_, sync_request) = request.get_header_request();
                                     debug!("registered offset sync request: {:#?}",sync_request);
-                                    offset_replica_list = HashSet::from_iter(sync_request.leader_replicas);
+                                    offset_listeners = sync_request
+                                        .leader_replicas
+                                        .iter()
+                                        .filter_map(|replica| context.offset_publishers().listener(replica))
+                                        .collect();
                                 },
-                                SpuServerRequest::FileStreamFetchRequest(request) =>  StreamFetchHandler::handle_stream_fetch(request,context.clone(),s_sink.clone(),end_event.clone())
+                                SpuServerRequest::FileStreamFetchRequest(request) =>  StreamFetchHandler::handle_stream_fetch(request,context.clone(),s_sink.clone(),end_event.clone(),stream_credits.clone()),
+
+                                SpuServerRequest::JoinGroupRequest(request) => call_service!(
+                                    request,
+                                    handle_join_group_request(request,context.clone()),
+                                    s_sink,
+                                    "join group handler"
+                                ),
+                                SpuServerRequest::HeartbeatRequest(request) => call_service!(
+                                    request,
+                                    handle_heartbeat_request(request,context.clone()),
+                                    s_sink,
+                                    "heartbeat handler"
+                                ),
+                                SpuServerRequest::LeaveGroupRequest(request) => call_service!(
+                                    request,
+                                    handle_leave_group_request(request,context.clone()),
+                                    s_sink,
+                                    "leave group handler"
+                                ),
+                                SpuServerRequest::OffsetCommitRequest(request) => call_service!(
+                                    request,
+                                    handle_offset_commit_request(request,context.clone()),
+                                    s_sink,
+                                    "offset commit handler"
+                                ),
+                                SpuServerRequest::OffsetFetchRequest(request) => call_service!(
+                                    request,
+                                    handle_offset_fetch_request(request,context.clone()),
+                                    s_sink,
+                                    "offset fetch handler"
+                                ),
+                                SpuServerRequest::InitProducerIdRequest(request) => call_service!(
+                                    request,
+                                    handle_init_producer_id_request(request,context.clone()),
+                                    s_sink,
+                                    "init producer id handler"
+                                ),
+                                SpuServerRequest::StreamFetchCreditRequest(request) => call_service!(
+                                    request,
+                                    handle_stream_credit_request(request,stream_credits.clone()),
+                                    s_sink,
+                                    "stream fetch credit handler"
+                                ),
 
                             }
                         } else {
@@ -178,3 +200,31 @@ where
         Ok(())
     }
 }
+
+/// wait for offset change of any of replicas,
+/// listeners always return latest offsets so slow connection doesn't fall behind.
+/// Listeners of replicas which are no longer led are dropped
+async fn next_offset_change(listeners: &mut Vec<OffsetChangeListener>) -> OffsetUpdateEvent {
+    loop {
+        if listeners.is_empty() {
+            return pending().await;
+        }
+
+        let (event, index, _) = select_all(
+            listeners
+                .iter_mut()
+                .map(|listener| listener.listen().boxed()),
+        )
+        .await;
+        match event {
+            Some(event) => return event,
+            None => {
+                debug!(
+                    "replica: {} is no longer led",
+                    listeners[index].replica_id()
+                );
+                listeners.swap_remove(index);
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//!
+//! # Stream Credit
+//!
+//! Credit of stream fetches on a connection. Stream fetch handler uses one credit
+//! for each response and waits when it has no credit left, until consumer grants more.
+//!
+use std::collections::HashMap;
+use std::io::Error;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use tracing::debug;
+use event_listener::Event;
+
+use dataplane::ErrorCode;
+use dataplane::api::{RequestMessage, ResponseMessage};
+use fluvio_spu_schema::server::stream_credit::{StreamFetchCreditRequest, StreamFetchCreditResponse};
+
+/// credit of single stream fetch
+#[derive(Debug)]
+pub struct StreamCredit {
+    credit: AtomicI32,
+    event: Event,
+}
+
+impl StreamCredit {
+    fn new(credit: i32) -> Self {
+        Self {
+            credit: AtomicI32::new(credit),
+            event: Event::new(),
+        }
+    }
+
+    /// add credit and wake up stream waiting for it
+    pub fn grant(&self, credit: i32) {
+        self.credit.fetch_add(credit, Ordering::SeqCst);
+        self.event.notify(usize::MAX);
+    }
+
+    /// use one credit for response
+    pub fn consume(&self) {
+        self.credit.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    pub fn available(&self) -> i32 {
+        self.credit.load(Ordering::SeqCst)
+    }
+
+    /// wait until there is credit left
+    pub async fn wait(&self) {
+        loop {
+            if self.available() > 0 {
+                return;
+            }
+
+            let listener = self.event.listen();
+            // credit may have been granted before listener was registered
+            if self.available() > 0 {
+                return;
+            }
+            listener.await;
+        }
+    }
+}
+
+/// Credits of flow controlled stream fetches on a connection
+#[derive(Debug, Default)]
+pub struct StreamCredits {
+    streams: Mutex<HashMap<i32, Arc<StreamCredit>>>,
+}
+
+impl StreamCredits {
+    /// register stream with initial credit, replaces previous stream with same id
+    pub fn register(&self, stream_id: i32, credit: i32) -> Arc<StreamCredit> {
+        let stream_credit = Arc::new(StreamCredit::new(credit));
+        self.streams
+            .lock()
+            .expect("stream credits lock")
+            .insert(stream_id, stream_credit.clone());
+        stream_credit
+    }
+
+    /// remove stream after it has ended
+    pub fn unregister(&self, stream_id: i32, stream_credit: &Arc<StreamCredit>) {
+        let mut streams = self.streams.lock().expect("stream credits lock");
+        // stream may have been replaced by new stream with same id
+        if matches!(streams.get(&stream_id), Some(current) if Arc::ptr_eq(current, stream_credit)) {
+            streams.remove(&stream_id);
+        }
+    }
+
+    /// grant credit to stream, returns false if stream is not found
+    pub fn grant(&self, stream_id: i32, credit: i32) -> bool {
+        match self
+            .streams
+            .lock()
+            .expect("stream credits lock")
+            .get(&stream_id)
+        {
+            Some(stream_credit) => {
+                stream_credit.grant(credit);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+pub async fn handle_stream_credit_request(
+    request: RequestMessage<StreamFetchCreditRequest>,
+    credits: Arc<StreamCredits>,
+) -> Result<ResponseMessage<StreamFetchCreditResponse>, Error> {
+    let (header, credit_request) = request.get_header_request();
+    debug!(
+        "granting stream: {} credit: {}",
+        credit_request.stream_id, credit_request.credit
+    );
+
+    let mut response = StreamFetchCreditResponse::default();
+    if !credits.grant(credit_request.stream_id, credit_request.credit) {
+        debug!("stream: {} not found", credit_request.stream_id);
+        response.error_code = ErrorCode::StreamFetchNotFound;
+    }
+    Ok(RequestMessage::<StreamFetchCreditRequest>::response_with_header(&header, response))
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::time::Duration;
+
+    use futures_util::future::join;
+    use fluvio_future::test_async;
+    use fluvio_future::timer::sleep;
+
+    use super::StreamCredits;
+
+    #[test_async]
+    async fn test_stream_credit() -> Result<(), ()> {
+        let credits = StreamCredits::default();
+        let stream_credit = credits.register(1, 1);
+
+        stream_credit.wait().await;
+        stream_credit.consume();
+        assert_eq!(stream_credit.available(), 0);
+
+        let grant = async {
+            sleep(Duration::from_millis(10)).await;
+            assert!(credits.grant(1, 2));
+        };
+        join(grant, stream_credit.wait()).await;
+        assert_eq!(stream_credit.available(), 2);
+
+        assert!(!credits.grant(2, 1));
+        credits.unregister(1, &stream_credit);
+        assert!(!credits.grant(1, 1));
+
+        Ok(())
+    }
+}
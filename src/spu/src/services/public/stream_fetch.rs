@@ -1,14 +1,14 @@
+use std::fs::File;
+use std::io::Error as IoError;
 use std::sync::Arc;
 
 use tracing::debug;
 use tracing::trace;
-use tracing::warn;
 use tracing::error;
 use futures_util::io::AsyncRead;
 use futures_util::io::AsyncWrite;
 use tokio::select;
 use event_listener::Event;
-use tokio::sync::broadcast::RecvError;
 
 use fluvio_future::zero_copy::ZeroCopyWrite;
 use fluvio_future::task::spawn;
@@ -16,20 +16,43 @@ use fluvio_socket::InnerFlvSink;
 use fluvio_socket::InnerExclusiveFlvSink;
 use fluvio_socket::FlvSocketError;
 use dataplane::api::{RequestMessage, RequestHeader};
-use dataplane::{Offset, Isolation, ReplicaKey};
-use dataplane::fetch::FilePartitionResponse;
+use dataplane::{ErrorCode, Offset, Isolation, ReplicaKey};
+use dataplane::fetch::{FetchablePartitionResponse, FilePartitionResponse};
+use dataplane::record::{FileRecordSet, RecordSet};
 use fluvio_spu_schema::server::stream_fetch::FileStreamFetchRequest;
+use fluvio_spu_schema::server::stream_fetch::DefaultStreamFetchRequest;
 use fluvio_spu_schema::server::stream_fetch::StreamFetchResponse;
+use fluvio_spu_schema::server::stream_fetch::bound_records;
+use fluvio_spu_schema::server::record_filter::{RecordFilter, filter_records};
+use fluvio_spu_schema::server::smart_stream::SmartStreamPayload;
 
 use crate::core::DefaultSharedGlobalContext;
+use crate::smart_stream::SmartStream;
+use super::stream_credit::{StreamCredit, StreamCredits};
 
 /// continuous fetch handler
 /// while client is active, it continuously send back new records
+///
+/// records are sent as file slices, unless request has filters or smart stream.
+/// then records are read and decoded, so they can be filtered and transformed
+/// before they are sent back.
+///
+/// stream ends once end offset or max records of request is reached. then a last
+/// response marks end of stream, since records up to end may have been filtered out.
+///
+/// with flow control, each response uses one credit of stream. when there is no
+/// credit left, handler waits until consumer grants more.
 pub struct StreamFetchHandler<S> {
     ctx: DefaultSharedGlobalContext,
     replica: ReplicaKey,
     isolation: Isolation,
     max_bytes: u32,
+    filters: Vec<RecordFilter>,
+    smart_stream: Option<SmartStream>,
+    end_offset: Option<Offset>,
+    remaining_records: Option<u32>,
+    credit: Option<(i32, Arc<StreamCredit>)>,
+    stream_credits: Arc<StreamCredits>,
     header: RequestHeader,
     kf_sink: InnerExclusiveFlvSink<S>,
     end_event: Arc<Event>,
@@ -46,21 +69,39 @@ where
         ctx: DefaultSharedGlobalContext,
         kf_sink: InnerExclusiveFlvSink<S>,
         end_event: Arc<Event>,
+        stream_credits: Arc<StreamCredits>,
     ) {
-        // first get receiver to offset update channel to we don't missed events
-
         let (header, msg) = request.get_header_request();
 
         let current_offset = msg.fetch_offset;
         let isolation = msg.isolation;
         let replica = ReplicaKey::new(msg.topic, msg.partition);
         let max_bytes = msg.max_bytes as u32;
+        let filters = msg.filters;
+        let smart_stream = msg.smart_stream;
+        let mut end_offset = msg.end_offset;
+        let mut remaining_records = msg.max_records;
+        let credit = msg.flow_control.map(|flow_control| {
+            let stream_credit =
+                stream_credits.register(flow_control.stream_id, flow_control.credit);
+            (flow_control.stream_id, stream_credit)
+        });
+        if filters.is_empty() && smart_stream.is_none() {
+            // without filters, every offset up to end is sent, so records are bounded by offset
+            if let Some(max_records) = remaining_records.take() {
+                let max_offset = current_offset + max_records as Offset;
+                end_offset = Some(end_offset.map_or(max_offset, |end| end.min(max_offset)));
+            }
+        }
         debug!(
-            "conn: {}, start continuous fetch replica: {} offset: {}, max_bytes: {}",
+            "conn: {}, start continuous fetch replica: {} offset: {}, max_bytes: {}, filters: {}, end: {:?}, max records: {:?}",
             kf_sink.id(),
             replica,
             current_offset,
-            max_bytes
+            max_bytes,
+            filters.len(),
+            end_offset,
+            remaining_records
         );
 
         let handler = Self {
@@ -69,14 +110,55 @@ where
             replica,
             header,
             max_bytes,
+            filters,
+            smart_stream: None,
+            end_offset,
+            remaining_records,
+            credit,
+            stream_credits,
             kf_sink,
             end_event,
         };
 
-        spawn(async move { handler.process(current_offset).await });
+        spawn(async move {
+            let credit = handler.credit.clone();
+            let stream_credits = handler.stream_credits.clone();
+            let result = handler.process(current_offset, smart_stream).await;
+            if let Some((stream_id, stream_credit)) = credit {
+                stream_credits.unregister(stream_id, &stream_credit);
+            }
+            result
+        });
     }
 
-    async fn process(mut self, starting_offset: Offset) -> Result<(), FlvSocketError> {
+    async fn process(
+        mut self,
+        starting_offset: Offset,
+        smart_stream: Option<SmartStreamPayload>,
+    ) -> Result<(), FlvSocketError> {
+        if let Some(payload) = smart_stream {
+            match SmartStream::new(payload) {
+                Ok(smart_stream) => self.smart_stream = Some(smart_stream),
+                Err(err) => {
+                    error!("conn: {}, {}", self.kf_sink.id(), err);
+                    return self.send_error(err.error_code()).await;
+                }
+            }
+        }
+
+        // register listener before reading, so offset changes are not missed
+        let mut listener = match self.ctx.offset_publishers().listener(&self.replica) {
+            Some(listener) => listener,
+            None => {
+                debug!(
+                    "conn: {}, replica: {} is not led by this spu",
+                    self.kf_sink.id(),
+                    self.replica
+                );
+                return self.send_error(ErrorCode::NotLeaderForPartition).await;
+            }
+        };
+
         let mut current_offset =
             if let Some(offset) = self.send_back_records(starting_offset).await? {
                 offset
@@ -88,8 +170,10 @@ where
                 return Ok(());
             };
 
-        let mut receiver = self.ctx.offset_channel().receiver();
-        //pin_mut!(receiver);
+        if self.end_reached(current_offset) {
+            debug!("conn: {}, end of stream reached", self.kf_sink.id());
+            return self.send_end_of_stream(current_offset).await;
+        }
 
         let mut counter: i32 = 0;
         loop {
@@ -107,53 +191,40 @@ where
                     break;
                 },
 
-                offset_event_res = receiver.recv() => {
-
-                    match offset_event_res {
-                        Ok(offset_event) => {
-
-                            debug!("conn: {}, received offset event connection: {:#?}", self.kf_sink.id(),offset_event);
-                            if offset_event.replica_id == self.replica {
-                                // depends on isolation, we need to keep track different offset
-                                let update_offset = match self.isolation {
-                                    Isolation::ReadCommitted => offset_event.hw,
-                                    Isolation::ReadUncommitted => offset_event.leo
-                                };
-                                debug!("conn: {}, update offset: {}",self.kf_sink.id(),update_offset);
-                                if update_offset != current_offset {
-                                    debug!("conn: {}, updated offset replica: {} offset: {} diff from prev: {}",self.kf_sink.id(), self.replica,update_offset,current_offset);
-                                    if let Some(offset) = self.send_back_records(current_offset).await? {
-                                        debug!("conn: {}, replica: {} read offset: {}",self.kf_sink.id(), self.replica,offset);
-                                        current_offset = offset;
-                                    } else {
-                                        debug!("conn: {}, no more replica: {} records can be read", self.kf_sink.id(),self.replica);
-                                        break;
-                                    }
-                                } else {
-                                    debug!("conn: {}, no changed in offset: {} offset: {} ignoring",self.kf_sink.id(), self.replica,update_offset);
-                                }
-                            } else {
-                                debug!("conn: {}, ignoring event because replica does not match",self.kf_sink.id());
-                            }
-
+                offset_event = listener.listen() => {
 
-                        },
-                        Err(err) => {
-                            match err {
-                                RecvError::Closed => {
-                                    warn!("conn: {}, lost connection to leader controller",self.kf_sink.id());
-                                },
-                                RecvError::Lagged(lag) => {
-                                    error!("conn: {}, lagging: {}",self.kf_sink.id(),lag);
-                                }
+                    let offset_event = match offset_event {
+                        Some(offset_event) => offset_event,
+                        None => {
+                            debug!("conn: {}, replica: {} is no longer led, terminating", self.kf_sink.id(), self.replica);
+                            self.send_error(ErrorCode::NotLeaderForPartition).await?;
+                            break;
+                        }
+                    };
+                    debug!("conn: {}, received offset event connection: {:#?}", self.kf_sink.id(),offset_event);
+                    // depends on isolation, we need to keep track different offset
+                    let update_offset = match self.isolation {
+                        Isolation::ReadCommitted => offset_event.hw,
+                        Isolation::ReadUncommitted => offset_event.leo
+                    };
+                    debug!("conn: {}, update offset: {}",self.kf_sink.id(),update_offset);
+                    if update_offset != current_offset {
+                        debug!("conn: {}, updated offset replica: {} offset: {} diff from prev: {}",self.kf_sink.id(), self.replica,update_offset,current_offset);
+                        if let Some(offset) = self.send_back_records(current_offset).await? {
+                            debug!("conn: {}, replica: {} read offset: {}",self.kf_sink.id(), self.replica,offset);
+                            current_offset = offset;
+                            if self.end_reached(current_offset) {
+                                debug!("conn: {}, end of stream reached", self.kf_sink.id());
+                                self.send_end_of_stream(current_offset).await?;
+                                break;
                             }
-
+                        } else {
+                            debug!("conn: {}, no more replica: {} records can be read", self.kf_sink.id(),self.replica);
+                            break;
                         }
+                    } else {
+                        debug!("conn: {}, no changed in offset: {} offset: {} ignoring",self.kf_sink.id(), self.replica,update_offset);
                     }
-
-
-
-
                 },
             }
         }
@@ -166,25 +237,77 @@ where
         Ok(())
     }
 
+    /// stream ends when end offset is reached or max records have been sent
+    fn end_reached(&self, offset: Offset) -> bool {
+        matches!(self.end_offset, Some(end) if offset >= end) || self.remaining_records == Some(0)
+    }
+
+    /// wait until stream has credit for response, returns false if connection has ended
+    async fn wait_for_credit(&self) -> bool {
+        let stream_credit = match &self.credit {
+            Some((_, stream_credit)) => stream_credit.clone(),
+            None => return true,
+        };
+        if stream_credit.available() > 0 {
+            return true;
+        }
+
+        debug!(
+            "conn: {}, replica: {} has no credit, waiting",
+            self.kf_sink.id(),
+            self.replica
+        );
+        select! {
+            _ = stream_credit.wait() => true,
+            _ = self.end_event.listen() => false,
+        }
+    }
+
     async fn send_back_records(
         &mut self,
         offset: Offset,
     ) -> Result<Option<Offset>, FlvSocketError> {
+        if !self.wait_for_credit().await {
+            debug!(
+                "conn: {}, connection has been terminated while waiting for credit",
+                self.kf_sink.id()
+            );
+            return Ok(None);
+        }
+        if let Some((_, stream_credit)) = &self.credit {
+            stream_credit.consume();
+        }
+
         let mut partition_response = FilePartitionResponse::default();
         partition_response.partition_index = self.replica.partition;
 
-        if let Some((hw, leo)) = self
-            .ctx
-            .leaders_state()
-            .read_records(
-                &self.replica,
-                offset,
-                self.max_bytes,
-                self.isolation.clone(),
-                &mut partition_response,
-            )
-            .await
-        {
+        // records are decoded only if they are filtered or processed by smart stream
+        let decode = !self.filters.is_empty() || self.smart_stream.is_some();
+        let leaders_state = self.ctx.leaders_state();
+        let read_result = if decode {
+            leaders_state
+                .read_records_file(
+                    &self.replica,
+                    offset,
+                    self.max_bytes,
+                    self.isolation.clone(),
+                    &mut partition_response,
+                )
+                .await
+        } else {
+            leaders_state
+                .read_records(
+                    &self.replica,
+                    offset,
+                    self.max_bytes,
+                    self.isolation.clone(),
+                    &mut partition_response,
+                )
+                .await
+                .map(|(hw, leo)| (hw, leo, None))
+        };
+
+        if let Some((hw, leo, file)) = read_result {
             debug!(
                 "conn: {}, retrieved slice len: {} replica: {}, from: {} to hw: {}, leo: {}",
                 partition_response.records.len(),
@@ -194,34 +317,21 @@ where
                 hw,
                 leo,
             );
-            let response = StreamFetchResponse {
-                topic: self.replica.topic.clone(),
-                partition: partition_response,
-            };
-
-            let response = RequestMessage::<FileStreamFetchRequest>::response_with_header(
-                &self.header,
-                response,
-            );
-            trace!(
-                "conn: {}, sending back file fetch response: {:#?}",
-                self.kf_sink.id(),
-                response
-            );
-
-            let mut inner_sink = self.kf_sink.lock().await;
-            inner_sink
-                .encode_file_slices(&response, self.header.api_version())
-                .await?;
-
-            trace!("conn: {}, finish sending fetch response", self.kf_sink.id());
-
             // get next offset
             let next_offset = match self.isolation {
                 Isolation::ReadCommitted => hw,
                 Isolation::ReadUncommitted => leo,
             };
 
+            if !decode {
+                self.send_response(partition_response, next_offset).await?;
+            } else if !self
+                .send_decoded_response(partition_response, file, next_offset)
+                .await?
+            {
+                return Ok(None);
+            }
+
             Ok(Some(next_offset))
         } else {
             debug!(
@@ -231,7 +341,178 @@ where
                 offset
             );
             // in this case, partition is not founded
+            // send back error so consumer can find new leader
+            self.send_response(partition_response, offset).await?;
             Ok(None)
         }
     }
+
+    async fn send_response(
+        &mut self,
+        partition_response: FilePartitionResponse,
+        next_offset: Offset,
+    ) -> Result<(), FlvSocketError> {
+        let response = StreamFetchResponse {
+            topic: self.replica.topic.clone(),
+            partition: partition_response,
+            next_offset,
+            end_of_stream: false,
+        };
+        self.send_file_response(response).await
+    }
+
+    /// send last response of stream, without records and regardless of credit
+    async fn send_end_of_stream(&mut self, offset: Offset) -> Result<(), FlvSocketError> {
+        let response = StreamFetchResponse {
+            topic: self.replica.topic.clone(),
+            partition: FilePartitionResponse {
+                partition_index: self.replica.partition,
+                ..Default::default()
+            },
+            next_offset: offset,
+            end_of_stream: true,
+        };
+        self.send_file_response(response).await
+    }
+
+    async fn send_file_response(
+        &mut self,
+        response: StreamFetchResponse<FileRecordSet>,
+    ) -> Result<(), FlvSocketError> {
+        let response =
+            RequestMessage::<FileStreamFetchRequest>::response_with_header(&self.header, response);
+        trace!(
+            "conn: {}, sending back file fetch response: {:#?}",
+            self.kf_sink.id(),
+            response
+        );
+
+        let mut inner_sink = self.kf_sink.lock().await;
+        inner_sink
+            .encode_file_slices(&response, self.header.api_version())
+            .await?;
+
+        trace!("conn: {}, finish sending fetch response", self.kf_sink.id());
+        Ok(())
+    }
+
+    async fn send_error(&mut self, error_code: ErrorCode) -> Result<(), FlvSocketError> {
+        let partition_response = FilePartitionResponse {
+            partition_index: self.replica.partition,
+            error_code,
+            ..Default::default()
+        };
+        self.send_response(partition_response, -1).await
+    }
+
+    /// send records of slice after they are filtered and transformed by smart stream,
+    /// returns false if smart stream failed
+    async fn send_decoded_response(
+        &mut self,
+        partition_response: FilePartitionResponse,
+        file: Option<File>,
+        next_offset: Offset,
+    ) -> Result<bool, FlvSocketError> {
+        use fluvio_future::task::spawn_blocking;
+
+        let version = self.header.api_version();
+        let slice = partition_response.records;
+        let filters = self.filters.clone();
+        let end_offset = self.end_offset;
+        let remaining_records = self.remaining_records;
+        let mut smart_stream = self.smart_stream.take();
+        // module runs on blocking thread, since it can take up to its fuel
+        let (records, smart_stream) = spawn_blocking(move || {
+            let records = read_records(file, &slice, version).map(|mut records| {
+                filter_records(&filters, &mut records);
+                let processed = match smart_stream.as_mut() {
+                    Some(smart_stream) => smart_stream.process(&mut records),
+                    None => Ok(()),
+                };
+                processed.map(|_| {
+                    let count = bound_records(&mut records, end_offset, remaining_records);
+                    (records, count)
+                })
+            });
+            (records, smart_stream)
+        })
+        .await;
+        self.smart_stream = smart_stream;
+
+        let records = match records? {
+            Ok((records, count)) => {
+                if let Some(remaining) = self.remaining_records.as_mut() {
+                    *remaining -= count;
+                }
+                records
+            }
+            Err(err) => {
+                error!("conn: {}, {}", self.kf_sink.id(), err);
+                self.send_error(err.error_code()).await?;
+                return Ok(false);
+            }
+        };
+        debug!(
+            "conn: {}, sending {} decoded batches",
+            self.kf_sink.id(),
+            records.batches.len()
+        );
+
+        let response = StreamFetchResponse {
+            topic: self.replica.topic.clone(),
+            partition: FetchablePartitionResponse {
+                partition_index: partition_response.partition_index,
+                error_code: partition_response.error_code,
+                high_watermark: partition_response.high_watermark,
+                last_stable_offset: partition_response.last_stable_offset,
+                log_start_offset: partition_response.log_start_offset,
+                aborted: partition_response.aborted,
+                records,
+            },
+            next_offset,
+            end_of_stream: false,
+        };
+
+        let response = RequestMessage::<DefaultStreamFetchRequest>::response_with_header(
+            &self.header,
+            response,
+        );
+        trace!(
+            "conn: {}, sending back decoded fetch response: {:#?}",
+            self.kf_sink.id(),
+            response
+        );
+
+        let mut inner_sink = self.kf_sink.lock().await;
+        inner_sink.send_response(&response, version).await?;
+
+        trace!("conn: {}, finish sending fetch response", self.kf_sink.id());
+        Ok(true)
+    }
+}
+
+/// read and decode batches of file slice from file of records
+fn read_records(
+    file: Option<File>,
+    records: &FileRecordSet,
+    version: i16,
+) -> Result<RecordSet, IoError> {
+    use std::io::Cursor;
+    use std::os::unix::fs::FileExt;
+
+    use dataplane::core::{Decoder, Encoder};
+
+    let file = match file {
+        Some(file) => file,
+        None => return Ok(RecordSet::default()),
+    };
+    let slice = records.raw_slice();
+    let mut buf = Vec::with_capacity(slice.len() as usize + 4);
+    (slice.len() as i32).encode(&mut buf, version)?;
+    buf.resize(slice.len() as usize + 4, 0);
+    file.read_exact_at(&mut buf[4..], slice.position())?;
+
+    let mut records = RecordSet::default();
+    records.decode(&mut Cursor::new(buf), version)?;
+    Ok(records)
 }
@@ -0,0 +1,468 @@
+//!
+//! # SmartStream
+//!
+//! Runs WebAssembly module of stream fetch over records before they are sent back.
+//! Modules are sandboxed: they can't import any host function, their memory is limited
+//! and they get limited fuel for every record, so a module can't hang the SPU.
+//!
+use std::fmt;
+
+use tracing::debug;
+use wasmi::{Config, Engine, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder};
+use wasmi::TypedFunc;
+
+use dataplane::ErrorCode;
+use dataplane::record::{DefaultAsyncBuffer, RecordSet};
+use fluvio_spu_schema::server::smart_stream::{SmartStreamKind, SmartStreamPayload};
+use fluvio_spu_schema::server::record_filter::drop_empty_batches;
+
+/// fuel available to module for processing single record
+const FUEL_PER_RECORD: u64 = 1_000_000;
+/// max size of module memory
+const MAX_MEMORY_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum SmartStreamError {
+    InvalidModule(String),
+    Runtime(String),
+}
+
+impl SmartStreamError {
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidModule(_) => ErrorCode::SmartStreamInvalidModule,
+            Self::Runtime(_) => ErrorCode::SmartStreamRuntimeError,
+        }
+    }
+}
+
+impl fmt::Display for SmartStreamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidModule(err) => write!(f, "invalid smart stream module: {}", err),
+            Self::Runtime(err) => write!(f, "smart stream error: {}", err),
+        }
+    }
+}
+
+fn invalid_module<E: fmt::Display>(err: E) -> SmartStreamError {
+    SmartStreamError::InvalidModule(err.to_string())
+}
+
+fn runtime_error<E: fmt::Display>(err: E) -> SmartStreamError {
+    SmartStreamError::Runtime(err.to_string())
+}
+
+/// function exported by module for its kind
+enum Transform {
+    Filter(TypedFunc<(i32, i32), i32>),
+    Map(TypedFunc<(i32, i32), i64>),
+    Aggregate(TypedFunc<(i32, i32, i32, i32), i64>),
+}
+
+/// instance of SmartStream module for a single stream fetch
+///
+/// accumulator of aggregate is kept between responses of the stream
+pub struct SmartStream {
+    store: Store<StoreLimits>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    dealloc: TypedFunc<(i32, i32), ()>,
+    transform: Transform,
+    accumulator: Vec<u8>,
+    fuel_added: u64,
+}
+
+impl fmt::Debug for SmartStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SmartStream")
+    }
+}
+
+impl SmartStream {
+    /// compile and instantiate module
+    pub fn new(payload: SmartStreamPayload) -> Result<Self, SmartStreamError> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, payload.wasm.as_slice()).map_err(invalid_module)?;
+
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_MEMORY_BYTES)
+            .build();
+        let mut store = Store::new(&engine, limits);
+        store.limiter(|limits| limits);
+        store.add_fuel(FUEL_PER_RECORD).map_err(runtime_error)?;
+
+        let linker = Linker::<StoreLimits>::new(&engine);
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(invalid_module)?;
+
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| invalid_module("memory is not exported"))?;
+        let alloc = instance
+            .get_typed_func(&store, "alloc")
+            .map_err(invalid_module)?;
+        let dealloc = instance
+            .get_typed_func(&store, "dealloc")
+            .map_err(invalid_module)?;
+        let transform = match payload.kind {
+            SmartStreamKind::Filter => Transform::Filter(
+                instance
+                    .get_typed_func(&store, "filter")
+                    .map_err(invalid_module)?,
+            ),
+            SmartStreamKind::Map => Transform::Map(
+                instance
+                    .get_typed_func(&store, "map")
+                    .map_err(invalid_module)?,
+            ),
+            SmartStreamKind::Aggregate => Transform::Aggregate(
+                instance
+                    .get_typed_func(&store, "aggregate")
+                    .map_err(invalid_module)?,
+            ),
+        };
+        debug!("instantiated smart stream: {:?}", payload.kind);
+
+        Ok(Self {
+            store,
+            memory,
+            alloc,
+            dealloc,
+            transform,
+            accumulator: payload.accumulator,
+            fuel_added: FUEL_PER_RECORD,
+        })
+    }
+
+    /// transform records in place
+    pub fn process(&mut self, records: &mut RecordSet) -> Result<(), SmartStreamError> {
+        for batch in records.batches.iter_mut() {
+            let mut error = None;
+            if let Transform::Filter(_) = self.transform {
+                batch.retain_records(|record| {
+                    if error.is_some() {
+                        return false;
+                    }
+                    let value = record.value.inner_value_ref().as_deref().unwrap_or(&[]);
+                    match self.filter(value) {
+                        Ok(keep) => keep,
+                        Err(err) => {
+                            error = Some(err);
+                            false
+                        }
+                    }
+                });
+            } else {
+                for record in batch.records_mut().iter_mut() {
+                    let value = record.value.inner_value_ref().as_deref().unwrap_or(&[]);
+                    let value = self.map(value)?;
+                    record.value = DefaultAsyncBuffer::new(Some(value));
+                }
+            }
+            if let Some(err) = error {
+                return Err(err);
+            }
+        }
+        drop_empty_batches(records);
+        Ok(())
+    }
+
+    fn filter(&mut self, value: &[u8]) -> Result<bool, SmartStreamError> {
+        self.refuel()?;
+        let ptr = self.write_input(value)?;
+        let len = value.len() as i32;
+        let keep = match &self.transform {
+            Transform::Filter(filter) => filter
+                .call(&mut self.store, (ptr, len))
+                .map_err(runtime_error)?,
+            _ => unreachable!("module is not filter"),
+        };
+        self.release(&[(ptr, len)])?;
+        Ok(keep != 0)
+    }
+
+    /// new value of record from map or aggregate
+    fn map(&mut self, value: &[u8]) -> Result<Vec<u8>, SmartStreamError> {
+        self.refuel()?;
+        let mut regions = vec![(self.write_input(value)?, value.len() as i32)];
+        if let Transform::Aggregate(_) = self.transform {
+            let accumulator = std::mem::take(&mut self.accumulator);
+            regions.push((self.write_input(&accumulator)?, accumulator.len() as i32));
+        }
+
+        let (ptr, len) = regions[0];
+        let output = match (&self.transform, regions.get(1)) {
+            (Transform::Map(map), _) => map.call(&mut self.store, (ptr, len)),
+            (Transform::Aggregate(aggregate), Some(&(acc_ptr, acc_len))) => {
+                aggregate.call(&mut self.store, (acc_ptr, acc_len, ptr, len))
+            }
+            _ => unreachable!("module is not map or aggregate"),
+        }
+        .map_err(runtime_error)?;
+
+        let (out_ptr, out_len) = output_region(output)?;
+        let output = self.read_memory(out_ptr, out_len)?;
+        // module may return value written into input memory
+        let within_input = regions.iter().any(|&(ptr, len)| {
+            out_ptr as i64 >= ptr as i64 && (out_ptr + out_len) as i64 <= ptr as i64 + len as i64
+        });
+        if out_len > 0 && !within_input {
+            regions.push((out_ptr as i32, out_len as i32));
+        }
+        self.release(&regions)?;
+
+        if let Transform::Aggregate(_) = self.transform {
+            self.accumulator = output.clone();
+        }
+        Ok(output)
+    }
+
+    /// top up fuel, so module has same amount of fuel for every record
+    fn refuel(&mut self) -> Result<(), SmartStreamError> {
+        let consumed = self.store.fuel_consumed().unwrap_or_default();
+        let remaining = self.fuel_added.saturating_sub(consumed);
+        let delta = FUEL_PER_RECORD.saturating_sub(remaining);
+        self.store.add_fuel(delta).map_err(runtime_error)?;
+        self.fuel_added += delta;
+        Ok(())
+    }
+
+    /// copy bytes into memory allocated by module
+    fn write_input(&mut self, bytes: &[u8]) -> Result<i32, SmartStreamError> {
+        let ptr = self
+            .alloc
+            .call(&mut self.store, bytes.len() as i32)
+            .map_err(runtime_error)?;
+        self.memory
+            .write(&mut self.store, ptr as usize, bytes)
+            .map_err(runtime_error)?;
+        Ok(ptr)
+    }
+
+    /// release memory of record, in reverse order of allocation
+    fn release(&mut self, regions: &[(i32, i32)]) -> Result<(), SmartStreamError> {
+        for &(ptr, len) in regions.iter().rev() {
+            self.dealloc
+                .call(&mut self.store, (ptr, len))
+                .map_err(runtime_error)?;
+        }
+        Ok(())
+    }
+
+    fn read_memory(&self, ptr: u32, len: u32) -> Result<Vec<u8>, SmartStreamError> {
+        let mut bytes = vec![0; len as usize];
+        self.memory
+            .read(&self.store, ptr as usize, &mut bytes)
+            .map_err(runtime_error)?;
+        Ok(bytes)
+    }
+}
+
+/// pointer and length packed in output of module
+fn output_region(output: i64) -> Result<(u32, u32), SmartStreamError> {
+    if output < 0 {
+        return Err(SmartStreamError::Runtime(format!(
+            "module returned error: {}",
+            output
+        )));
+    }
+    Ok(((output >> 32) as u32, (output & 0xffff_ffff) as u32))
+}
+
+#[cfg(test)]
+mod test {
+
+    use dataplane::batch::DefaultBatch;
+    use dataplane::record::{DefaultRecord, RecordSet};
+    use fluvio_spu_schema::server::smart_stream::SmartStreamPayload;
+
+    use super::SmartStream;
+    use super::SmartStreamError;
+
+    /// memory and stack allocator shared by test modules,
+    /// memory grows as needed and is released if it was allocated last
+    const ALLOC: &str = r#"
+        (memory (export "memory") 1)
+        (global $next (mut i32) (i32.const 1024))
+        (func (export "alloc") (param $len i32) (result i32)
+            (local $ptr i32) (local $end i32)
+            (local.set $ptr (global.get $next))
+            (local.set $end (i32.add (local.get $ptr) (local.get $len)))
+            (if (i32.gt_u (local.get $end) (i32.mul (memory.size) (i32.const 65536)))
+                (then (drop (memory.grow
+                    (i32.sub (i32.div_u (i32.add (local.get $end) (i32.const 65535))
+                                        (i32.const 65536))
+                             (memory.size))))))
+            (global.set $next (local.get $end))
+            (local.get $ptr))
+        (func (export "dealloc") (param $ptr i32) (param $len i32)
+            (if (i32.eq (i32.add (local.get $ptr) (local.get $len)) (global.get $next))
+                (then (global.set $next (local.get $ptr)))))
+    "#;
+
+    fn module(funcs: &str) -> Vec<u8> {
+        wat::parse_str(format!("(module {} {})", ALLOC, funcs)).expect("valid module")
+    }
+
+    fn records(values: &[&str]) -> RecordSet {
+        let mut batch = DefaultBatch::default();
+        for value in values {
+            batch.add_record(DefaultRecord::from(value.as_bytes().to_vec()));
+        }
+        RecordSet::default().add(batch)
+    }
+
+    fn values(records: &RecordSet) -> Vec<(i64, String)> {
+        records.batches[0]
+            .records()
+            .iter()
+            .map(|record| {
+                let value = record.value.inner_value_ref().clone().unwrap_or_default();
+                (
+                    record.get_offset_delta(),
+                    String::from_utf8(value).expect("utf8"),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_filter() {
+        // keep records starting with 'a'
+        let wasm = module(
+            r#"(func (export "filter") (param $ptr i32) (param $len i32) (result i32)
+                (if (result i32) (i32.eqz (local.get $len))
+                    (then (i32.const 0))
+                    (else (i32.eq (i32.load8_u (local.get $ptr)) (i32.const 97)))))"#,
+        );
+        let mut smart_stream = SmartStream::new(SmartStreamPayload::filter(wasm)).expect("module");
+        let mut records = records(&["apple", "banana", "avocado", ""]);
+
+        smart_stream.process(&mut records).expect("process");
+        assert_eq!(
+            values(&records),
+            vec![(0, "apple".to_owned()), (2, "avocado".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_map() {
+        // uppercase ascii letters in place
+        let wasm = module(
+            r#"(func (export "map") (param $ptr i32) (param $len i32) (result i64)
+                (local $i i32) (local $c i32)
+                (block $done
+                    (loop $next
+                        (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+                        (local.set $c (i32.load8_u (i32.add (local.get $ptr) (local.get $i))))
+                        (if (i32.and (i32.ge_u (local.get $c) (i32.const 97))
+                                     (i32.le_u (local.get $c) (i32.const 122)))
+                            (then (i32.store8 (i32.add (local.get $ptr) (local.get $i))
+                                              (i32.sub (local.get $c) (i32.const 32)))))
+                        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                        (br $next)))
+                (i64.or (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+                        (i64.extend_i32_u (local.get $len))))"#,
+        );
+        let mut smart_stream = SmartStream::new(SmartStreamPayload::map(wasm)).expect("module");
+        let mut records = records(&["hello", "World"]);
+
+        smart_stream.process(&mut records).expect("process");
+        assert_eq!(
+            values(&records),
+            vec![(0, "HELLO".to_owned()), (1, "WORLD".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_aggregate() {
+        // count records in single byte accumulator
+        let wasm = module(
+            r#"(func (export "aggregate") (param $acc i32) (param $acc_len i32)
+                    (param $ptr i32) (param $len i32) (result i64)
+                (i32.store8 (local.get $acc)
+                    (i32.add (i32.load8_u (local.get $acc)) (i32.const 1)))
+                (i64.or (i64.shl (i64.extend_i32_u (local.get $acc)) (i64.const 32))
+                        (i64.const 1)))"#,
+        );
+        let mut smart_stream =
+            SmartStream::new(SmartStreamPayload::aggregate(wasm, vec![b'0'])).expect("module");
+
+        let mut first = records(&["a", "b"]);
+        smart_stream.process(&mut first).expect("process");
+        assert_eq!(
+            values(&first),
+            vec![(0, "1".to_owned()), (1, "2".to_owned())]
+        );
+
+        // accumulator is kept between record sets
+        let mut second = records(&["c"]);
+        smart_stream.process(&mut second).expect("process");
+        assert_eq!(values(&second), vec![(0, "3".to_owned())]);
+    }
+
+    #[test]
+    fn test_memory_released() {
+        // records are bigger in total than module memory can grow to
+        let wasm = module(
+            r#"(func (export "filter") (param $ptr i32) (param $len i32) (result i32)
+                (i32.const 1))"#,
+        );
+        let mut smart_stream = SmartStream::new(SmartStreamPayload::filter(wasm)).expect("module");
+        let value = "a".repeat(1024 * 1024);
+        let mut records = records(&vec![value.as_str(); 32]);
+
+        smart_stream.process(&mut records).expect("process");
+        assert_eq!(values(&records).len(), 32);
+    }
+
+    #[test]
+    fn test_out_of_fuel() {
+        let wasm = module(
+            r#"(func (export "filter") (param $ptr i32) (param $len i32) (result i32)
+                (loop $forever (br $forever))
+                (i32.const 1))"#,
+        );
+        let mut smart_stream = SmartStream::new(SmartStreamPayload::filter(wasm)).expect("module");
+        let mut records = records(&["a"]);
+
+        assert!(matches!(
+            smart_stream.process(&mut records),
+            Err(SmartStreamError::Runtime(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_module() {
+        assert!(matches!(
+            SmartStream::new(SmartStreamPayload::filter(b"not wasm".to_vec())),
+            Err(SmartStreamError::InvalidModule(_))
+        ));
+
+        // module without dealloc function
+        let wasm = wat::parse_str(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "alloc") (param $len i32) (result i32) (i32.const 0))
+                (func (export "filter") (param $ptr i32) (param $len i32) (result i32)
+                    (i32.const 1)))"#,
+        )
+        .expect("valid module");
+        assert!(matches!(
+            SmartStream::new(SmartStreamPayload::filter(wasm)),
+            Err(SmartStreamError::InvalidModule(_))
+        ));
+
+        // module without filter function
+        let wasm = module("");
+        assert!(matches!(
+            SmartStream::new(SmartStreamPayload::filter(wasm)),
+            Err(SmartStreamError::InvalidModule(_))
+        ));
+    }
+}
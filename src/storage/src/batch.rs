@@ -46,6 +46,10 @@ where
         &self.inner
     }
 
+    pub fn into_batch(self) -> Batch<R> {
+        self.inner
+    }
+
     pub fn get_pos(&self) -> Size {
         self.pos
     }
@@ -69,7 +73,7 @@ where
     }
 
     pub fn records_remainder_bytes(&self, remainder: usize) -> usize {
-        self.inner.records.remainder_bytes(remainder)
+        self.inner.records().remainder_bytes(remainder)
     }
 
     /// decode next batch from file
@@ -104,6 +108,12 @@ where
         let mut cursor = Cursor::new(bytes);
         let mut batch = Batch::default();
         batch.decode_from_file_buf(&mut cursor, 0)?;
+        if batch.batch_len < BATCH_HEADER_SIZE as i32 {
+            return Err(IoError::new(
+                ErrorKind::InvalidData,
+                format!("invalid batch len: {} at pos: {}", batch.batch_len, pos),
+            ));
+        }
         let mut file_batch = FileBatchPos::new(batch, pos);
 
         let remainder = file_batch.len() as usize - BATCH_HEADER_SIZE as usize;
@@ -149,7 +159,7 @@ where
         }
 
         let mut cursor = Cursor::new(bytes);
-        self.inner.records.decode(&mut cursor, 0)?;
+        self.inner.decode_records(&mut cursor, 0)?;
 
         Ok(())
     }
@@ -223,7 +233,7 @@ impl<R> FileBatchStream<R>
 where
     R: BatchRecords,
 {
-    pub(crate) async fn next(&mut self) -> Option<FileBatchPos<R>> {
+    pub async fn next(&mut self) -> Option<FileBatchPos<R>> {
         trace!("reading next from pos: {}", self.pos);
         match FileBatchPos::from(&mut self.file, self.pos).await {
             Ok(batch_res) => {
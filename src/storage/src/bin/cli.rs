@@ -1,23 +1,55 @@
 use std::path::PathBuf;
-use std::io::Error as IoError;
+use std::process;
 
+use structopt::clap::arg_enum;
 use structopt::StructOpt;
 
 use fluvio_future::task::run_block_on;
 use fluvio_future::fs::util as fs_util;
 
+use dataplane::Offset;
+use dataplane::record::DefaultAsyncBuffer;
+use fluvio_types::defaults::SPU_LOG_INDEX_MAX_BYTES;
+use fluvio_types::defaults::SPU_LOG_INDEX_MAX_INTERVAL_BYTES;
+
+use fluvio_storage::ConfigOption;
 use fluvio_storage::DefaultFileBatchStream;
 use fluvio_storage::LogIndex;
+use fluvio_storage::LogRecovery;
 use fluvio_storage::StorageError;
 use fluvio_storage::OffsetPosition;
+use fluvio_storage::SegmentReport;
+use fluvio_storage::check_replica;
+use fluvio_storage::check_segment;
+use fluvio_storage::read_high_watermark;
+use fluvio_storage::repair_segment;
+use fluvio_storage::truncate_segment;
+use fluvio_storage::log_path_get_offset;
 
 #[derive(Debug, StructOpt)]
-#[structopt(name = "storage", about = "Flavio Storage CLI")]
+#[structopt(name = "storage", about = "Fluvio Storage CLI")]
 enum Main {
+    /// Print batches and records of log segment
     #[structopt(name = "log")]
     Log(LogOpt),
+    /// Print entries of segment index
     #[structopt(name = "index")]
     Index(IndexOpt),
+    /// Check that log segment and its indexes are consistent
+    #[structopt(name = "verify")]
+    Verify(VerifyOpt),
+    /// Check all segments and high watermark of replica directory
+    #[structopt(name = "validate")]
+    Validate(ValidateOpt),
+    /// Print high watermark from checkpoint file
+    #[structopt(name = "checkpoint")]
+    Checkpoint(CheckpointOpt),
+    /// Truncate log segment after last good batch and rebuild its indexes
+    #[structopt(name = "repair")]
+    Repair(RepairOpt),
+    /// Remove batches of log segment from offset and rebuild its indexes
+    #[structopt(name = "truncate")]
+    Truncate(TruncateOpt),
 }
 
 fn main() {
@@ -25,13 +57,35 @@ fn main() {
 
     let opt = Main::from_args();
 
-    let res = match opt {
-        Main::Log(opt) => dump_log(opt),
-        Main::Index(opt) => dump_index(opt),
-    };
+    let result = run_block_on(async {
+        match opt {
+            Main::Log(opt) => dump_log(opt).await,
+            Main::Index(opt) => dump_index(opt).await,
+            Main::Verify(opt) => verify_segment(opt).await,
+            Main::Validate(opt) => validate_replica(opt).await,
+            Main::Checkpoint(opt) => print_checkpoint(opt).await,
+            Main::Repair(opt) => repair(opt).await,
+            Main::Truncate(opt) => truncate(opt).await,
+        }
+    });
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) => {
+            eprintln!("error occurred: {}", err);
+            process::exit(1);
+        }
+    }
+}
 
-    if let Err(err) = res {
-        println!("error occured: {:#?}", err)
+arg_enum! {
+    #[derive(Debug, Clone, PartialEq)]
+    #[allow(non_camel_case_types)]
+    pub enum ValueFormat {
+        text,
+        hex,
+        json,
     }
 }
 
@@ -39,92 +93,281 @@ fn main() {
 pub(crate) struct LogOpt {
     #[structopt(parse(from_os_str))]
     file_name: PathBuf,
-}
 
-async fn print_logs(path: PathBuf) -> Result<(), IoError> {
-    let file = fs_util::open(path).await?;
+    /// Format of record key and value
+    #[structopt(
+        short = "O",
+        long = "format",
+        value_name = "type",
+        possible_values = &ValueFormat::variants(),
+        case_insensitive = true,
+        default_value = "text"
+    )]
+    format: ValueFormat,
+
+    /// Only print batch headers
+    #[structopt(long)]
+    headers_only: bool,
+}
 
-    let mut _batch_stream = DefaultFileBatchStream::new(file);
+async fn dump_log(opt: LogOpt) -> Result<bool, StorageError> {
+    println!("dumping log: {}", opt.file_name.display());
+    let file = fs_util::open(&opt.file_name).await?;
+    let mut batch_stream = DefaultFileBatchStream::new(file);
 
-    //  println!("base offset: {}",batch_stream.get_base_offset());
-    /*
-    TODO: comment out
+    let mut batches = 0;
     while let Some(file_batch) = batch_stream.next().await {
-        // let batch_base_offset = batch.get_base_offset();
         let batch = file_batch.get_batch();
-        //let header = batch.get_header();
-        // let offset_delta = header.last_offset_delta;
+        let header = batch.get_header();
+        batches += 1;
 
         println!(
-            "batch offset: {}, len: {}, pos: {}",
+            "batch offset: {}..={}, pos: {}, len: {}, records: {}, crc: {:#010x} ({}), \
+             first timestamp: {}, max timestamp: {}, producer: {}, attributes: {:#x}",
             batch.get_base_offset(),
-            file_batch.len(),
-            file_batch.get_pos()
+            batch.get_last_offset(),
+            file_batch.get_pos(),
+            file_batch.total_len(),
+            batch.records().len(),
+            header.crc,
+            if batch.verify_crc() {
+                "valid"
+            } else {
+                "invalid"
+            },
+            header.first_timestamp,
+            header.max_time_stamp,
+            header.producer_id,
+            header.attributes
         );
+        if opt.headers_only {
+            continue;
+        }
 
-        for record in &batch.records {
-            println!("record offset: {}", record.get_offset_delta());
+        for record in batch.records() {
+            println!(
+                "  offset: {}, timestamp: {}, key: {}, value: {}",
+                batch.get_base_offset() + record.get_offset_delta(),
+                batch.get_record_timestamp(record),
+                format_value(&record.key, &opt.format),
+                format_value(&record.value, &opt.format)
+            );
         }
     }
-    */
 
-    Ok(())
+    if let Some(err) = batch_stream.invalid() {
+        println!("invalid batch after {} batches: {}", batches, err);
+        return Ok(false);
+    }
+    println!("{} batches", batches);
+    Ok(true)
 }
 
-pub(crate) fn dump_log(opt: LogOpt) -> Result<(), IoError> {
-    let file_path = opt.file_name;
-
-    println!("dumping batch: {:#?}", file_path);
-    let ft = print_logs(file_path);
-    let result = run_block_on(ft);
-    if let Err(err) = result {
-        println!("error in async: {:#?}", err)
+/// render key or value of record, null is printed for missing value
+fn format_value(buffer: &DefaultAsyncBuffer, format: &ValueFormat) -> String {
+    let bytes = match buffer.inner_value_ref() {
+        Some(bytes) => bytes,
+        None => return "null".to_owned(),
     };
-    Ok(())
+    match format {
+        ValueFormat::text => format!("{:?}", String::from_utf8_lossy(bytes)),
+        ValueFormat::hex => bytes.iter().map(|byte| format!("{:02x}", byte)).collect(),
+        ValueFormat::json => match serde_json::from_slice::<serde_json::Value>(bytes) {
+            Ok(value) => value.to_string(),
+            Err(_) => {
+                serde_json::Value::String(String::from_utf8_lossy(bytes).into_owned()).to_string()
+            }
+        },
+    }
 }
 
 #[derive(Debug, StructOpt)]
 pub(crate) struct IndexOpt {
     #[structopt(parse(from_os_str))]
     file_name: PathBuf,
+
+    /// Max number of entries to print
+    #[structopt(long, value_name = "integer", default_value = "100")]
+    max: usize,
 }
 
-pub(crate) fn dump_index(opt: IndexOpt) -> Result<(), IoError> {
-    let file_path = opt.file_name;
+async fn dump_index(opt: IndexOpt) -> Result<bool, StorageError> {
+    println!("dumping index: {}", opt.file_name.display());
+    let base_offset = log_path_get_offset(&opt.file_name)?;
+    let index = LogIndex::open_from_path(&opt.file_name).await?;
 
-    println!("dumping index: {:#?}", file_path);
-    let ft = print_index(file_path);
-    let result = run_block_on(ft);
-    if let Err(err) = result {
-        println!("error in async: {:#?}", err)
-    };
-    Ok(())
+    println!("has {} slots", index.len());
+    let mut count = 0;
+    for (i, entry) in index.iter().enumerate() {
+        if entry.position() == 0 {
+            continue;
+        }
+        count += 1;
+        if count <= opt.max {
+            println!(
+                "i: {} offset: {} pos: {}",
+                i,
+                base_offset + entry.offset() as Offset,
+                entry.position()
+            );
+        }
+    }
+    if count > opt.max {
+        println!("there was {} entries only {} was displayed", count, opt.max);
+    } else {
+        println!("there was {} entries", count);
+    }
+
+    Ok(true)
 }
 
-const MAX: u32 = 100;
+#[derive(Debug, StructOpt)]
+pub(crate) struct VerifyOpt {
+    /// Log segment, its index files are found in same directory
+    #[structopt(parse(from_os_str))]
+    file_name: PathBuf,
+}
 
-async fn print_index(path: PathBuf) -> Result<(), StorageError> {
-    let log = LogIndex::open_from_path(path).await?;
+async fn verify_segment(opt: VerifyOpt) -> Result<bool, StorageError> {
+    let report = check_segment(&opt.file_name).await?;
+    print_segment_report(&report);
+    Ok(report.is_valid())
+}
 
-    println!("has {} bytes", log.len());
-    let entries = log.len();
-    let mut count: u32 = 0;
-    let mut display: u32 = 0;
-    for i in 0..entries {
-        let (offset, pos) = log[i as usize].to_be();
-        if offset > 0 && pos > 0 {
-            count += 1;
-            if count < MAX {
-                println!("i: {} offset: {}  pos: {}", i, offset, pos);
-                display += 1;
-            }
-        }
+fn print_segment_report(report: &SegmentReport) {
+    println!(
+        "segment: {}, offsets: {}..{}, len: {}, batches: {}, index entries: {}, time index entries: {}",
+        report.base_offset,
+        report.base_offset,
+        report.end_offset,
+        report.log_len,
+        report.batches,
+        report.index_entries,
+        report.time_index_entries
+    );
+    for issue in &report.issues {
+        println!("  {}", issue);
     }
-    if count > MAX {
-        println!("there was {} entries only {} was displayed", count, display);
-    } else {
-        println!("there was {} entries:", count);
+}
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct ValidateOpt {
+    /// Replica directory, such as spu-logs-5001/topic-0
+    #[structopt(parse(from_os_str))]
+    dir: PathBuf,
+}
+
+async fn validate_replica(opt: ValidateOpt) -> Result<bool, StorageError> {
+    let report = check_replica(&opt.dir).await?;
+    for segment in &report.segments {
+        print_segment_report(segment);
+    }
+    match report.high_watermark {
+        Some(high_watermark) => println!("high watermark: {}", high_watermark),
+        None => println!("high watermark: none"),
     }
+    for issue in &report.issues {
+        println!("{}", issue);
+    }
+
+    let valid = report.is_valid();
+    println!(
+        "replica: {} is {}",
+        opt.dir.display(),
+        if valid { "valid" } else { "invalid" }
+    );
+    Ok(valid)
+}
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct CheckpointOpt {
+    /// Checkpoint file, such as replication.chk
+    #[structopt(parse(from_os_str))]
+    file_name: PathBuf,
+}
+
+async fn print_checkpoint(opt: CheckpointOpt) -> Result<bool, StorageError> {
+    let high_watermark = read_high_watermark(&opt.file_name).await?;
+    println!("high watermark: {}", high_watermark);
+    Ok(true)
+}
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct IndexConfigOpt {
+    /// Max size of index file, must match SPU setting
+    #[structopt(long, value_name = "integer")]
+    index_max_bytes: Option<u32>,
+
+    /// Bytes between index entries, must match SPU setting
+    #[structopt(long, value_name = "integer")]
+    index_max_interval_bytes: Option<u32>,
+}
+
+impl IndexConfigOpt {
+    fn as_config(&self) -> ConfigOption {
+        ConfigOption {
+            index_max_bytes: self.index_max_bytes.unwrap_or(SPU_LOG_INDEX_MAX_BYTES),
+            index_max_interval_bytes: self
+                .index_max_interval_bytes
+                .unwrap_or(SPU_LOG_INDEX_MAX_INTERVAL_BYTES),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct RepairOpt {
+    /// Log segment, its index files are rebuilt
+    #[structopt(parse(from_os_str))]
+    file_name: PathBuf,
+
+    /// Repair segment even if it is not last segment of replica
+    #[structopt(long)]
+    force: bool,
+
+    #[structopt(flatten)]
+    index: IndexConfigOpt,
+}
+
+async fn repair(opt: RepairOpt) -> Result<bool, StorageError> {
+    let recovery = repair_segment(&opt.file_name, opt.force, &opt.index.as_config()).await?;
+    print_recovery(&recovery);
+    Ok(true)
+}
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct TruncateOpt {
+    /// Log segment, its index files are rebuilt
+    #[structopt(parse(from_os_str))]
+    file_name: PathBuf,
+
+    /// Batch containing this offset and all batches after it are removed
+    #[structopt(long, value_name = "integer")]
+    offset: Offset,
+
+    /// Truncate segment even if it is not last segment of replica
+    #[structopt(long)]
+    force: bool,
+
+    #[structopt(flatten)]
+    index: IndexConfigOpt,
+}
+
+async fn truncate(opt: TruncateOpt) -> Result<bool, StorageError> {
+    let recovery = truncate_segment(
+        &opt.file_name,
+        opt.offset,
+        opt.force,
+        &opt.index.as_config(),
+    )
+    .await?;
+    print_recovery(&recovery);
+    Ok(true)
+}
 
-    Ok(())
+fn print_recovery(recovery: &LogRecovery) {
+    println!(
+        "end offset: {}, log len: {}, discarded bytes: {}",
+        recovery.end_offset, recovery.valid_len, recovery.discarded_bytes
+    );
 }
@@ -0,0 +1,332 @@
+//!
+//! # Compaction
+//!
+//! Read only segments of compacted replica are rewritten to keep only last record of each key.
+//! Records keep their offsets, so readers skip over removed offsets. Last batch of segment
+//! is always kept, even when all of its records are removed, so end offset of segment is
+//! not changed.
+//!
+//! Compacted segment is written to compaction directory of replica and then moved over
+//! original segment. Marker file is written before files are moved, so interrupted swap
+//! is completed when replica is opened again.
+//!
+//! Compacted copies are written from segment files without access to replica, so replica
+//! can still be written and read meanwhile. Only swapping segments needs the replica.
+//!
+//! Only committed records, before high watermark, are compacted. Uncommitted records may
+//! be truncated when leader changes, so they neither replace committed records of their
+//! key nor are they removed.
+//!
+use std::collections::HashMap;
+use std::path::Path;
+
+use tracing::debug;
+use tracing::trace;
+
+use dataplane::{current_timestamp, Offset, Size, Timestamp};
+use fluvio_future::fs::create_dir_all;
+use fluvio_future::fs::remove_dir_all;
+use fluvio_future::fs::remove_file;
+use fluvio_future::fs::rename;
+use fluvio_future::fs::util as file_util;
+
+use crate::batch::DefaultFileBatchStream;
+use crate::index::EXTENSION as INDEX_EXTENSION;
+use crate::records::MESSAGE_LOG_EXTENSION;
+use crate::segment::MutableSegment;
+use crate::segment::ReadSegment;
+use crate::time_index::EXTENSION as TIME_INDEX_EXTENSION;
+use crate::util::generate_file_name;
+use crate::util::log_path_get_offset;
+use crate::ConfigOption;
+use crate::StorageError;
+
+/// directory of replica where compacted segments are written
+pub(crate) const COMPACTION_DIR: &str = "compaction";
+
+const SWAP_EXTENSION: &str = "swap";
+
+/// files of segment, log is moved last
+const SEGMENT_EXTENSIONS: [&str; 3] =
+    [INDEX_EXTENSION, TIME_INDEX_EXTENSION, MESSAGE_LOG_EXTENSION];
+
+/// offset of last record of each key
+pub(crate) type KeyOffsets = HashMap<Vec<u8>, Offset>;
+
+/// Read only segments of replica to be compacted, taken from replica by `FileReplica::compaction`
+#[derive(Debug)]
+pub struct Compaction {
+    option: ConfigOption,
+    base_offsets: Vec<Offset>,
+    active_base_offset: Offset,
+    /// size of active segment log when compaction was planned, later records are not read
+    active_len: Size,
+    /// records at or after high watermark are not compacted
+    high_watermark: Offset,
+}
+
+/// Compacted copies of segments, swapped in by `FileReplica::swap_compacted`
+#[derive(Debug)]
+pub struct CompactedSegments {
+    /// base offset of segment and number of records removed from it
+    segments: Vec<(Offset, usize)>,
+}
+
+impl CompactedSegments {
+    pub(crate) fn into_segments(self) -> Vec<(Offset, usize)> {
+        self.segments
+    }
+}
+
+impl Compaction {
+    pub(crate) fn new(
+        option: ConfigOption,
+        base_offsets: Vec<Offset>,
+        active_base_offset: Offset,
+        active_len: Size,
+        high_watermark: Offset,
+    ) -> Self {
+        Self {
+            option,
+            base_offsets,
+            active_base_offset,
+            active_len,
+            high_watermark,
+        }
+    }
+
+    /// write compacted copies of segments to compaction directory of replica
+    ///
+    /// segments which have nothing to remove are not copied
+    pub async fn compact(self) -> Result<CompactedSegments, StorageError> {
+        let replica_dir = &self.option.base_dir;
+        let high_watermark = self.high_watermark;
+        let mut keys = KeyOffsets::new();
+        for base_offset in &self.base_offsets {
+            let log_path = generate_file_name(replica_dir, *base_offset, MESSAGE_LOG_EXTENSION);
+            collect_key_offsets(&log_path, Size::MAX, high_watermark, &mut keys).await?;
+        }
+        let active_log_path =
+            generate_file_name(replica_dir, self.active_base_offset, MESSAGE_LOG_EXTENSION);
+        collect_key_offsets(&active_log_path, self.active_len, high_watermark, &mut keys).await?;
+        debug!(
+            "compacting replica with {} keys before hw: {}",
+            keys.len(),
+            high_watermark
+        );
+
+        let tombstone_before =
+            current_timestamp() - self.option.delete_retention_seconds as Timestamp * 1000;
+        create_compaction_dir(replica_dir).await?;
+        let compaction_option = self
+            .option
+            .clone()
+            .base_dir(replica_dir.join(COMPACTION_DIR));
+
+        let mut segments = vec![];
+        for base_offset in self.base_offsets {
+            let segment = ReadSegment::open_for_read(base_offset, &self.option).await?;
+            let removed = compact_segment(
+                &segment,
+                &keys,
+                tombstone_before,
+                high_watermark,
+                &compaction_option,
+            )
+            .await?;
+            if removed > 0 {
+                segments.push((base_offset, removed));
+            }
+        }
+        Ok(CompactedSegments { segments })
+    }
+}
+
+/// record offsets of keys in segment log up to `end_pos`, later records replace earlier ones
+///
+/// records at or after `high_watermark` are not committed, so they are skipped
+async fn collect_key_offsets(
+    log_path: &Path,
+    end_pos: Size,
+    high_watermark: Offset,
+    keys: &mut KeyOffsets,
+) -> Result<(), StorageError> {
+    let file = file_util::open(log_path).await?;
+    let mut batch_stream = DefaultFileBatchStream::new(file);
+    let mut pos = 0;
+    while pos < end_pos {
+        let batch_pos = match batch_stream.next().await {
+            Some(batch_pos) => batch_pos,
+            None => break,
+        };
+        pos = batch_pos.get_pos() + batch_pos.total_len();
+        let batch = batch_pos.get_batch();
+        for record in batch.records() {
+            let offset = batch.get_base_offset() + record.get_offset_delta();
+            if offset >= high_watermark {
+                break;
+            }
+            if let Some(key) = record.key.inner_value_ref() {
+                keys.insert(key.clone(), offset);
+            }
+        }
+    }
+    if let Some(err) = batch_stream.invalid() {
+        return Err(err.into());
+    }
+    Ok(())
+}
+
+/// write compacted copy of segment to `option.base_dir`
+///
+/// record is kept if it has no key, it is last record of its key or it is at or after
+/// `high_watermark`, tombstone is kept only if its timestamp is not before `tombstone_before`.
+/// return number of records removed, copy is removed if nothing could be removed.
+async fn compact_segment(
+    segment: &ReadSegment,
+    keys: &KeyOffsets,
+    tombstone_before: Timestamp,
+    high_watermark: Offset,
+    option: &ConfigOption,
+) -> Result<usize, StorageError> {
+    let base_offset = segment.get_base_offset();
+    let mut compacted = MutableSegment::create(base_offset, option).await?;
+    let mut batch_stream = segment.open_default_batch_stream().await?;
+    let mut removed = 0;
+    let mut last_batch = None;
+
+    while let Some(batch_pos) = batch_stream.next().await {
+        let mut batch = batch_pos.into_batch();
+        let keep: Vec<bool> = batch
+            .records()
+            .iter()
+            .map(|record| {
+                let offset = batch.get_base_offset() + record.get_offset_delta();
+                match record.key.inner_value_ref() {
+                    Some(key) if offset < high_watermark => {
+                        let is_last = keys.get(key) == Some(&offset);
+                        let is_expired_tombstone = record.value.inner_value_ref().is_none()
+                            && batch.get_record_timestamp(record) < tombstone_before;
+                        is_last && !is_expired_tombstone
+                    }
+                    _ => true,
+                }
+            })
+            .collect();
+        let kept = keep.iter().filter(|keep| **keep).count();
+        if kept < keep.len() {
+            removed += keep.len() - kept;
+            let mut keep = keep.into_iter();
+            batch.retain_records(|_| keep.next().unwrap_or(true));
+        }
+
+        // empty batch is only written if it is last one of segment
+        if let Some(previous) = last_batch.replace(batch) {
+            if !previous.records().is_empty() {
+                compacted.send(previous).await?;
+            }
+        }
+    }
+    if let Some(err) = batch_stream.invalid() {
+        return Err(err.into());
+    }
+    if let Some(batch) = last_batch {
+        compacted.send(batch).await?;
+    }
+
+    compacted.roll_over().await?;
+    compacted.sync().await?;
+    drop(compacted);
+
+    if removed == 0 {
+        trace!("nothing to compact in segment: {}", base_offset);
+        remove_segment_files(&option.base_dir, base_offset).await?;
+    }
+    Ok(removed)
+}
+
+/// move compacted segment from compaction directory over original segment
+pub(crate) async fn swap_segment(
+    base_offset: Offset,
+    compaction_dir: &Path,
+    replica_dir: &Path,
+) -> Result<(), StorageError> {
+    let marker = generate_file_name(compaction_dir, base_offset, SWAP_EXTENSION);
+    let file = file_util::create(&marker).await?;
+    file.sync_all().await?;
+    drop(file);
+    complete_swap(base_offset, compaction_dir, replica_dir).await
+}
+
+/// move files of segment which are still in compaction directory and remove marker
+async fn complete_swap(
+    base_offset: Offset,
+    compaction_dir: &Path,
+    replica_dir: &Path,
+) -> Result<(), StorageError> {
+    for extension in SEGMENT_EXTENSIONS.iter() {
+        let compacted = generate_file_name(compaction_dir, base_offset, extension);
+        if compacted.exists() {
+            debug!("moving compacted file: {}", compacted.display());
+            rename(
+                &compacted,
+                generate_file_name(replica_dir, base_offset, extension),
+            )
+            .await?;
+        }
+    }
+    remove_file(generate_file_name(
+        compaction_dir,
+        base_offset,
+        SWAP_EXTENSION,
+    ))
+    .await?;
+    Ok(())
+}
+
+/// complete swaps interrupted by crash and remove incomplete compacted segments,
+/// this must be done before segments of replica are opened
+pub(crate) async fn recover_compaction(replica_dir: &Path) -> Result<(), StorageError> {
+    let compaction_dir = replica_dir.join(COMPACTION_DIR);
+    if !compaction_dir.exists() {
+        return Ok(());
+    }
+
+    for entry in compaction_dir.read_dir()? {
+        let path = entry?.path();
+        if path.extension().and_then(|extension| extension.to_str()) == Some(SWAP_EXTENSION) {
+            let base_offset = log_path_get_offset(&path)?;
+            debug!("completing swap of compacted segment: {}", base_offset);
+            complete_swap(base_offset, &compaction_dir, replica_dir).await?;
+        }
+    }
+
+    remove_dir_all(&compaction_dir).await?;
+    Ok(())
+}
+
+/// create empty compaction directory
+async fn create_compaction_dir(replica_dir: &Path) -> Result<(), StorageError> {
+    let compaction_dir = replica_dir.join(COMPACTION_DIR);
+    if compaction_dir.exists() {
+        remove_dir_all(&compaction_dir).await?;
+    }
+    create_dir_all(&compaction_dir).await?;
+    Ok(())
+}
+
+/// remove compaction directory after all compacted segments are swapped
+pub(crate) async fn remove_compaction_dir(replica_dir: &Path) -> Result<(), StorageError> {
+    remove_dir_all(replica_dir.join(COMPACTION_DIR)).await?;
+    Ok(())
+}
+
+async fn remove_segment_files(dir: &Path, base_offset: Offset) -> Result<(), StorageError> {
+    for extension in SEGMENT_EXTENSIONS.iter() {
+        let path = generate_file_name(dir, base_offset, extension);
+        if path.exists() {
+            remove_file(path).await?;
+        }
+    }
+    Ok(())
+}
@@ -8,6 +8,7 @@ use serde::Deserialize;
 use fluvio_types::defaults::SPU_LOG_INDEX_MAX_BYTES;
 use fluvio_types::defaults::SPU_LOG_INDEX_MAX_INTERVAL_BYTES;
 use fluvio_types::defaults::SPU_LOG_SEGMENT_MAX_BYTES;
+use fluvio_types::defaults::SPU_LOG_DELETE_RETENTION_SEC;
 
 use dataplane::Size;
 
@@ -22,6 +23,54 @@ pub struct ConfigOption {
     pub index_max_interval_bytes: Size,
     #[serde(default = "default_segment_max_bytes")]
     pub segment_max_bytes: Size,
+    /// read only segments older than this are removed, no limit if not set
+    #[serde(default)]
+    pub retention_seconds: Option<u32>,
+    /// oldest read only segments are removed while replica is bigger than this, no limit if not set
+    #[serde(default)]
+    pub retention_bytes: Option<u64>,
+    /// check crc of batches when records are read, corrupted records are not sent
+    #[serde(default)]
+    pub verify_crc_on_read: bool,
+    /// when written records are synced to disk
+    #[serde(default)]
+    pub flush_policy: FlushPolicy,
+    /// sync written records before high watermark is moved past them
+    #[serde(default)]
+    pub flush_before_commit: bool,
+    /// how old records are cleaned up
+    #[serde(default)]
+    pub cleanup_policy: CleanupPolicy,
+    /// tombstones of compacted replica are removed once they are older than this
+    #[serde(default = "default_delete_retention_seconds")]
+    pub delete_retention_seconds: u32,
+}
+
+/// Policy for cleaning up old records of replica
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+pub enum CleanupPolicy {
+    /// old segments are removed by retention
+    #[default]
+    Delete,
+    /// read only segments are compacted to keep only last record of each key
+    Compact,
+}
+
+/// Policy for syncing records of active segment to disk.
+///
+/// Records are always handed to OS after every batch so they can be read,
+/// policy controls when OS is asked to write them to disk.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+pub enum FlushPolicy {
+    /// OS decides when records are written to disk
+    #[default]
+    OsManaged,
+    /// sync after every batch
+    EveryBatch,
+    /// sync once at least this many records are written since last sync
+    EveryRecords(u32),
+    /// sync when batch is written at least this many milliseconds after last sync
+    Interval(u32),
 }
 
 impl fmt::Display for ConfigOption {
@@ -46,6 +95,10 @@ fn default_segment_max_bytes() -> Size {
     SPU_LOG_SEGMENT_MAX_BYTES
 }
 
+fn default_delete_retention_seconds() -> u32 {
+    SPU_LOG_DELETE_RETENTION_SEC
+}
+
 impl ConfigOption {
     pub fn new(
         base_dir: PathBuf,
@@ -58,6 +111,13 @@ impl ConfigOption {
             index_max_bytes,
             index_max_interval_bytes,
             segment_max_bytes,
+            retention_seconds: None,
+            retention_bytes: None,
+            verify_crc_on_read: false,
+            flush_policy: FlushPolicy::default(),
+            flush_before_commit: false,
+            cleanup_policy: CleanupPolicy::default(),
+            delete_retention_seconds: default_delete_retention_seconds(),
         }
     }
 
@@ -75,6 +135,41 @@ impl ConfigOption {
         self.segment_max_bytes = bytes;
         self
     }
+
+    pub fn retention_seconds(mut self, seconds: Option<u32>) -> Self {
+        self.retention_seconds = seconds;
+        self
+    }
+
+    pub fn retention_bytes(mut self, bytes: Option<u64>) -> Self {
+        self.retention_bytes = bytes;
+        self
+    }
+
+    pub fn verify_crc_on_read(mut self, verify: bool) -> Self {
+        self.verify_crc_on_read = verify;
+        self
+    }
+
+    pub fn flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.flush_policy = policy;
+        self
+    }
+
+    pub fn flush_before_commit(mut self, flush: bool) -> Self {
+        self.flush_before_commit = flush;
+        self
+    }
+
+    pub fn cleanup_policy(mut self, policy: CleanupPolicy) -> Self {
+        self.cleanup_policy = policy;
+        self
+    }
+
+    pub fn delete_retention_seconds(mut self, seconds: u32) -> Self {
+        self.delete_retention_seconds = seconds;
+        self
+    }
 }
 
 impl Default for ConfigOption {
@@ -84,6 +179,13 @@ impl Default for ConfigOption {
             index_max_bytes: default_index_max_bytes(),
             index_max_interval_bytes: default_index_max_interval_bytes(),
             segment_max_bytes: default_segment_max_bytes(),
+            retention_seconds: None,
+            retention_bytes: None,
+            verify_crc_on_read: false,
+            flush_policy: FlushPolicy::default(),
+            flush_before_commit: false,
+            cleanup_policy: CleanupPolicy::default(),
+            delete_retention_seconds: default_delete_retention_seconds(),
         }
     }
 }
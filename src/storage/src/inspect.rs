@@ -0,0 +1,690 @@
+//!
+//! # Offline inspection
+//!
+//! Check and repair segment files of replica without opening it. These are used by
+//! storage cli on copies of SPU data directories, so checks never modify files.
+//!
+use std::fmt;
+use std::io::Error as IoError;
+use std::io::ErrorKind;
+use std::mem::size_of;
+use std::path::Path;
+
+use futures_lite::io::AsyncWriteExt;
+use tracing::debug;
+use tracing::warn;
+
+use dataplane::{Offset, Size, Timestamp};
+use fluvio_future::fs::read;
+use fluvio_future::fs::util as file_util;
+use fluvio_future::fs::OpenOptions;
+
+use crate::compaction::COMPACTION_DIR;
+use crate::index::OffsetPosition;
+use crate::index::EXTENSION as INDEX_EXTENSION;
+use crate::mut_index::MutLogIndex;
+use crate::records::MESSAGE_LOG_EXTENSION;
+use crate::replica::HW_CHECKPOINT_FILE_NAME;
+use crate::time_index::TimeIndex;
+use crate::util::generate_file_name;
+use crate::util::log_path_get_offset;
+use crate::validator::find_corrupt_batches;
+use crate::validator::recover;
+use crate::validator::LogRecovery;
+use crate::BatchHeaderStream;
+use crate::ConfigOption;
+use crate::CorruptBatch;
+use crate::StorageError;
+
+/// problem found in segment files
+#[derive(Debug, Clone, PartialEq)]
+pub enum SegmentIssue {
+    /// bytes at end of log which are not complete batch
+    TornBatch {
+        position: u64,
+        len: u64,
+    },
+    CorruptBatch(CorruptBatch),
+    /// batch doesn't start after previous batch or before base offset of segment
+    OffsetNotOrdered {
+        position: u64,
+        base_offset: Offset,
+    },
+    /// index entry doesn't point to start of batch
+    InvalidIndexEntry {
+        offset: Offset,
+        position: Size,
+    },
+    /// index entry points to batch with different base offset
+    IndexOffsetMismatch {
+        offset: Offset,
+        position: Size,
+        batch_offset: Offset,
+    },
+    /// index entry is not after previous entry
+    IndexNotOrdered {
+        offset: Offset,
+        position: Size,
+    },
+    /// time index entry doesn't point to base offset of batch
+    InvalidTimeIndexEntry {
+        timestamp: Timestamp,
+        offset: Offset,
+    },
+    /// time index entry is not after previous entry
+    TimeIndexNotOrdered {
+        timestamp: Timestamp,
+        offset: Offset,
+    },
+}
+
+impl fmt::Display for SegmentIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::TornBatch { position, len } => {
+                write!(f, "incomplete batch at pos: {}, {} bytes", position, len)
+            }
+            Self::CorruptBatch(batch) => write!(
+                f,
+                "crc mismatch at pos: {}, offsets: {}..={}",
+                batch.position, batch.base_offset, batch.last_offset
+            ),
+            Self::OffsetNotOrdered {
+                position,
+                base_offset,
+            } => write!(
+                f,
+                "batch at pos: {} with base offset: {} is not after previous batch",
+                position, base_offset
+            ),
+            Self::InvalidIndexEntry { offset, position } => write!(
+                f,
+                "index entry offset: {} pos: {} is not start of batch",
+                offset, position
+            ),
+            Self::IndexOffsetMismatch {
+                offset,
+                position,
+                batch_offset,
+            } => write!(
+                f,
+                "index entry offset: {} pos: {} points to batch with offset: {}",
+                offset, position, batch_offset
+            ),
+            Self::IndexNotOrdered { offset, position } => write!(
+                f,
+                "index entry offset: {} pos: {} is not after previous entry",
+                offset, position
+            ),
+            Self::InvalidTimeIndexEntry { timestamp, offset } => write!(
+                f,
+                "time index entry timestamp: {} offset: {} is not start of batch",
+                timestamp, offset
+            ),
+            Self::TimeIndexNotOrdered { timestamp, offset } => write!(
+                f,
+                "time index entry timestamp: {} offset: {} is not after previous entry",
+                timestamp, offset
+            ),
+        }
+    }
+}
+
+/// result of checking segment files
+#[derive(Debug, Default)]
+pub struct SegmentReport {
+    pub base_offset: Offset,
+    /// offset after last complete batch
+    pub end_offset: Offset,
+    pub log_len: u64,
+    pub batches: usize,
+    pub index_entries: usize,
+    pub time_index_entries: usize,
+    pub issues: Vec<SegmentIssue>,
+}
+
+impl SegmentReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// problem found in replica directory outside of segments
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplicaIssue {
+    /// segment doesn't start at end offset of previous segment
+    SegmentNotContinuous {
+        base_offset: Offset,
+        previous_end_offset: Offset,
+    },
+    MissingCheckpoint,
+    /// high watermark is not in offsets of replica
+    InvalidHighWatermark {
+        high_watermark: Offset,
+        end_offset: Offset,
+    },
+    /// compaction was interrupted, it is completed when replica is opened
+    IncompleteCompaction,
+}
+
+impl fmt::Display for ReplicaIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::SegmentNotContinuous {
+                base_offset,
+                previous_end_offset,
+            } => write!(
+                f,
+                "segment: {} doesn't start at end of previous segment: {}",
+                base_offset, previous_end_offset
+            ),
+            Self::MissingCheckpoint => write!(f, "high watermark checkpoint is missing"),
+            Self::InvalidHighWatermark {
+                high_watermark,
+                end_offset,
+            } => write!(
+                f,
+                "high watermark: {} is after end offset: {}",
+                high_watermark, end_offset
+            ),
+            Self::IncompleteCompaction => write!(f, "compaction is not completed"),
+        }
+    }
+}
+
+/// result of checking all segments of replica
+#[derive(Debug, Default)]
+pub struct ReplicaReport {
+    pub segments: Vec<SegmentReport>,
+    pub high_watermark: Option<Offset>,
+    pub issues: Vec<ReplicaIssue>,
+}
+
+impl ReplicaReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty() && self.segments.iter().all(|segment| segment.is_valid())
+    }
+}
+
+/// check that batches of log are complete and ordered and indexes point to them
+pub async fn check_segment<P>(log_path: P) -> Result<SegmentReport, StorageError>
+where
+    P: AsRef<Path>,
+{
+    let log_path = log_path.as_ref();
+    let base_offset = log_path_get_offset(log_path)?;
+    let dir = log_path.parent().unwrap_or_else(|| Path::new("."));
+    let file = file_util::open(log_path).await?;
+    let log_len = file.metadata().await?.len();
+    debug!("checking segment: {}, len: {}", log_path.display(), log_len);
+
+    let mut report = SegmentReport {
+        base_offset,
+        end_offset: base_offset,
+        log_len,
+        ..Default::default()
+    };
+
+    // position and base offset of each batch
+    let mut batches: Vec<(Size, Offset)> = vec![];
+    let mut valid_len: u64 = 0;
+    let mut batch_stream = BatchHeaderStream::new(file);
+    while let Some(batch_pos) = batch_stream.next().await {
+        let batch_end = batch_pos.get_pos() as u64 + batch_pos.total_len() as u64;
+        if batch_end > log_len {
+            break;
+        }
+        let batch_base_offset = batch_pos.get_base_offset();
+        if batch_base_offset < report.end_offset {
+            report.issues.push(SegmentIssue::OffsetNotOrdered {
+                position: batch_pos.get_pos() as u64,
+                base_offset: batch_base_offset,
+            });
+        }
+        batches.push((batch_pos.get_pos(), batch_base_offset));
+        report.end_offset = batch_pos.get_last_offset() + 1;
+        valid_len = batch_end;
+    }
+    report.batches = batches.len();
+
+    let corrupted = find_corrupt_batches(log_path, 0, valid_len, base_offset, report.end_offset)
+        .await?
+        .into_iter()
+        .map(SegmentIssue::CorruptBatch);
+    report.issues.extend(corrupted);
+    if valid_len < log_len {
+        report.issues.push(SegmentIssue::TornBatch {
+            position: valid_len,
+            len: log_len - valid_len,
+        });
+    }
+
+    let index_path = generate_file_name(dir, base_offset, INDEX_EXTENSION);
+    let entries = read_index_entries(&index_path).await?;
+    report.index_entries = entries.len();
+    let mut previous: Option<(Size, Size)> = None;
+    for entry in entries {
+        let offset = base_offset + entry.offset() as Offset;
+        let position = entry.position();
+        if let Some(previous) = previous {
+            if entry.offset() <= previous.offset() || position <= previous.position() {
+                report
+                    .issues
+                    .push(SegmentIssue::IndexNotOrdered { offset, position });
+            }
+        }
+        match batches.binary_search_by(|(batch_pos, _)| batch_pos.cmp(&position)) {
+            Ok(idx) if batches[idx].1 != offset => {
+                report.issues.push(SegmentIssue::IndexOffsetMismatch {
+                    offset,
+                    position,
+                    batch_offset: batches[idx].1,
+                })
+            }
+            Ok(_) => {}
+            Err(_) => report
+                .issues
+                .push(SegmentIssue::InvalidIndexEntry { offset, position }),
+        }
+        previous = Some(entry);
+    }
+
+    let option = ConfigOption::default().base_dir(dir.to_path_buf());
+    let time_index = TimeIndex::open_for_read(base_offset, &option).await?;
+    report.time_index_entries = time_index.entries().len();
+    let mut previous: Option<(Timestamp, Size)> = None;
+    for (timestamp, relative_offset) in time_index.entries().iter().copied() {
+        let offset = base_offset + relative_offset as Offset;
+        if let Some((previous_timestamp, previous_offset)) = previous {
+            if timestamp <= previous_timestamp || relative_offset <= previous_offset {
+                report
+                    .issues
+                    .push(SegmentIssue::TimeIndexNotOrdered { timestamp, offset });
+            }
+        }
+        if !batches
+            .iter()
+            .any(|(_, batch_offset)| *batch_offset == offset)
+        {
+            report
+                .issues
+                .push(SegmentIssue::InvalidTimeIndexEntry { timestamp, offset });
+        }
+        previous = Some((timestamp, relative_offset));
+    }
+
+    Ok(report)
+}
+
+/// check all segments of replica directory and its high watermark checkpoint
+pub async fn check_replica<P>(replica_dir: P) -> Result<ReplicaReport, StorageError>
+where
+    P: AsRef<Path>,
+{
+    let replica_dir = replica_dir.as_ref();
+    let mut report = ReplicaReport::default();
+    for base_offset in segment_base_offsets(replica_dir)? {
+        let log_path = generate_file_name(replica_dir, base_offset, MESSAGE_LOG_EXTENSION);
+        let segment = check_segment(&log_path).await?;
+        if let Some(previous) = report.segments.last() {
+            if segment.base_offset != previous.end_offset {
+                report.issues.push(ReplicaIssue::SegmentNotContinuous {
+                    base_offset: segment.base_offset,
+                    previous_end_offset: previous.end_offset,
+                });
+            }
+        }
+        report.segments.push(segment);
+    }
+
+    let checkpoint_path = replica_dir.join(HW_CHECKPOINT_FILE_NAME);
+    if checkpoint_path.exists() {
+        let high_watermark = read_high_watermark(&checkpoint_path).await?;
+        if let Some(last) = report.segments.last() {
+            if high_watermark > last.end_offset {
+                report.issues.push(ReplicaIssue::InvalidHighWatermark {
+                    high_watermark,
+                    end_offset: last.end_offset,
+                });
+            }
+        }
+        report.high_watermark = Some(high_watermark);
+    } else {
+        report.issues.push(ReplicaIssue::MissingCheckpoint);
+    }
+
+    if replica_dir.join(COMPACTION_DIR).exists() {
+        report.issues.push(ReplicaIssue::IncompleteCompaction);
+    }
+
+    Ok(report)
+}
+
+/// base offsets of all segments in replica directory, in order
+fn segment_base_offsets(replica_dir: &Path) -> Result<Vec<Offset>, StorageError> {
+    let mut base_offsets = vec![];
+    for entry in replica_dir.read_dir()? {
+        let path = entry?.path();
+        if path.extension().and_then(|extension| extension.to_str()) == Some(MESSAGE_LOG_EXTENSION)
+        {
+            base_offsets.push(log_path_get_offset(&path)?);
+        }
+    }
+    base_offsets.sort_unstable();
+    Ok(base_offsets)
+}
+
+/// check if log can be shortened, returns true if it is last segment of replica
+///
+/// shortening other segments leaves gap before following segment, so it requires `force`
+fn check_last_segment(log_path: &Path, force: bool) -> Result<bool, StorageError> {
+    let base_offset = log_path_get_offset(log_path)?;
+    let dir = log_path.parent().unwrap_or_else(|| Path::new("."));
+    let next_segment = segment_base_offsets(dir)?
+        .into_iter()
+        .find(|offset| *offset > base_offset);
+    match next_segment {
+        None => Ok(true),
+        Some(_) if force => Ok(false),
+        Some(next_offset) => Err(IoError::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "{} is not last segment, next segment starts at offset: {}",
+                log_path.display(),
+                next_offset
+            ),
+        )
+        .into()),
+    }
+}
+
+/// lower high watermark checkpoint in directory of log to `end_offset` if it is after it,
+/// so replica doesn't commit offsets which have been removed
+async fn clamp_high_watermark(log_path: &Path, end_offset: Offset) -> Result<(), StorageError> {
+    let dir = log_path.parent().unwrap_or_else(|| Path::new("."));
+    let checkpoint_path = dir.join(HW_CHECKPOINT_FILE_NAME);
+    if !checkpoint_path.exists() {
+        return Ok(());
+    }
+
+    let high_watermark = read_high_watermark(&checkpoint_path).await?;
+    if high_watermark > end_offset {
+        warn!(
+            "lowering high watermark: {} to end offset: {}",
+            high_watermark, end_offset
+        );
+        let mut file = OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(&checkpoint_path)
+            .await?;
+        file.write_all(&end_offset.to_be_bytes()).await?;
+        file.sync_all().await?;
+    }
+    Ok(())
+}
+
+/// read high watermark from checkpoint file of replica
+pub async fn read_high_watermark<P>(path: P) -> Result<Offset, StorageError>
+where
+    P: AsRef<Path>,
+{
+    let bytes = read(path).await?;
+    if bytes.len() != size_of::<Offset>() {
+        return Err(IoError::new(
+            ErrorKind::InvalidData,
+            format!(
+                "there should be exact {} bytes but {} bytes available",
+                size_of::<Offset>(),
+                bytes.len()
+            ),
+        )
+        .into());
+    }
+    let mut offset = [0u8; size_of::<Offset>()];
+    offset.copy_from_slice(&bytes);
+    Ok(Offset::from_be_bytes(offset))
+}
+
+/// truncate log after last good batch and rebuild its indexes
+///
+/// Only last segment of replica is repaired unless `force` is set.
+/// High watermark checkpoint is lowered to new end of last segment.
+/// `option` is used for index settings, its base dir is ignored
+pub async fn repair_segment<P>(
+    log_path: P,
+    force: bool,
+    option: &ConfigOption,
+) -> Result<LogRecovery, StorageError>
+where
+    P: AsRef<Path>,
+{
+    let log_path = log_path.as_ref();
+    let last_segment = check_last_segment(log_path, force)?;
+    let recovery = recover(log_path).await?;
+    rebuild_indexes(log_path, option).await?;
+    if last_segment {
+        clamp_high_watermark(log_path, recovery.end_offset).await?;
+    }
+    Ok(recovery)
+}
+
+/// remove batches of log from batch containing offset and rebuild its indexes
+///
+/// Only last segment of replica is truncated unless `force` is set.
+/// High watermark checkpoint is lowered to new end of last segment.
+/// `option` is used for index settings, its base dir is ignored
+pub async fn truncate_segment<P>(
+    log_path: P,
+    offset: Offset,
+    force: bool,
+    option: &ConfigOption,
+) -> Result<LogRecovery, StorageError>
+where
+    P: AsRef<Path>,
+{
+    let log_path = log_path.as_ref();
+    let last_segment = check_last_segment(log_path, force)?;
+    let base_offset = log_path_get_offset(log_path)?;
+    let file = file_util::open(log_path).await?;
+    let log_len = file.metadata().await?.len();
+
+    let mut end_offset = base_offset;
+    let mut valid_len: u64 = 0;
+    let mut batch_stream = BatchHeaderStream::new(file);
+    while let Some(batch_pos) = batch_stream.next().await {
+        if batch_pos.get_last_offset() >= offset {
+            break;
+        }
+        end_offset = batch_pos.get_last_offset() + 1;
+        valid_len = batch_pos.get_pos() as u64 + batch_pos.total_len() as u64;
+    }
+    let discarded_bytes = log_len.saturating_sub(valid_len);
+
+    if discarded_bytes > 0 {
+        warn!(
+            "truncating log: {} to: {} bytes at offset: {}",
+            log_path.display(),
+            valid_len,
+            end_offset
+        );
+        let file = OpenOptions::new().write(true).open(log_path).await?;
+        file.set_len(valid_len).await?;
+        file.sync_all().await?;
+    }
+    rebuild_indexes(log_path, option).await?;
+    if last_segment {
+        clamp_high_watermark(log_path, end_offset).await?;
+    }
+
+    Ok(LogRecovery {
+        end_offset,
+        valid_len,
+        discarded_bytes,
+    })
+}
+
+/// write index and time index of log again, same as segment does when batches are written
+async fn rebuild_indexes(log_path: &Path, option: &ConfigOption) -> Result<(), StorageError> {
+    let base_offset = log_path_get_offset(log_path)?;
+    let dir = log_path.parent().unwrap_or_else(|| Path::new("."));
+    let option = option.clone().base_dir(dir.to_path_buf());
+    debug!("rebuilding indexes of segment: {}", log_path.display());
+
+    let mut index = MutLogIndex::create(base_offset, &option).await?;
+    let mut time_index = TimeIndex::create(base_offset, &option).await?;
+    let mut batch_stream = BatchHeaderStream::new(file_util::open(log_path).await?);
+    while let Some(batch_pos) = batch_stream.next().await {
+        let offset_delta = (batch_pos.get_base_offset() - base_offset) as Size;
+        let batch_end = batch_pos.get_pos() + batch_pos.total_len();
+        index
+            .send((offset_delta, batch_pos.get_pos(), batch_end))
+            .await?;
+        time_index
+            .send((
+                batch_pos.get_batch().get_header().max_time_stamp,
+                offset_delta,
+            ))
+            .await?;
+    }
+    index.shrink().await?;
+    Ok(())
+}
+
+/// entries of index file, empty entries are skipped
+async fn read_index_entries(path: &Path) -> Result<Vec<(Size, Size)>, IoError> {
+    let bytes = match read(path).await {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == ErrorKind::NotFound => vec![],
+        Err(err) => return Err(err),
+    };
+
+    // entries are stored in big endian as in memory mapped index
+    Ok(bytes
+        .chunks_exact(size_of::<(Size, Size)>())
+        .map(|entry| {
+            let mut offset = [0u8; size_of::<Size>()];
+            let mut position = [0u8; size_of::<Size>()];
+            offset.copy_from_slice(&entry[..size_of::<Size>()]);
+            position.copy_from_slice(&entry[size_of::<Size>()..]);
+            (Size::from_ne_bytes(offset), Size::from_ne_bytes(position))
+        })
+        .filter(|entry| entry.position() > 0)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::env::temp_dir;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    use fluvio_future::test_async;
+    use flv_util::fixture::ensure_clean_dir;
+    use dataplane::Offset;
+
+    use crate::fixture::create_batch;
+    use crate::records::MESSAGE_LOG_EXTENSION;
+    use crate::util::generate_file_name;
+    use crate::ConfigOption;
+    use crate::FileReplica;
+    use crate::ReplicaStorage;
+    use crate::StorageError;
+
+    use super::check_replica;
+    use super::check_segment;
+    use super::repair_segment;
+    use super::truncate_segment;
+    use super::SegmentIssue;
+
+    const BASE_OFFSET: Offset = 20;
+
+    fn test_option(dir: &str) -> ConfigOption {
+        let base_dir = temp_dir().join(dir);
+        ensure_clean_dir(&base_dir);
+        ConfigOption {
+            segment_max_bytes: 1000,
+            base_dir,
+            index_max_bytes: 1000,
+            index_max_interval_bytes: 0,
+            ..Default::default()
+        }
+    }
+
+    #[test_async]
+    async fn test_check_and_repair_segment() -> Result<(), StorageError> {
+        let option = test_option("test_inspect");
+        let mut replica = FileReplica::create("test", 0, BASE_OFFSET, &option)
+            .await
+            .expect("create rep");
+        for _ in 0..3 {
+            replica.send(create_batch()).await?;
+        }
+        replica.update_high_watermark_to_end().await?;
+        drop(replica);
+
+        let replica_dir = option.base_dir.join("test-0");
+        let log_path = generate_file_name(&replica_dir, BASE_OFFSET, MESSAGE_LOG_EXTENSION);
+        let report = check_replica(&replica_dir).await?;
+        assert!(report.is_valid(), "{:?}", report);
+        assert_eq!(report.high_watermark, Some(BASE_OFFSET + 6));
+        assert_eq!(report.segments.len(), 1);
+        assert_eq!(report.segments[0].batches, 3);
+        assert_eq!(report.segments[0].end_offset, BASE_OFFSET + 6);
+        assert_eq!(report.segments[0].index_entries, 2);
+
+        // partially written batch at end of log
+        let log_len = report.segments[0].log_len;
+        let mut file = OpenOptions::new().append(true).open(&log_path)?;
+        file.write_all(&[0, 0, 0, 0, 0, 0, 0, 26, 0, 0, 0, 100, 1])?;
+        drop(file);
+        let report = check_segment(&log_path).await?;
+        assert_eq!(
+            report.issues,
+            vec![SegmentIssue::TornBatch {
+                position: log_len,
+                len: 13
+            }]
+        );
+        let report = check_replica(&replica_dir).await?;
+        assert!(report.issues.is_empty());
+        assert!(!report.is_valid());
+
+        // segments followed by another segment are only shortened with force
+        let next_log_path =
+            generate_file_name(&replica_dir, BASE_OFFSET + 6, MESSAGE_LOG_EXTENSION);
+        std::fs::File::create(&next_log_path)?;
+        assert!(repair_segment(&log_path, false, &option).await.is_err());
+        assert!(truncate_segment(&log_path, BASE_OFFSET, false, &option)
+            .await
+            .is_err());
+        std::fs::remove_file(&next_log_path)?;
+
+        let recovery = repair_segment(&log_path, false, &option).await?;
+        assert_eq!(recovery.end_offset, BASE_OFFSET + 6);
+        assert_eq!(recovery.discarded_bytes, 13);
+        assert!(check_segment(&log_path).await?.is_valid());
+
+        // last batch is removed with offset in middle of it
+        let recovery = truncate_segment(&log_path, BASE_OFFSET + 5, false, &option).await?;
+        assert_eq!(recovery.end_offset, BASE_OFFSET + 4);
+        let report = check_segment(&log_path).await?;
+        assert!(report.is_valid(), "{:?}", report);
+        assert_eq!(report.batches, 2);
+        assert_eq!(report.index_entries, 1);
+
+        // high watermark is lowered to end of truncated log
+        let report = check_replica(&replica_dir).await?;
+        assert!(report.is_valid(), "{:?}", report);
+        assert_eq!(report.high_watermark, Some(BASE_OFFSET + 4));
+
+        // replica can be opened after repair
+        let replica = FileReplica::create("test", 0, BASE_OFFSET, &option)
+            .await
+            .expect("open rep");
+        assert_eq!(replica.get_leo(), BASE_OFFSET + 4);
+
+        Ok(())
+    }
+}
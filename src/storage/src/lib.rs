@@ -4,19 +4,25 @@ mod fixture;
 mod batch;
 mod batch_header;
 mod checkpoint;
+mod compaction;
 mod error;
 mod records;
 mod index;
+mod inspect;
 mod mut_records;
 mod mut_index;
+mod time_index;
 mod range_map;
 mod replica;
 mod segment;
+mod snapshot;
 mod util;
 mod validator;
 mod config;
 
 pub use crate::config::ConfigOption;
+pub use crate::config::FlushPolicy;
+pub use crate::config::CleanupPolicy;
 pub use crate::batch::DefaultFileBatchStream;
 pub use crate::batch_header::BatchHeaderPos;
 pub use crate::batch_header::BatchHeaderStream;
@@ -25,11 +31,26 @@ pub use crate::records::FileRecordsSlice;
 pub use crate::index::LogIndex;
 pub use crate::index::OffsetPosition;
 pub use crate::replica::FileReplica;
+pub use crate::compaction::Compaction;
+pub use crate::compaction::CompactedSegments;
+pub use crate::snapshot::LogSnapshot;
+pub use crate::validator::CorruptBatch;
+pub use crate::validator::LogRecovery;
+pub use crate::inspect::SegmentIssue;
+pub use crate::inspect::SegmentReport;
+pub use crate::inspect::ReplicaIssue;
+pub use crate::inspect::ReplicaReport;
+pub use crate::inspect::check_segment;
+pub use crate::inspect::check_replica;
+pub use crate::inspect::read_high_watermark;
+pub use crate::inspect::repair_segment;
+pub use crate::inspect::truncate_segment;
+pub use crate::util::log_path_get_offset;
 pub(crate) use crate::segment::SegmentSlice;
 
 use dataplane::{ErrorCode, Offset};
 use dataplane::fetch::FilePartitionResponse;
-use fluvio_future::file_slice::AsyncFileSlice;
+use dataplane::record::FileRecordSet;
 
 pub trait Captures<'a> {}
 impl<'a, T: ?Sized> Captures<'a> for T {}
@@ -42,7 +63,7 @@ pub trait SlicePartitionResponse {
 
     fn set_log_start_offset(&mut self, offset: i64);
 
-    fn set_slice(&mut self, slice: AsyncFileSlice);
+    fn set_slice(&mut self, slice: FileRecordSet);
 
     fn set_error_code(&mut self, error: ErrorCode);
 }
@@ -60,8 +81,8 @@ impl SlicePartitionResponse for FilePartitionResponse {
         self.log_start_offset = offset;
     }
 
-    fn set_slice(&mut self, slice: AsyncFileSlice) {
-        self.records = slice.into();
+    fn set_slice(&mut self, slice: FileRecordSet) {
+        self.records = slice;
     }
 
     fn set_error_code(&mut self, error: ErrorCode) {
@@ -157,6 +157,29 @@ impl MutLogIndex {
         ))
     }
 
+    /// remove entries of batches at or after log position, after log is truncated
+    pub async fn truncate(&mut self, log_len: Size) -> Result<(), IoError> {
+        let pos = self.pos as usize;
+        let keep = self[..pos]
+            .iter()
+            .position(|entry| entry.position() >= log_len)
+            .unwrap_or(pos);
+        if keep == pos {
+            return Ok(());
+        }
+
+        debug!(
+            "truncating index to: {} entries out of: {}, log len: {}",
+            keep, pos, log_len
+        );
+        for entry in self[keep..pos].iter_mut() {
+            *entry = (0, 0);
+        }
+        self.pos = keep as Size;
+        self.bytes_delta = 0;
+        self.mmap.flush_ft().await
+    }
+
     pub async fn send(&mut self, item: (Size, Size, Size)) -> Result<(), IoError> {
         let batch_size = item.2;
 
@@ -332,4 +355,32 @@ mod tests {
         );
         Ok(())
     }
+
+    const TEST_FILE4: &str = "00000000000000000124.index";
+
+    #[test_async]
+    async fn test_mut_index_truncate() -> Result<(), IoError> {
+        let option = default_option(0);
+        let test_file = option.base_dir.join(TEST_FILE4);
+        ensure_clean_file(&test_file);
+
+        let mut index_sink = MutLogIndex::create(124, &option).await?;
+
+        index_sink.send((100, 16, 70)).await?;
+        index_sink.send((500, 200, 70)).await?;
+        index_sink.send((800, 300, 70)).await?;
+
+        index_sink.truncate(200).await?;
+        assert_eq!(index_sink.pos, 1);
+        assert_eq!(
+            index_sink.find_offset(600).map(|p| p.to_be()),
+            Some((100, 16))
+        );
+        drop(index_sink);
+
+        let index_sink = MutLogIndex::open(124, &option).await?;
+        assert_eq!(index_sink.pos, 1);
+
+        Ok(())
+    }
 }
@@ -1,6 +1,8 @@
 use std::io::Error as IoError;
 use std::path::PathBuf;
 use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
 
 use tracing::debug;
 use tracing::trace;
@@ -16,9 +18,8 @@ use dataplane::{Offset, Size};
 use dataplane::core::Encoder;
 
 use crate::util::generate_file_name;
-use crate::validator::validate;
-use crate::validator::LogValidationError;
 use crate::ConfigOption;
+use crate::FlushPolicy;
 use crate::StorageError;
 use crate::records::FileRecords;
 
@@ -30,6 +31,10 @@ pub struct MutFileRecords {
     item_last_offset_delta: Size,
     f_sink: BoundedFileSink,
     path: PathBuf,
+    flush_policy: FlushPolicy,
+    /// records written after last sync
+    unsynced_records: u32,
+    last_sync: Instant,
 }
 
 impl Unpin for MutFileRecords {}
@@ -50,6 +55,9 @@ impl MutFileRecords {
             f_sink,
             item_last_offset_delta: 0,
             path: log_path.to_owned(),
+            flush_policy: option.flush_policy,
+            unsynced_records: 0,
+            last_sync: Instant::now(),
         })
     }
 
@@ -70,6 +78,9 @@ impl MutFileRecords {
             f_sink,
             item_last_offset_delta: 0,
             path: log_path.to_owned(),
+            flush_policy: option.flush_policy,
+            unsynced_records: 0,
+            last_sync: Instant::now(),
         })
     }
 
@@ -77,10 +88,6 @@ impl MutFileRecords {
         self.base_offset
     }
 
-    pub async fn validate(&mut self) -> Result<Offset, LogValidationError> {
-        validate(self.f_sink.get_path()).await
-    }
-
     pub fn get_pos(&self) -> Size {
         self.f_sink.get_current_len() as Size
     }
@@ -97,8 +104,15 @@ impl MutFileRecords {
         trace!("start sending finally {} bytes", buffer.len());
         if self.f_sink.can_be_appended(buffer.len() as u64) {
             self.f_sink.write_all(&buffer).await?;
-            // for now, we flush for every send
-            self.f_sink.flush().await.map_err(|err| err.into())
+            // always flush to OS, so records can be read from file
+            self.f_sink.flush().await?;
+            self.unsynced_records = self
+                .unsynced_records
+                .saturating_add(self.item_last_offset_delta + 1);
+            if self.is_sync_due() {
+                self.sync().await?;
+            }
+            Ok(())
         } else {
             Err(StorageError::NoRoom(item))
         }
@@ -108,6 +122,35 @@ impl MutFileRecords {
     pub async fn flush(&mut self) -> Result<(), IoError> {
         self.f_sink.flush().await
     }
+
+    /// check if written records should be synced according to flush policy
+    fn is_sync_due(&self) -> bool {
+        match self.flush_policy {
+            FlushPolicy::OsManaged => false,
+            FlushPolicy::EveryBatch => true,
+            FlushPolicy::EveryRecords(records) => self.unsynced_records >= records,
+            FlushPolicy::Interval(ms) => {
+                self.last_sync.elapsed() >= Duration::from_millis(ms as u64)
+            }
+        }
+    }
+
+    /// records which are written but not synced to disk
+    #[cfg(test)]
+    pub fn get_unsynced_records(&self) -> u32 {
+        self.unsynced_records
+    }
+
+    /// sync written records to disk
+    pub async fn sync(&mut self) -> Result<(), IoError> {
+        if self.unsynced_records > 0 {
+            trace!("syncing {} records to disk", self.unsynced_records);
+            self.f_sink.inner().sync_data().await?;
+            self.unsynced_records = 0;
+        }
+        self.last_sync = Instant::now();
+        Ok(())
+    }
 }
 
 impl FileRecords for MutFileRecords {
@@ -152,6 +195,7 @@ mod tests {
     use crate::fixture::create_batch;
     use crate::fixture::read_bytes_from_file;
     use crate::ConfigOption;
+    use crate::FlushPolicy;
 
     const TEST_FILE_NAME: &str = "00000000000000000100.log"; // for offset 100
 
@@ -177,8 +221,8 @@ mod tests {
 
         let batch = DefaultBatch::decode_from(&mut Cursor::new(bytes), 0)?;
         assert_eq!(batch.get_header().magic, 2, "check magic");
-        assert_eq!(batch.records.len(), 2);
-        let mut records = batch.records;
+        assert_eq!(batch.records().len(), 2);
+        let mut records = batch.into_records();
         assert_eq!(records.len(), 2);
         let record1 = records.remove(0);
         assert_eq!(record1.value.inner_value(), Some(vec![10, 20]));
@@ -194,4 +238,46 @@ mod tests {
 
         Ok(())
     }
+
+    const TEST_SYNC_FILE_NAME: &str = "00000000000000000200.log"; // for offset 200
+
+    #[test_async]
+    async fn test_flush_policy() -> Result<(), StorageError> {
+        let test_file = temp_dir().join(TEST_SYNC_FILE_NAME);
+        ensure_clean_file(&test_file);
+
+        let options = ConfigOption {
+            base_dir: temp_dir(),
+            segment_max_bytes: 1000,
+            flush_policy: FlushPolicy::EveryRecords(3),
+            ..Default::default()
+        };
+        let mut msg_sink = MutFileRecords::create(200, &options).await?;
+
+        // batch has 2 records
+        msg_sink.send(create_batch()).await?;
+        assert_eq!(msg_sink.get_unsynced_records(), 2);
+        msg_sink.send(create_batch()).await?;
+        assert_eq!(msg_sink.get_unsynced_records(), 0);
+        msg_sink.send(create_batch()).await?;
+        assert_eq!(msg_sink.get_unsynced_records(), 2);
+        msg_sink.sync().await?;
+        assert_eq!(msg_sink.get_unsynced_records(), 0);
+
+        let options = options.flush_policy(FlushPolicy::OsManaged);
+        let mut msg_sink = MutFileRecords::open(200, &options).await?;
+        msg_sink.send(create_batch()).await?;
+        msg_sink.send(create_batch()).await?;
+        assert_eq!(msg_sink.get_unsynced_records(), 4);
+
+        // records are synced by timer of replica owner if no more batches are written
+        let options = options.flush_policy(FlushPolicy::Interval(60_000));
+        let mut msg_sink = MutFileRecords::open(200, &options).await?;
+        msg_sink.send(create_batch()).await?;
+        assert_eq!(msg_sink.get_unsynced_records(), 2);
+        msg_sink.sync().await?;
+        assert_eq!(msg_sink.get_unsynced_records(), 0);
+
+        Ok(())
+    }
 }
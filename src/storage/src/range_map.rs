@@ -79,7 +79,6 @@ impl SegmentList {
         Ok((segments, last_offset))
     }
 
-    #[allow(dead_code)]
     pub fn len(&self) -> usize {
         self.segments.len()
     }
@@ -105,11 +104,36 @@ impl SegmentList {
         self.segments.insert(segment.get_base_offset(), segment);
     }
 
-    #[allow(dead_code)]
     pub fn get_segment(&self, offset: Offset) -> Option<&ReadSegment> {
         self.segments.get(&offset)
     }
 
+    /// segment with lowest base offset
+    pub fn oldest_segment(&self) -> Option<&ReadSegment> {
+        self.segments.values().next()
+    }
+
+    /// iterate segments in order of base offset
+    pub fn segments(&self) -> impl Iterator<Item = &ReadSegment> {
+        self.segments.values()
+    }
+
+    /// remove segment with base offset, min offset is moved to next segment
+    pub fn remove_segment(&mut self, base_offset: Offset) -> Option<ReadSegment> {
+        let segment = self.segments.remove(&base_offset)?;
+        debug!("removed segment base: {}", base_offset);
+        self.min_base_offset = self.segments.keys().next().copied().unwrap_or(-1);
+        Some(segment)
+    }
+
+    /// total size of records in all segments
+    pub fn msg_size(&self) -> u64 {
+        self.segments
+            .values()
+            .map(|segment| segment.get_msg_size())
+            .sum()
+    }
+
     pub fn find_segment(&self, offset: Offset) -> Option<(&Offset, &ReadSegment)> {
         (&self.segments)
             .range((Excluded(offset - self.max_base_offset), Included(offset)))
@@ -154,6 +178,7 @@ mod tests {
             base_dir,
             index_max_bytes: 1000,
             index_max_interval_bytes: 0,
+            ..Default::default()
         }
     }
 
@@ -2,6 +2,8 @@ use std::io::Error as IoError;
 use std::io::ErrorKind;
 use std::path::PathBuf;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
 
 use tracing::debug;
 
@@ -34,7 +36,8 @@ pub(crate) trait FileRecords {
 
 pub struct FileRecordsSlice {
     base_offset: Offset,
-    file: File,
+    /// shared with file slices, file is closed when last of them is dropped
+    file: Arc<File>,
     path: PathBuf,
     len: u64,
 }
@@ -53,7 +56,7 @@ impl FileRecordsSlice {
 
         Ok(FileRecordsSlice {
             base_offset,
-            file,
+            file: Arc::new(file),
             path: log_path,
             len,
         })
@@ -63,6 +66,21 @@ impl FileRecordsSlice {
         self.base_offset
     }
 
+    /// size of log file in bytes
+    pub fn get_len(&self) -> u64 {
+        self.len
+    }
+
+    /// log file shared with readers of its slices
+    pub fn shared_file(&self) -> Arc<File> {
+        self.file.clone()
+    }
+
+    /// last time log file was modified
+    pub async fn get_last_modified(&self) -> Result<SystemTime, IoError> {
+        self.file.metadata().await?.modified()
+    }
+
     #[allow(dead_code)]
     pub async fn validate(&mut self) -> Result<Offset, LogValidationError> {
         validate(&self.path).await
@@ -1,24 +1,43 @@
 use std::io::Error as IoError;
 use std::mem;
+use std::time::Duration;
 
 use tracing::debug;
 use tracing::trace;
 use tracing::error;
+use tracing::warn;
 
 use fluvio_future::fs::create_dir_all;
-use dataplane::{ErrorCode, Offset, Size};
+use fluvio_future::file_slice::AsyncFileSlice;
+use dataplane::{ErrorCode, Offset, Size, Timestamp};
 use dataplane::batch::DefaultBatch;
 use dataplane::record::RecordSet;
 
 use crate::checkpoint::CheckPoint;
+use crate::compaction::COMPACTION_DIR;
+use crate::compaction::Compaction;
+use crate::compaction::CompactedSegments;
+use crate::compaction::recover_compaction;
+use crate::compaction::remove_compaction_dir;
+use crate::compaction::swap_segment;
 use crate::range_map::SegmentList;
+use crate::records::MESSAGE_LOG_EXTENSION;
+use crate::util::generate_file_name;
 use crate::segment::MutableSegment;
+use crate::segment::ReadSegment;
+use crate::snapshot::LogSnapshot;
+use crate::CleanupPolicy;
 use crate::ConfigOption;
+use crate::FlushPolicy;
+use crate::CorruptBatch;
 use crate::SegmentSlice;
 use crate::StorageError;
 use crate::SlicePartitionResponse;
 use crate::ReplicaStorage;
 
+/// file of replica where high watermark is stored
+pub(crate) const HW_CHECKPOINT_FILE_NAME: &str = "replication.chk";
+
 /// Replica is public abstraction for commit log which are distributed.
 /// Internally it is stored as list of segments.  Each segment contains finite sets of record batches.
 ///
@@ -61,8 +80,9 @@ impl FileReplica {
     /// and partition.
     ///
     /// If there is existing directory then it will load existing logs.
-    /// The logs will be validated to ensure it's safe to use it.
-    /// It is possible logs can't be used because they may be corrupted.
+    /// The active segment is recovered, batches which were not completely written
+    /// or are corrupted are truncated from its log, so replica can be used again.
+    /// Compaction interrupted by crash is completed before segments are loaded.
     pub async fn create<S>(
         topic: S,
         partition: Size,
@@ -79,16 +99,25 @@ impl FileReplica {
 
         let mut rep_option = option.clone();
         rep_option.base_dir = replica_dir;
+        recover_compaction(&rep_option.base_dir).await?;
         // create active segment
 
         let (segments, last_offset_res) = SegmentList::from_dir(&rep_option).await?;
 
         let active_segment = if let Some(last_offset) = last_offset_res {
-            trace!("last segment found, validating offsets: {}", last_offset);
-            let mut last_segment = MutableSegment::open_for_write(last_offset, &rep_option).await?;
-            last_segment.validate().await?;
+            trace!("last segment found, recovering offsets: {}", last_offset);
+            let (last_segment, recovery) =
+                MutableSegment::open_for_recovery(last_offset, &rep_option).await?;
+            if recovery.discarded_bytes > 0 {
+                warn!(
+                    "replica: {} recovered active segment: {}, discarded: {} bytes",
+                    rep_option.base_dir.display(),
+                    last_offset,
+                    recovery.discarded_bytes
+                );
+            }
             trace!(
-                "segment validated with last offset: {}",
+                "segment recovered with last offset: {}",
                 last_segment.get_end_offset()
             );
             last_segment
@@ -100,7 +129,7 @@ impl FileReplica {
         let last_base_offset = active_segment.get_base_offset();
 
         let commit_checkpoint: CheckPoint<Offset> =
-            CheckPoint::create(&rep_option, "replication.chk", last_base_offset).await?;
+            CheckPoint::create(&rep_option, HW_CHECKPOINT_FILE_NAME, last_base_offset).await?;
 
         Ok(FileReplica {
             option: rep_option,
@@ -113,6 +142,8 @@ impl FileReplica {
     }
 
     /// update committed offset (high watermark)
+    ///
+    /// with `flush_before_commit`, written records are synced before high watermark is moved
     pub async fn update_high_watermark(&mut self, offset: Offset) -> Result<(), IoError> {
         let old_offset = self.get_hw();
         if old_offset == offset {
@@ -127,10 +158,29 @@ impl FileReplica {
                 old_offset,
                 offset
             );
+            if self.option.flush_before_commit && offset > old_offset {
+                self.active_segment.sync().await?;
+            }
             self.commit_checkpoint.write(offset).await
         }
     }
 
+    /// how often written records must be synced, if replica has interval flush policy
+    ///
+    /// records are synced when batch is written after interval, owner of replica
+    /// must call `sync` when no batches are written
+    pub fn flush_interval(&self) -> Option<Duration> {
+        match self.option.flush_policy {
+            FlushPolicy::Interval(ms) => Some(Duration::from_millis(ms as u64)),
+            _ => None,
+        }
+    }
+
+    /// sync records written to active segment to disk
+    pub async fn sync(&mut self) -> Result<(), IoError> {
+        self.active_segment.sync().await
+    }
+
     /// update high watermark to end
     pub async fn update_high_watermark_to_end(&mut self) -> Result<(), IoError> {
         self.update_high_watermark(self.get_leo()).await
@@ -146,6 +196,158 @@ impl FileReplica {
         }
     }
 
+    /// remove oldest read only segments which are beyond retention limits of the replica
+    ///
+    /// A segment is removed if it has not been written for longer than `retention_seconds`
+    /// or if the replica is bigger than `retention_bytes`. Active segment is never removed.
+    /// Segments of compacted replica are not removed.
+    /// This moves log start offset to base offset of the oldest remaining segment.
+    /// Files of removed segment are deleted, log file is closed when last slice read from it is dropped.
+    /// Return number of segments removed.
+    pub async fn enforce_retention(&mut self) -> Result<usize, StorageError> {
+        let retention_age = self
+            .option
+            .retention_seconds
+            .map(|seconds| Duration::from_secs(seconds as u64));
+        let retention_bytes = self.option.retention_bytes;
+        if self.option.cleanup_policy == CleanupPolicy::Compact
+            || (retention_age.is_none() && retention_bytes.is_none())
+        {
+            return Ok(0);
+        }
+
+        let mut total_size = self.prev_segments.msg_size() + self.active_segment.get_msg_size();
+        let mut removed = 0;
+
+        while let Some(segment) = self.prev_segments.oldest_segment() {
+            let base_offset = segment.get_base_offset();
+            let segment_size = segment.get_msg_size();
+
+            let over_size = match retention_bytes {
+                Some(max_bytes) => total_size > max_bytes,
+                None => false,
+            };
+            let expired = match retention_age {
+                Some(max_age) => {
+                    let modified = segment.get_last_modified().await?;
+                    // if clock went backward, segment is considered recent
+                    match modified.elapsed() {
+                        Ok(age) => age >= max_age,
+                        Err(_) => false,
+                    }
+                }
+                None => false,
+            };
+
+            if !over_size && !expired {
+                break;
+            }
+
+            debug!(
+                "removing segment: {} by retention, size: {}, over size: {}, expired: {}",
+                base_offset, segment_size, over_size, expired
+            );
+            if let Some(segment) = self.prev_segments.remove_segment(base_offset) {
+                segment.remove_files().await?;
+            }
+            total_size -= segment_size;
+            removed += 1;
+        }
+
+        if removed > 0 {
+            debug!(
+                "removed {} segments, log start offset: {}",
+                removed,
+                self.get_log_start_offset()
+            );
+        }
+        Ok(removed)
+    }
+
+    /// compact read only segments of replica with compact cleanup policy
+    ///
+    /// only last record of each key is kept, tombstones are removed after `delete_retention_seconds`.
+    /// Each segment is replaced by its compacted copy, offsets of remaining records are not changed.
+    /// Return number of records removed.
+    pub async fn compact(&mut self) -> Result<usize, StorageError> {
+        match self.compaction() {
+            Some(compaction) => {
+                let compacted = compaction.compact().await?;
+                self.swap_compacted(compacted).await
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// segments to be compacted, None if replica doesn't have compact cleanup policy
+    ///
+    /// only records before high watermark are compacted. compacted copies are written
+    /// by `Compaction::compact` without access to replica, then they replace segments
+    /// by `swap_compacted`
+    pub fn compaction(&self) -> Option<Compaction> {
+        if self.option.cleanup_policy != CleanupPolicy::Compact || self.prev_segments.len() == 0 {
+            return None;
+        }
+        let base_offsets = self
+            .prev_segments
+            .segments()
+            .map(|segment| segment.get_base_offset())
+            .collect();
+        Some(Compaction::new(
+            self.option.clone(),
+            base_offsets,
+            self.active_segment.get_base_offset(),
+            self.active_segment.get_msg_size() as Size,
+            self.get_hw(),
+        ))
+    }
+
+    /// replace read only segments with their compacted copies
+    /// return number of records removed
+    pub async fn swap_compacted(
+        &mut self,
+        compacted: CompactedSegments,
+    ) -> Result<usize, StorageError> {
+        let compaction_dir = self.option.base_dir.join(COMPACTION_DIR);
+        let mut total_removed = 0;
+        for (base_offset, removed) in compacted.into_segments() {
+            // segment may be removed by retention after compaction started
+            if self.prev_segments.get_segment(base_offset).is_none() {
+                continue;
+            }
+
+            debug!(
+                "replacing segment: {} with compacted one, removed {} records",
+                base_offset, removed
+            );
+            // log file of segment is kept open by slices which may be still read
+            self.prev_segments.remove_segment(base_offset);
+            swap_segment(base_offset, &compaction_dir, &self.option.base_dir).await?;
+            let segment = ReadSegment::open_for_read(base_offset, &self.option).await?;
+            self.prev_segments.add_segment(segment);
+            total_removed += removed;
+        }
+
+        remove_compaction_dir(&self.option.base_dir).await?;
+        Ok(total_removed)
+    }
+
+    /// find offset of first record with timestamp at or after given timestamp
+    /// return None if all records are older than timestamp
+    pub async fn find_offset_by_timestamp(
+        &self,
+        timestamp: Timestamp,
+    ) -> Result<Option<Offset>, StorageError> {
+        for segment in self.prev_segments.segments() {
+            if let Some(offset) = segment.find_offset_by_timestamp(timestamp).await? {
+                return Ok(Some(offset));
+            }
+        }
+        self.active_segment
+            .find_offset_by_timestamp(timestamp)
+            .await
+    }
+
     /// find the segment that contains offsets
     /// segment could be active segment which can be written
     /// or read only segment.
@@ -225,7 +427,7 @@ impl FileReplica {
 
         match self.find_segment(start_offset) {
             Some(segment) => {
-                let slice = match segment {
+                let slice = match &segment {
                     SegmentSlice::MutableSegment(segment) => {
                         // optimization
                         if start_offset == self.get_leo() {
@@ -253,8 +455,6 @@ impl FileReplica {
                 match slice {
                     Ok(slice) => match slice {
                         Some(slice) => {
-                            use fluvio_future::file_slice::AsyncFileSlice;
-
                             let limited_slice = if slice.len() > max_len as u64 {
                                 debug!(
                                     "retrieved record slice fd: {}, position: {}, max {} out of len {}",
@@ -275,8 +475,18 @@ impl FileReplica {
                                 slice
                             };
 
+                            if self.option.verify_crc_on_read {
+                                let error_code = self
+                                    .verify_slice(&segment, &limited_slice, start_offset)
+                                    .await;
+                                if error_code.is_error() {
+                                    response.set_error_code(error_code);
+                                    return;
+                                }
+                            }
+
                             // limit slice
-                            response.set_slice(limited_slice);
+                            response.set_slice(segment.to_record_set(limited_slice));
                         }
                         None => {
                             debug!("records not found for: {}", start_offset);
@@ -296,6 +506,73 @@ impl FileReplica {
         }
     }
 
+    /// check crc of batches in slice before it is sent
+    async fn verify_slice(
+        &self,
+        segment: &SegmentSlice<'_>,
+        slice: &AsyncFileSlice,
+        start_offset: Offset,
+    ) -> ErrorCode {
+        match segment.find_corrupt_batches(slice, start_offset).await {
+            Ok(corrupted) => match corrupted.first() {
+                Some(batch) => {
+                    error!(
+                        "replica: {} has corrupted records at offsets: {}..={}",
+                        self.option.base_dir.display(),
+                        batch.base_offset,
+                        batch.last_offset
+                    );
+                    ErrorCode::CorruptMessage
+                }
+                None => ErrorCode::None,
+            },
+            Err(err) => {
+                error!("error verifying records at: {}, {:#?}", start_offset, err);
+                ErrorCode::UnknownServerError
+            }
+        }
+    }
+
+    /// read all committed batches of replica into memory
+    ///
+    /// this is meant for small replicas, such as compacted internal topics
+    pub async fn read_committed_batches(&self) -> Result<Vec<DefaultBatch>, StorageError> {
+        self.log_snapshot().read_committed_batches().await
+    }
+
+    /// segment logs up to their current size, to be read without access to replica
+    pub fn log_snapshot(&self) -> LogSnapshot {
+        let mut snapshot = LogSnapshot::new(self.get_hw());
+        let segments = self
+            .prev_segments
+            .segments()
+            .map(|segment| {
+                (
+                    segment.get_base_offset(),
+                    segment.get_end_offset(),
+                    segment.get_msg_size(),
+                )
+            })
+            .chain(std::iter::once((
+                self.active_segment.get_base_offset(),
+                self.active_segment.get_end_offset(),
+                self.active_segment.get_msg_size(),
+            )));
+        for (base_offset, end_offset, len) in segments {
+            let path =
+                generate_file_name(&self.option.base_dir, base_offset, MESSAGE_LOG_EXTENSION);
+            snapshot.add_log(base_offset, end_offset, path, len as Size);
+        }
+        snapshot
+    }
+
+    /// scan all segments for batches whose crc doesn't match
+    ///
+    /// this finds corruption of records on disk, including records which are not read
+    pub async fn scrub(&self) -> Result<Vec<CorruptBatch>, StorageError> {
+        self.log_snapshot().scrub().await
+    }
+
     pub async fn send(&mut self, item: DefaultBatch) -> Result<(), StorageError> {
         trace!("start_send");
         if let Err(err) = self.active_segment.send(item).await {
@@ -330,26 +607,32 @@ mod tests {
     use std::fs;
     use std::fs::metadata;
     use std::io::Cursor;
+    use std::sync::Arc;
 
     use fluvio_future::test_async;
     use dataplane::batch::DefaultBatch;
-    use dataplane::{Offset, ErrorCode};
+    use dataplane::{Offset, ErrorCode, Timestamp};
     use dataplane::core::{Decoder, Encoder};
     use dataplane::fetch::FilePartitionResponse;
-    use dataplane::record::RecordSet;
+    use dataplane::record::{DefaultAsyncBuffer, DefaultRecord, RecordSet};
     use flv_util::fixture::ensure_clean_dir;
 
     use super::FileReplica;
+    use super::COMPACTION_DIR;
     use crate::fixture::create_batch;
     use crate::fixture::read_bytes_from_file;
+    use crate::CleanupPolicy;
     use crate::ConfigOption;
     use crate::StorageError;
     use crate::ReplicaStorage;
+    use crate::time_index::EXTENSION as TIME_INDEX_EXTENSION;
 
     const TEST_SEG_NAME: &str = "00000000000000000020.log";
     const TEST_SE2_NAME: &str = "00000000000000000022.log";
     const TEST_SEG_IDX: &str = "00000000000000000020.index";
     const TEST_SEG2_IDX: &str = "00000000000000000022.index";
+    const TEST_SEG_TIME_IDX: &str = "00000000000000000020.timeindex";
+    const TEST_SEG2_TIME_IDX: &str = "00000000000000000022.timeindex";
     const START_OFFSET: Offset = 20;
 
     fn base_option(dir: &str) -> ConfigOption {
@@ -360,6 +643,7 @@ mod tests {
             base_dir,
             index_max_interval_bytes: 1000,
             index_max_bytes: 1000,
+            ..Default::default()
         }
     }
 
@@ -371,6 +655,7 @@ mod tests {
             base_dir,
             index_max_bytes: 1000,
             index_max_interval_bytes: 0,
+            ..Default::default()
         }
     }
 
@@ -403,7 +688,7 @@ mod tests {
         assert_eq!(batch.get_header().magic, 2, "check magic");
         assert_eq!(batch.get_base_offset(), START_OFFSET);
         assert_eq!(batch.get_header().last_offset_delta, 1);
-        assert_eq!(batch.records.len(), 2);
+        assert_eq!(batch.records().len(), 2);
 
         // there should not be any segment for offset 0 since base offset is 20
         let segment = replica.find_segment(0);
@@ -417,6 +702,84 @@ mod tests {
         Ok(())
     }
 
+    const TEST_RECOVERY_DIR: &str = "test_recovery";
+
+    #[test_async]
+    async fn test_replica_recover_torn_batch() -> Result<(), StorageError> {
+        let option = base_option(TEST_RECOVERY_DIR);
+        let mut replica = FileReplica::create("test", 0, START_OFFSET, &option)
+            .await
+            .expect("test replica");
+        replica.send(create_batch()).await.expect("send");
+        replica.send(create_batch()).await.expect("send");
+        drop(replica);
+
+        // last batch is partially written
+        let test_file = option.base_dir.join("test-0").join(TEST_SEG_NAME);
+        let len = metadata(&test_file)?.len();
+        let file = fs::OpenOptions::new().write(true).open(&test_file)?;
+        file.set_len(len - 10)?;
+        drop(file);
+
+        let mut replica = FileReplica::create("test", 0, START_OFFSET, &option)
+            .await
+            .expect("recovered replica");
+        assert_eq!(replica.get_leo(), START_OFFSET + 2);
+
+        replica.send(create_batch()).await.expect("send");
+        assert_eq!(replica.get_leo(), START_OFFSET + 4);
+        drop(replica);
+
+        let replica = FileReplica::create("test", 0, START_OFFSET, &option)
+            .await
+            .expect("reopened replica");
+        assert_eq!(replica.get_leo(), START_OFFSET + 4);
+
+        Ok(())
+    }
+
+    const TEST_CORRUPT_DIR: &str = "test_corrupt";
+
+    #[test_async]
+    async fn test_replica_corrupt_batch() -> Result<(), StorageError> {
+        use std::io::{Seek, SeekFrom, Write};
+
+        let option = base_option(TEST_CORRUPT_DIR).verify_crc_on_read(true);
+        let mut replica = FileReplica::create("test", 0, START_OFFSET, &option)
+            .await
+            .expect("test replica");
+        replica.send(create_batch()).await.expect("send");
+        replica.send(create_batch()).await.expect("send");
+        assert!(replica.scrub().await?.is_empty());
+
+        // corrupt last byte of second batch
+        let test_file = option.base_dir.join("test-0").join(TEST_SEG_NAME);
+        let len = metadata(&test_file)?.len();
+        let mut file = fs::OpenOptions::new().write(true).open(&test_file)?;
+        file.seek(SeekFrom::Start(len - 1))?;
+        file.write_all(&[0xff])?;
+        drop(file);
+
+        let corrupted = replica.scrub().await?;
+        assert_eq!(corrupted.len(), 1);
+        assert_eq!(corrupted[0].base_offset, START_OFFSET + 2);
+        assert_eq!(corrupted[0].last_offset, START_OFFSET + 3);
+
+        let mut response = FilePartitionResponse::default();
+        replica
+            .read_records(
+                START_OFFSET + 2,
+                None,
+                FileReplica::PREFER_MAX_LEN,
+                &mut response,
+            )
+            .await;
+        assert_eq!(response.error_code, ErrorCode::CorruptMessage);
+        assert_eq!(response.records.len(), 0);
+
+        Ok(())
+    }
+
     const TEST_UNCOMMIT_DIR: &str = "test_uncommitted";
 
     #[test_async]
@@ -531,7 +894,7 @@ mod tests {
 
         assert_eq!(replica.get_log_start_offset(), START_OFFSET);
         let replica_dir = &option.base_dir.join("test-1");
-        let dir_contents = fs::read_dir(&replica_dir)?;
+        let dir_contents = fs::read_dir(replica_dir)?.filter(|entry| !is_time_index(entry));
         assert_eq!(dir_contents.count(), 5, "should be 5 files");
 
         let seg2_file = replica_dir.join(TEST_SE2_NAME);
@@ -539,7 +902,7 @@ mod tests {
 
         let batch = DefaultBatch::decode_from(&mut Cursor::new(bytes), 0)?;
         assert_eq!(batch.get_header().magic, 2, "check magic");
-        assert_eq!(batch.records.len(), 2);
+        assert_eq!(batch.records().len(), 2);
         assert_eq!(batch.get_base_offset(), 22);
 
         let metadata_res = metadata(replica_dir.join(TEST_SEG2_IDX));
@@ -553,6 +916,339 @@ mod tests {
         Ok(())
     }
 
+    fn is_time_index(entry: &std::io::Result<fs::DirEntry>) -> bool {
+        match entry {
+            Ok(entry) => entry.path().extension() == Some(TIME_INDEX_EXTENSION.as_ref()),
+            Err(_) => false,
+        }
+    }
+
+    #[test_async]
+    async fn test_rep_log_roll_over_time_index() -> Result<(), StorageError> {
+        let option = rollover_option("test_replica_time_index");
+
+        let mut replica = FileReplica::create("test", 1, START_OFFSET, &option)
+            .await
+            .expect("create rep");
+        replica.send(create_batch()).await?;
+        replica.send(create_batch()).await?;
+
+        // each segment has its time index
+        let replica_dir = &option.base_dir.join("test-1");
+        let time_indexes = fs::read_dir(replica_dir)?.filter(is_time_index);
+        assert_eq!(time_indexes.count(), 2, "should be 2 time index files");
+        assert!(metadata(replica_dir.join(TEST_SEG_TIME_IDX)).is_ok());
+        assert!(metadata(replica_dir.join(TEST_SEG2_TIME_IDX)).is_ok());
+
+        Ok(())
+    }
+
+    /// create replica with 2 read only segments (20, 22) and active segment (24)
+    async fn create_rolled_replica(option: &ConfigOption) -> FileReplica {
+        let mut replica = FileReplica::create("test", 1, START_OFFSET, option)
+            .await
+            .expect("create rep");
+        for _ in 0..3 {
+            replica.send(create_batch()).await.expect("send");
+        }
+        assert_eq!(replica.prev_segments.len(), 2);
+        replica
+    }
+
+    #[test_async]
+    async fn test_replica_retention_bytes() -> Result<(), StorageError> {
+        let option = rollover_option("test_retention_bytes");
+
+        let mut replica =
+            create_rolled_replica(&option.clone().retention_bytes(Some(100_000))).await;
+        assert_eq!(replica.enforce_retention().await?, 0);
+        assert_eq!(replica.get_log_start_offset(), START_OFFSET);
+        drop(replica);
+
+        // only fits active segment
+        let mut replica =
+            FileReplica::create("test", 1, START_OFFSET, &option.retention_bytes(Some(100)))
+                .await
+                .expect("open rep");
+        let log_file = Arc::downgrade(
+            &replica
+                .prev_segments
+                .get_segment(20)
+                .expect("segment")
+                .shared_log_file(),
+        );
+        let mut read_before = FilePartitionResponse::default();
+        replica.read_records(20, None, 1000, &mut read_before).await;
+        assert_eq!(read_before.error_code, ErrorCode::None);
+
+        assert_eq!(replica.enforce_retention().await?, 2);
+        assert_eq!(replica.get_log_start_offset(), 24);
+
+        // records read before could still be sent, so log file is closed only after they are dropped
+        assert!(log_file.upgrade().is_some());
+        drop(read_before);
+        assert!(log_file.upgrade().is_none());
+
+        let mut response = FilePartitionResponse::default();
+        replica.read_records(20, None, 1000, &mut response).await;
+        assert_eq!(response.error_code, ErrorCode::OffsetOutOfRange);
+        assert_eq!(response.log_start_offset, 24);
+
+        let replica_dir = replica.option.base_dir.clone();
+        assert!(metadata(replica_dir.join(TEST_SEG_NAME)).is_err());
+        assert!(metadata(replica_dir.join(TEST_SEG_IDX)).is_err());
+        assert_eq!(fs::read_dir(&replica_dir)?.count(), 4, "should be 4 files");
+
+        Ok(())
+    }
+
+    #[test_async]
+    async fn test_replica_retention_age() -> Result<(), StorageError> {
+        let option = rollover_option("test_retention_age");
+
+        let mut replica =
+            create_rolled_replica(&option.clone().retention_seconds(Some(3600))).await;
+        assert_eq!(replica.enforce_retention().await?, 0);
+        drop(replica);
+
+        let mut replica =
+            FileReplica::create("test", 1, START_OFFSET, &option.retention_seconds(Some(0)))
+                .await
+                .expect("open rep");
+        assert_eq!(replica.enforce_retention().await?, 2);
+        assert_eq!(replica.get_log_start_offset(), 24);
+        assert_eq!(replica.get_leo(), 26);
+
+        Ok(())
+    }
+
+    /// batch of 2 records, second record is 10ms after first
+    fn create_timestamp_batch(timestamp: Timestamp) -> DefaultBatch {
+        let mut batch = DefaultBatch::default();
+        for delta in &[0, 10] {
+            let record = DefaultRecord {
+                value: vec![10, 20].into(),
+                ..Default::default()
+            };
+            batch.add_record_with_timestamp(record, timestamp + delta);
+        }
+        batch
+    }
+
+    #[test_async]
+    async fn test_replica_find_offset_by_timestamp() -> Result<(), StorageError> {
+        let option = rollover_option("test_find_timestamp");
+
+        let mut replica = FileReplica::create("test", 1, START_OFFSET, &option)
+            .await
+            .expect("create rep");
+        // segments 20, 22 are rolled over, 24 is active
+        for timestamp in &[1000, 2000, 3000] {
+            replica.send(create_timestamp_batch(*timestamp)).await?;
+        }
+        assert_eq!(replica.prev_segments.len(), 2);
+
+        assert_eq!(replica.find_offset_by_timestamp(0).await?, Some(20));
+        assert_eq!(replica.find_offset_by_timestamp(1005).await?, Some(21));
+        assert_eq!(replica.find_offset_by_timestamp(1500).await?, Some(22));
+        assert_eq!(replica.find_offset_by_timestamp(3010).await?, Some(25));
+        assert_eq!(replica.find_offset_by_timestamp(3011).await?, None);
+        drop(replica);
+
+        // time index is loaded from files
+        let replica = FileReplica::create("test", 1, START_OFFSET, &option)
+            .await
+            .expect("open rep");
+        assert_eq!(replica.find_offset_by_timestamp(2001).await?, Some(23));
+
+        Ok(())
+    }
+
+    /// batch with records of given key and value
+    fn create_keyed_batch(records: &[(&str, Option<&str>)], timestamp: Timestamp) -> DefaultBatch {
+        let mut batch = DefaultBatch::default();
+        for (key, value) in records {
+            let record = DefaultRecord {
+                key: key.to_string().into(),
+                value: DefaultAsyncBuffer::new(value.map(|value| value.as_bytes().to_vec())),
+                ..Default::default()
+            };
+            batch.add_record_with_timestamp(record, timestamp);
+        }
+        batch
+    }
+
+    /// key and value of records in segment log
+    fn read_log_records(path: &std::path::Path) -> Vec<(Offset, Vec<u8>, Option<Vec<u8>>)> {
+        let bytes = read_bytes_from_file(path).expect("read log");
+        let len = bytes.len() as u64;
+        let mut src = Cursor::new(bytes);
+        let mut records = vec![];
+        while src.position() < len {
+            let batch = DefaultBatch::decode_from(&mut src, 0).expect("decode");
+            for record in batch.records() {
+                records.push((
+                    batch.get_base_offset() + record.get_offset_delta(),
+                    record.key.inner_value_ref().clone().unwrap_or_default(),
+                    record.value.inner_value_ref().clone(),
+                ));
+            }
+        }
+        records
+    }
+
+    #[test_async]
+    async fn test_replica_compaction() -> Result<(), StorageError> {
+        let option = rollover_option("test_compaction");
+        let now = dataplane::current_timestamp();
+
+        let mut replica = FileReplica::create("test", 1, START_OFFSET, &option)
+            .await
+            .expect("create rep");
+        replica
+            .send(create_keyed_batch(
+                &[("a", Some("1")), ("b", Some("1"))],
+                now,
+            ))
+            .await?;
+        replica
+            .send(create_keyed_batch(&[("a", Some("2")), ("c", None)], 1000))
+            .await?;
+        replica
+            .send(create_keyed_batch(&[("b", Some("2")), ("d", None)], now))
+            .await?;
+        replica.update_high_watermark_to_end().await?;
+        assert_eq!(replica.prev_segments.len(), 2);
+
+        // compaction is disabled by delete cleanup policy
+        assert_eq!(replica.compact().await?, 0);
+        drop(replica);
+
+        let option = option.cleanup_policy(CleanupPolicy::Compact);
+        let mut replica = FileReplica::create("test", 1, START_OFFSET, &option)
+            .await
+            .expect("open rep");
+        assert_eq!(replica.compact().await?, 3);
+        assert_eq!(replica.prev_segments.len(), 2);
+        assert_eq!(replica.compact().await?, 0);
+        assert_eq!(replica.get_log_start_offset(), START_OFFSET);
+        assert_eq!(replica.get_leo(), START_OFFSET + 6);
+
+        let replica_dir = replica.option.base_dir.clone();
+        assert!(read_log_records(&replica_dir.join(TEST_SEG_NAME)).is_empty());
+        assert_eq!(
+            read_log_records(&replica_dir.join(TEST_SE2_NAME)),
+            vec![(22, b"a".to_vec(), Some(b"2".to_vec()))]
+        );
+        assert!(metadata(replica_dir.join(COMPACTION_DIR)).is_err());
+
+        // removed offsets are skipped by readers
+        let mut response = FilePartitionResponse::default();
+        replica.read_records(20, None, 1000, &mut response).await;
+        assert_eq!(response.error_code, ErrorCode::None);
+        assert!(response.records.len() > 0);
+        drop(replica);
+
+        // compacted segments are loaded when replica is opened again
+        let replica = FileReplica::create("test", 1, START_OFFSET, &option)
+            .await
+            .expect("open rep");
+        assert_eq!(replica.prev_segments.len(), 2);
+        assert_eq!(replica.get_leo(), START_OFFSET + 6);
+        // records 20, 21 are removed, 22 is older
+        assert_eq!(replica.find_offset_by_timestamp(now).await?, Some(24));
+
+        Ok(())
+    }
+
+    /// replica can be written while compacted segments are written
+    #[test_async]
+    async fn test_replica_compaction_while_writing() -> Result<(), StorageError> {
+        let option =
+            rollover_option("test_compaction_writing").cleanup_policy(CleanupPolicy::Compact);
+        let now = dataplane::current_timestamp();
+
+        let mut replica = FileReplica::create("test", 1, START_OFFSET, &option)
+            .await
+            .expect("create rep");
+        replica
+            .send(create_keyed_batch(
+                &[("a", Some("1")), ("b", Some("1"))],
+                now,
+            ))
+            .await?;
+        replica
+            .send(create_keyed_batch(
+                &[("a", Some("2")), ("b", Some("2"))],
+                now,
+            ))
+            .await?;
+        replica.update_high_watermark_to_end().await?;
+
+        let compaction = replica.compaction().expect("compaction");
+        let compacted = compaction.compact().await?;
+
+        // records written after compaction started are not considered
+        replica
+            .send(create_keyed_batch(&[("b", Some("3"))], now))
+            .await?;
+
+        assert_eq!(replica.swap_compacted(compacted).await?, 2);
+        assert_eq!(replica.get_leo(), START_OFFSET + 5);
+        let replica_dir = replica.option.base_dir.clone();
+        assert!(read_log_records(&replica_dir.join(TEST_SEG_NAME)).is_empty());
+        assert!(metadata(replica_dir.join(COMPACTION_DIR)).is_err());
+
+        replica.update_high_watermark_to_end().await?;
+        assert_eq!(replica.compact().await?, 1);
+
+        Ok(())
+    }
+
+    /// records after high watermark may be truncated, so they don't replace committed records
+    #[test_async]
+    async fn test_replica_compaction_uncommitted() -> Result<(), StorageError> {
+        let option =
+            rollover_option("test_compaction_uncommitted").cleanup_policy(CleanupPolicy::Compact);
+        let now = dataplane::current_timestamp();
+
+        let mut replica = FileReplica::create("test", 1, START_OFFSET, &option)
+            .await
+            .expect("create rep");
+        replica
+            .send(create_keyed_batch(
+                &[("a", Some("1")), ("b", Some("1"))],
+                now,
+            ))
+            .await?;
+        replica
+            .send(create_keyed_batch(
+                &[("a", Some("2")), ("b", Some("2"))],
+                now,
+            ))
+            .await?;
+        replica
+            .send(create_keyed_batch(&[("a", Some("3"))], now))
+            .await?;
+        replica.update_high_watermark(START_OFFSET + 2).await?;
+        assert_eq!(replica.prev_segments.len(), 2);
+
+        // newest records of keys are not committed
+        assert_eq!(replica.compact().await?, 0);
+        let replica_dir = replica.option.base_dir.clone();
+        assert_eq!(read_log_records(&replica_dir.join(TEST_SEG_NAME)).len(), 2);
+
+        replica.update_high_watermark_to_end().await?;
+        assert_eq!(replica.compact().await?, 3);
+        assert!(read_log_records(&replica_dir.join(TEST_SEG_NAME)).is_empty());
+        assert_eq!(
+            read_log_records(&replica_dir.join(TEST_SE2_NAME)),
+            vec![(23, b"b".to_vec(), Some(b"2".to_vec()))]
+        );
+
+        Ok(())
+    }
+
     const TEST_COMMIT_DIR: &str = "test_commit";
 
     #[test_async]
@@ -580,6 +1276,31 @@ mod tests {
         Ok(())
     }
 
+    const TEST_READ_BATCHES_DIR: &str = "test_read_batches";
+
+    #[test_async]
+    async fn test_replica_read_committed_batches() -> Result<(), StorageError> {
+        let option = rollover_option(TEST_READ_BATCHES_DIR);
+        let mut replica = FileReplica::create("test", 0, 0, &option)
+            .await
+            .expect("test replica");
+
+        for _ in 0..3 {
+            replica.send(create_batch()).await.expect("send");
+        }
+        replica.update_high_watermark(4).await.expect("hw");
+        assert!(replica.prev_segments.len() > 0);
+
+        // last batch is not committed
+        let batches = replica.read_committed_batches().await?;
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].get_base_offset(), 0);
+        assert_eq!(batches[1].get_base_offset(), 2);
+        assert_eq!(batches[1].records().len(), 2);
+
+        Ok(())
+    }
+
     const TEST_COMMIT_FETCH_DIR: &str = "test_commit_fetch";
 
     /// test fetch only committed records
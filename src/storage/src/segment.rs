@@ -1,29 +1,43 @@
 use std::fmt;
 use std::io::Error as IoError;
 use std::ops::Deref;
+use std::sync::Arc;
+use std::time::SystemTime;
 
 use tracing::debug;
 use tracing::trace;
 
 use dataplane::batch::DefaultBatch;
-use dataplane::{Offset, Size};
+use dataplane::record::FileRecordSet;
+use dataplane::{Offset, Size, Timestamp};
 use fluvio_future::file_slice::AsyncFileSlice;
 use fluvio_future::fs::util as file_util;
+use fluvio_future::fs::remove_file;
+use fluvio_future::fs::File;
 
 use crate::BatchHeaderStream;
 use crate::mut_index::MutLogIndex;
 use crate::index::LogIndex;
 use crate::index::Index;
+use crate::index::EXTENSION as INDEX_EXTENSION;
+use crate::time_index::TimeIndex;
 use crate::records::FileRecords;
 use crate::mut_records::MutFileRecords;
 use crate::records::FileRecordsSlice;
+use crate::records::MESSAGE_LOG_EXTENSION;
 use crate::BatchHeaderPos;
 use crate::ConfigOption;
+use crate::FlushPolicy;
 use crate::StorageError;
 use crate::DefaultFileBatchStream;
 use crate::index::OffsetPosition;
 use crate::validator::LogValidationError;
+use crate::validator::LogRecovery;
+use crate::validator::recover;
+use crate::validator::find_corrupt_batches;
+use crate::validator::CorruptBatch;
 use crate::util::OffsetError;
+use crate::util::generate_file_name;
 
 pub(crate) type MutableSegment = Segment<MutLogIndex, MutFileRecords>;
 pub(crate) type ReadSegment = Segment<LogIndex, FileRecordsSlice>;
@@ -51,13 +65,44 @@ impl<'a> SegmentSlice<'a> {
             Self::Segment(_) => false,
         }
     }
+
+    /// record set of slice of this segment.
+    /// Log file of read only segment is kept open until record set is dropped,
+    /// so slice can be sent after segment is removed
+    pub fn to_record_set(&self, slice: AsyncFileSlice) -> FileRecordSet {
+        match self {
+            Self::MutableSegment(_) => slice.into(),
+            Self::Segment(segment) => FileRecordSet::with_owner(slice, segment.shared_log_file()),
+        }
+    }
+
+    /// find batches with crc mismatch in slice of segment log starting at `start_offset`
+    pub async fn find_corrupt_batches(
+        &self,
+        slice: &AsyncFileSlice,
+        start_offset: Offset,
+    ) -> Result<Vec<CorruptBatch>, StorageError> {
+        match self {
+            Self::MutableSegment(segment) => {
+                segment
+                    .find_corrupt_batches(slice.position(), slice.len(), start_offset)
+                    .await
+            }
+            Self::Segment(segment) => {
+                segment
+                    .find_corrupt_batches(slice.position(), slice.len(), start_offset)
+                    .await
+            }
+        }
+    }
 }
 
-/// Segment contains both message log and index
+/// Segment contains message log, offset index and time index
 pub(crate) struct Segment<I, L> {
     option: ConfigOption,
     msg_log: L,
     index: I,
+    time_index: TimeIndex,
     base_offset: Offset,
     end_offset: Offset,
 }
@@ -124,6 +169,23 @@ where
         Ok(DefaultFileBatchStream::new(file))
     }
 
+    /// find batches with crc mismatch in `len` bytes of log from `position`
+    pub async fn find_corrupt_batches(
+        &self,
+        position: u64,
+        len: u64,
+        start_offset: Offset,
+    ) -> Result<Vec<CorruptBatch>, StorageError> {
+        Ok(find_corrupt_batches(
+            self.msg_log.get_path(),
+            position,
+            len,
+            start_offset,
+            self.get_end_offset(),
+        )
+        .await?)
+    }
+
     /// get file slice from offset to end of segment
     pub async fn records_slice(
         &self,
@@ -162,6 +224,53 @@ where
         }
     }
 
+    /// find offset of first record which has timestamp at or after timestamp
+    pub async fn find_offset_by_timestamp(
+        &self,
+        timestamp: Timestamp,
+    ) -> Result<Option<Offset>, StorageError> {
+        trace!("finding offset by timestamp: {}", timestamp);
+        // segment without time index is scanned from beginning
+        let start_pos = if self.time_index.is_empty() {
+            0
+        } else {
+            match self.time_index.find_timestamp(timestamp) {
+                Some(delta) => match self.index.find_offset(delta) {
+                    None => 0,
+                    Some(entry) => entry.position(),
+                },
+                None => return Ok(None),
+            }
+        };
+        trace!("scanning records by timestamp from pos: {}", start_pos);
+
+        let file = file_util::open(self.msg_log.get_path()).await?;
+        let mut batch_stream = DefaultFileBatchStream::new_with_pos(file, start_pos).await?;
+        while let Some(batch_pos) = batch_stream.next().await {
+            let batch = batch_pos.get_batch();
+            for record in batch.records() {
+                if batch.get_record_timestamp(record) >= timestamp {
+                    return Ok(Some(batch.get_base_offset() + record.get_offset_delta()));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// offset after last batch, batches after last index entry are scanned
+    async fn find_end_offset(&self) -> Result<Offset, StorageError> {
+        let position = match self.index.last() {
+            Some(entry) => entry.position(),
+            None => 0,
+        };
+        let mut end_offset = self.base_offset;
+        let mut header_stream = self.open_batch_header_stream(position).await?;
+        while let Some(batch_pos) = header_stream.next().await {
+            end_offset = batch_pos.get_last_offset() + 1;
+        }
+        Ok(end_offset)
+    }
+
     /// find position of the offset
     pub(crate) async fn find_offset_position(
         &self,
@@ -217,20 +326,60 @@ impl Segment<LogIndex, FileRecordsSlice> {
         let msg_log = FileRecordsSlice::open(base_offset, option).await?;
         let base_offset = msg_log.get_base_offset();
         let index = LogIndex::open_from_offset(base_offset, option).await?;
+        let time_index = TimeIndex::open_for_read(base_offset, option).await?;
 
         let base_offset = msg_log.get_base_offset();
-        Ok(Segment {
+        let mut segment = Segment {
             msg_log,
             index,
+            time_index,
             option: option.to_owned(),
             base_offset,
             end_offset: base_offset,
-        })
+        };
+        segment.end_offset = segment.find_end_offset().await?;
+        Ok(segment)
     }
 
     pub fn to_segment_slice(&self) -> SegmentSlice {
         SegmentSlice::new_segment(self)
     }
+
+    /// size of records in bytes
+    pub fn get_msg_size(&self) -> u64 {
+        self.msg_log.get_len()
+    }
+
+    /// log file shared with record sets read from this segment
+    pub fn shared_log_file(&self) -> Arc<File> {
+        self.msg_log.shared_file()
+    }
+
+    /// last time records were written to this segment
+    pub async fn get_last_modified(&self) -> Result<SystemTime, IoError> {
+        self.msg_log.get_last_modified().await
+    }
+
+    /// delete log and index files of segment
+    ///
+    /// files stay open until segment is dropped, so slices of records read before can still be sent
+    pub async fn remove_files(&self) -> Result<(), StorageError> {
+        let log_path = generate_file_name(
+            &self.option.base_dir,
+            self.base_offset,
+            MESSAGE_LOG_EXTENSION,
+        );
+        let index_path =
+            generate_file_name(&self.option.base_dir, self.base_offset, INDEX_EXTENSION);
+        let time_index_path = self.time_index.get_path().clone();
+        debug!("removing segment: {}", log_path.display());
+        remove_file(log_path).await?;
+        remove_file(index_path).await?;
+        if time_index_path.exists() {
+            remove_file(time_index_path).await?;
+        }
+        Ok(())
+    }
 }
 
 impl Unpin for Segment<MutLogIndex, MutFileRecords> {}
@@ -246,11 +395,13 @@ impl Segment<MutLogIndex, MutFileRecords> {
         let msg_log = MutFileRecords::create(base_offset, option).await?;
 
         let index = MutLogIndex::create(base_offset, option).await?;
+        let time_index = TimeIndex::create(base_offset, option).await?;
 
         Ok(MutableSegment {
             option: option.to_owned(),
             msg_log,
             index,
+            time_index,
             base_offset,
             end_offset: base_offset,
         })
@@ -268,25 +419,45 @@ impl Segment<MutLogIndex, MutFileRecords> {
         let msg_log = MutFileRecords::open(base_offset, option).await?;
         let base_offset = msg_log.get_base_offset();
         let index = MutLogIndex::open(base_offset, option).await?;
+        let time_index = TimeIndex::open_for_write(base_offset, option).await?;
 
         let base_offset = msg_log.get_base_offset();
         Ok(MutableSegment {
             option: option.to_owned(),
             msg_log,
             index,
+            time_index,
             base_offset,
             end_offset: base_offset,
         })
     }
 
+    /// open active segment after crash, log is truncated after last good batch
+    /// and indexes are trimmed to match it
+    pub async fn open_for_recovery(
+        base_offset: Offset,
+        option: &ConfigOption,
+    ) -> Result<(MutableSegment, LogRecovery), StorageError> {
+        let log_path = generate_file_name(&option.base_dir, base_offset, MESSAGE_LOG_EXTENSION);
+        let recovery = recover(&log_path).await?;
+
+        let mut segment = Self::open_for_write(base_offset, option).await?;
+        segment.index.truncate(recovery.valid_len as Size).await?;
+        segment
+            .time_index
+            .truncate((recovery.end_offset - segment.base_offset) as Size)
+            .await?;
+        segment.end_offset = recovery.end_offset;
+        Ok((segment, recovery))
+    }
+
     fn get_log_pos(&self) -> u32 {
         self.msg_log.get_pos()
     }
 
-    /// validate the segment and load last offset
-    pub async fn validate(&mut self) -> Result<(), StorageError> {
-        self.end_offset = self.msg_log.validate().await?;
-        Ok(())
+    /// size of records in bytes
+    pub fn get_msg_size(&self) -> u64 {
+        self.get_log_pos() as u64
     }
 
     // shrink index
@@ -294,8 +465,11 @@ impl Segment<MutLogIndex, MutFileRecords> {
         self.index.shrink().await
     }
 
-    // perform any action during roll over
+    // perform any action during roll over, records are synced unless OS manages it
     pub async fn roll_over(&mut self) -> Result<(), IoError> {
+        if self.option.flush_policy != FlushPolicy::OsManaged || self.option.flush_before_commit {
+            self.msg_log.sync().await?;
+        }
         self.index.shrink().await
     }
 
@@ -331,7 +505,10 @@ impl Segment<MutLogIndex, MutFileRecords> {
             ));
         }
 
-        let batch_offset_delta = (current_offset - base_offset) as i32;
+        // batch can start after current offset when segment is compacted
+        let batch_base_offset = item.get_base_offset();
+        let batch_offset_delta = (batch_base_offset - base_offset) as i32;
+        let max_timestamp = item.get_header().max_time_stamp;
         debug!(
             "start writing batch base_off: {}, pos: {}, batch record: {}",
             base_offset,
@@ -345,10 +522,13 @@ impl Segment<MutLogIndex, MutFileRecords> {
                 self.index
                     .send((batch_offset_delta as u32, pos, batch_len))
                     .await?;
+                self.time_index
+                    .send((max_timestamp, batch_offset_delta as u32))
+                    .await?;
 
                 let last_offset_delta = self.msg_log.get_item_last_offset_delta();
                 trace!("flushing: last offset delta: {}", last_offset_delta);
-                self.end_offset = self.end_offset + last_offset_delta as Offset + 1;
+                self.end_offset = batch_base_offset + last_offset_delta as Offset + 1;
                 debug!("send flushed leo: {}", self.end_offset);
                 Ok(())
             }
@@ -360,12 +540,17 @@ impl Segment<MutLogIndex, MutFileRecords> {
     pub async fn flush(&mut self) -> Result<(), StorageError> {
         self.msg_log.flush().await.map_err(|err| err.into())
     }
+
+    /// sync written records to disk
+    pub async fn sync(&mut self) -> Result<(), IoError> {
+        self.msg_log.sync().await
+    }
 }
 
 /// compute total number of values in the default batch
 fn compute_batch_record_size(batch: &DefaultBatch) -> usize {
     batch
-        .records
+        .records()
         .iter()
         .fold(0, |acc, batch| acc + batch.value.len())
 }
@@ -401,6 +586,7 @@ mod tests {
             base_dir,
             index_max_interval_bytes,
             index_max_bytes: 1000,
+            ..Default::default()
         }
     }
 
@@ -436,7 +622,7 @@ mod tests {
         let batch = DefaultBatch::decode_from(&mut Cursor::new(bytes), 0).expect("decode");
         assert_eq!(batch.get_base_offset(), 20);
         assert_eq!(batch.get_header().magic, 2, "check magic");
-        assert_eq!(batch.records.len(), 1);
+        assert_eq!(batch.records().len(), 1);
 
         let seg1_metadata = metadata(test_dir.join(SEG_INDEX)).expect("read metadata");
         assert_eq!(seg1_metadata.len(), 1000);
@@ -480,7 +666,7 @@ mod tests {
         let batch = DefaultBatch::decode_from(&mut Cursor::new(bytes), 0)?;
         assert_eq!(batch.get_base_offset(), 20);
         assert_eq!(batch.get_header().magic, 2, "check magic");
-        assert_eq!(batch.records.len(), 4);
+        assert_eq!(batch.records().len(), 4);
 
         let seg1_metadata = metadata(test_dir.join(SEG_INDEX))?;
         assert_eq!(seg1_metadata.len(), 1000);
@@ -0,0 +1,151 @@
+//!
+//! # Log Snapshot
+//!
+//! Paths and sizes of segment logs of replica, taken while replica is held.
+//! Logs are then read without access to replica, so replica can be written
+//! meanwhile. Records written after snapshot was taken are not read.
+//!
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use tracing::debug;
+
+use dataplane::{Offset, Size};
+use dataplane::batch::{BatchHeader, DefaultBatch};
+use fluvio_future::fs::util as file_util;
+
+use crate::batch::DefaultFileBatchStream;
+use crate::batch_header::BatchHeaderStream;
+use crate::validator::find_corrupt_batches;
+use crate::validator::CorruptBatch;
+use crate::validator::LogValidationError;
+use crate::StorageError;
+
+/// log of segment up to its size when snapshot was taken
+#[derive(Debug)]
+struct SegmentLog {
+    base_offset: Offset,
+    end_offset: Offset,
+    path: PathBuf,
+    len: Size,
+}
+
+/// Segment logs of replica, taken from replica by `FileReplica::log_snapshot`
+#[derive(Debug)]
+pub struct LogSnapshot {
+    /// logs ordered by base offset, active segment is last
+    logs: Vec<SegmentLog>,
+    high_watermark: Offset,
+}
+
+impl LogSnapshot {
+    pub(crate) fn new(high_watermark: Offset) -> Self {
+        Self {
+            logs: vec![],
+            high_watermark,
+        }
+    }
+
+    pub(crate) fn add_log(
+        &mut self,
+        base_offset: Offset,
+        end_offset: Offset,
+        path: PathBuf,
+        len: Size,
+    ) {
+        self.logs.push(SegmentLog {
+            base_offset,
+            end_offset,
+            path,
+            len,
+        });
+    }
+
+    /// read all committed batches of snapshot into memory
+    ///
+    /// this is meant for small replicas, such as compacted internal topics
+    pub async fn read_committed_batches(&self) -> Result<Vec<DefaultBatch>, StorageError> {
+        let mut batches = vec![];
+        for log in &self.logs {
+            debug!(
+                "reading committed batches of segment: {}, len: {}",
+                log.base_offset, log.len
+            );
+            let file = file_util::open(&log.path).await?;
+            let mut batch_stream = DefaultFileBatchStream::new(file);
+            let mut pos = 0;
+            while pos < log.len {
+                let batch_pos = match batch_stream.next().await {
+                    Some(batch_pos) => batch_pos,
+                    None => break,
+                };
+                pos = batch_pos.get_pos() + batch_pos.total_len();
+                let batch = batch_pos.into_batch();
+                if batch.get_last_offset() >= self.high_watermark {
+                    return Ok(batches);
+                }
+                batches.push(batch);
+            }
+            if let Some(err) = batch_stream.invalid() {
+                return Err(err.into());
+            }
+        }
+        Ok(batches)
+    }
+
+    /// headers of all batches in last `segments` logs of snapshot, in offset order
+    pub async fn read_batch_headers(
+        &self,
+        segments: usize,
+    ) -> Result<Vec<BatchHeader>, StorageError> {
+        let mut headers = vec![];
+        let first = self.logs.len().saturating_sub(segments);
+        for log in &self.logs[first..] {
+            debug!(
+                "reading batch headers of segment: {}, len: {}",
+                log.base_offset, log.len
+            );
+            let file = file_util::open(&log.path).await?;
+            let mut header_stream = BatchHeaderStream::new(file);
+            let mut pos = 0;
+            while pos < log.len {
+                let batch_pos = match header_stream.next().await {
+                    Some(batch_pos) => batch_pos,
+                    None => break,
+                };
+                pos = batch_pos.get_pos() + batch_pos.total_len();
+                headers.push(batch_pos.get_batch().get_header().clone());
+            }
+            if let Some(err) = header_stream.invalid() {
+                return Err(err.into());
+            }
+        }
+        Ok(headers)
+    }
+
+    /// scan logs of snapshot for batches whose crc doesn't match
+    ///
+    /// log of segment removed after snapshot was taken is skipped
+    pub async fn scrub(&self) -> Result<Vec<CorruptBatch>, StorageError> {
+        let mut corrupted = vec![];
+        for log in &self.logs {
+            debug!("scrubbing segment: {}, len: {}", log.base_offset, log.len);
+            match find_corrupt_batches(
+                &log.path,
+                0,
+                log.len as u64,
+                log.base_offset,
+                log.end_offset,
+            )
+            .await
+            {
+                Ok(batches) => corrupted.extend(batches),
+                Err(LogValidationError::IoError(err)) if err.kind() == ErrorKind::NotFound => {
+                    debug!("segment: {} is removed, skipping", log.base_offset);
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(corrupted)
+    }
+}
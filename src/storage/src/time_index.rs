@@ -0,0 +1,256 @@
+use std::io::Error as IoError;
+use std::io::ErrorKind;
+use std::mem::size_of;
+use std::path::PathBuf;
+
+use tracing::debug;
+use tracing::trace;
+use futures_lite::io::AsyncWriteExt;
+
+use fluvio_future::fs::File;
+use fluvio_future::fs::OpenOptions;
+use fluvio_future::fs::read;
+use dataplane::{Offset, Size, Timestamp};
+
+use crate::util::generate_file_name;
+use crate::ConfigOption;
+
+/// size of each entry, timestamp and relative offset
+const TIME_INDEX_ENTRY_SIZE: usize = size_of::<Timestamp>() + size_of::<Size>();
+
+pub const EXTENSION: &str = "timeindex";
+
+/// Segment time index
+///
+/// Maps timestamp into relative offset of the batch where the timestamp was first reached.
+/// Entry is only added when batch has max timestamp greater than any previous batch,
+/// so entries are always sorted by timestamp.
+///
+/// Entries are kept in memory and appended to the file as they are added.
+pub(crate) struct TimeIndex {
+    path: PathBuf,
+    file: Option<File>,
+    entries: Vec<(Timestamp, Size)>,
+}
+
+impl TimeIndex {
+    /// create new empty time index, existing index file is truncated
+    pub async fn create(base_offset: Offset, option: &ConfigOption) -> Result<Self, IoError> {
+        let path = generate_file_name(&option.base_dir, base_offset, EXTENSION);
+        debug!("creating time index at: {:#?}", path);
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .await?;
+
+        Ok(Self {
+            path,
+            file: Some(file),
+            entries: vec![],
+        })
+    }
+
+    /// open time index for active segment, new entries can be added
+    pub async fn open_for_write(
+        base_offset: Offset,
+        option: &ConfigOption,
+    ) -> Result<Self, IoError> {
+        let mut index = Self::open_for_read(base_offset, option).await?;
+        let file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&index.path)
+            .await?;
+        index.file = Some(file);
+        Ok(index)
+    }
+
+    /// load time index of read only segment
+    ///
+    /// segments written before time index was introduced don't have index file,
+    /// those are loaded as empty index
+    pub async fn open_for_read(
+        base_offset: Offset,
+        option: &ConfigOption,
+    ) -> Result<Self, IoError> {
+        let path = generate_file_name(&option.base_dir, base_offset, EXTENSION);
+        let bytes = match read(&path).await {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                trace!("no time index found at: {:#?}", path);
+                vec![]
+            }
+            Err(err) => return Err(err),
+        };
+
+        // partially written entry at the end is ignored
+        let entries = bytes
+            .chunks_exact(TIME_INDEX_ENTRY_SIZE)
+            .map(|entry| {
+                let mut timestamp = [0u8; size_of::<Timestamp>()];
+                let mut offset = [0u8; size_of::<Size>()];
+                timestamp.copy_from_slice(&entry[..size_of::<Timestamp>()]);
+                offset.copy_from_slice(&entry[size_of::<Timestamp>()..]);
+                (
+                    Timestamp::from_be_bytes(timestamp),
+                    Size::from_be_bytes(offset),
+                )
+            })
+            .collect();
+
+        Ok(Self {
+            path,
+            file: None,
+            entries,
+        })
+    }
+
+    pub fn get_path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// max timestamp and relative offset of batches
+    pub fn entries(&self) -> &[(Timestamp, Size)] {
+        &self.entries
+    }
+
+    /// add max timestamp of batch at relative offset
+    pub async fn send(&mut self, item: (Timestamp, Size)) -> Result<(), IoError> {
+        let (timestamp, relative_offset) = item;
+        if let Some((last_timestamp, _)) = self.entries.last() {
+            if timestamp <= *last_timestamp {
+                trace!(
+                    "time index skipped, timestamp: {} is not after: {}",
+                    timestamp,
+                    last_timestamp
+                );
+                return Ok(());
+            }
+        }
+
+        let file = match self.file.as_mut() {
+            Some(file) => file,
+            None => {
+                return Err(IoError::new(
+                    ErrorKind::PermissionDenied,
+                    "time index is read only",
+                ))
+            }
+        };
+
+        let mut entry = Vec::with_capacity(TIME_INDEX_ENTRY_SIZE);
+        entry.extend_from_slice(&timestamp.to_be_bytes());
+        entry.extend_from_slice(&relative_offset.to_be_bytes());
+        file.write_all(&entry).await?;
+        file.flush().await?;
+        self.entries.push(item);
+        trace!("time index written: {:#?}", item);
+        Ok(())
+    }
+
+    /// remove entries at or after relative offset, after log is truncated
+    pub async fn truncate(&mut self, relative_offset: Size) -> Result<(), IoError> {
+        let keep = self
+            .entries
+            .iter()
+            .position(|(_, offset)| *offset >= relative_offset)
+            .unwrap_or(self.entries.len());
+        if keep == self.entries.len() {
+            return Ok(());
+        }
+
+        debug!(
+            "truncating time index: {:#?} to: {} entries",
+            self.path, keep
+        );
+        let file = match self.file.as_mut() {
+            Some(file) => file,
+            None => {
+                return Err(IoError::new(
+                    ErrorKind::PermissionDenied,
+                    "time index is read only",
+                ))
+            }
+        };
+        file.set_len((keep * TIME_INDEX_ENTRY_SIZE) as u64).await?;
+        self.entries.truncate(keep);
+        Ok(())
+    }
+
+    /// find relative offset of first batch which has records at or after timestamp
+    pub fn find_timestamp(&self, timestamp: Timestamp) -> Option<Size> {
+        let idx = match self
+            .entries
+            .binary_search_by(|(entry_timestamp, _)| entry_timestamp.cmp(&timestamp))
+        {
+            Ok(idx) => idx,
+            Err(idx) => idx,
+        };
+        self.entries.get(idx).map(|(_, offset)| *offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::Error as IoError;
+
+    use fluvio_future::test_async;
+    use flv_util::fixture::ensure_clean_file;
+
+    use super::TimeIndex;
+    use crate::fixture::default_option;
+
+    const TEST_FILE: &str = "00000000000000000150.timeindex";
+
+    #[test_async]
+    async fn test_time_index_write_and_find() -> Result<(), IoError> {
+        let option = default_option(0);
+        let test_file = option.base_dir.join(TEST_FILE);
+        ensure_clean_file(&test_file);
+
+        let mut index = TimeIndex::create(150, &option).await?;
+        assert_eq!(index.find_timestamp(100), None);
+
+        index.send((1000, 0)).await?;
+        index.send((900, 2)).await?; // ignored, before last timestamp
+        index.send((2000, 5)).await?;
+        index.send((3000, 9)).await?;
+
+        assert_eq!(index.find_timestamp(0), Some(0));
+        assert_eq!(index.find_timestamp(1000), Some(0));
+        assert_eq!(index.find_timestamp(1500), Some(5));
+        assert_eq!(index.find_timestamp(3000), Some(9));
+        assert_eq!(index.find_timestamp(3001), None);
+        drop(index);
+
+        let index = TimeIndex::open_for_read(150, &option).await?;
+        assert_eq!(index.find_timestamp(1500), Some(5));
+
+        let mut index = TimeIndex::open_for_write(150, &option).await?;
+        index.send((4000, 12)).await?;
+        assert_eq!(index.find_timestamp(3500), Some(12));
+        drop(index);
+
+        let index = TimeIndex::open_for_read(150, &option).await?;
+        assert_eq!(index.find_timestamp(3500), Some(12));
+
+        let mut index = TimeIndex::open_for_write(150, &option).await?;
+        index.truncate(6).await?;
+        assert_eq!(index.find_timestamp(2500), None);
+        index.send((5000, 7)).await?;
+        drop(index);
+
+        let index = TimeIndex::open_for_read(150, &option).await?;
+        assert_eq!(index.find_timestamp(1500), Some(5));
+        assert_eq!(index.find_timestamp(2500), Some(7));
+
+        Ok(())
+    }
+}
@@ -1,12 +1,20 @@
 use std::io::Error as IoError;
+use std::io::Cursor;
+use std::io::SeekFrom;
+use std::cmp::min;
 use std::fmt;
 use std::path::Path;
 
 use tracing::warn;
 use tracing::trace;
+use futures_lite::io::AsyncReadExt;
+use futures_lite::io::AsyncSeekExt;
 
 use dataplane::Offset;
+use dataplane::core::Decoder;
+use dataplane::batch::{BatchHeader, BATCH_PREAMBLE_SIZE, BATCH_HEADER_SIZE};
 use fluvio_future::fs::util as file_util;
+use fluvio_future::fs::OpenOptions;
 
 use crate::BatchHeaderStream;
 use crate::util::log_path_get_offset;
@@ -113,6 +121,206 @@ where
     Ok(end_offset + 1)
 }
 
+/// bytes of batch header before crc, partition leader epoch and magic
+const BATCH_CRC_POS: usize = 5;
+
+/// bytes of batch header covered by crc start after crc
+const BATCH_CRC_END: usize = BATCH_CRC_POS + 4;
+
+/// check crc of batch bytes after preamble
+fn batch_crc_matches(bytes: &[u8], header: &BatchHeader) -> bool {
+    crc32c::crc32c(&bytes[BATCH_CRC_END..]) == header.crc
+}
+
+/// batch in log whose content doesn't match its crc
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorruptBatch {
+    /// position of batch in log
+    pub position: u64,
+    pub base_offset: Offset,
+    /// last offset of batch, or of the range when batch length is unreadable
+    pub last_offset: Offset,
+}
+
+/// scan batches of log in range from `position` with `len` bytes for crc mismatch
+///
+/// `start_offset` and `end_offset` are offsets of range, used when batch length
+/// is corrupted and rest of range can't be scanned. batch which continues after
+/// range is not checked unless range ends at end of log.
+pub async fn find_corrupt_batches<P>(
+    path: P,
+    position: u64,
+    len: u64,
+    start_offset: Offset,
+    end_offset: Offset,
+) -> Result<Vec<CorruptBatch>, LogValidationError>
+where
+    P: AsRef<Path>,
+{
+    let file_path = path.as_ref();
+    let mut file = file_util::open(file_path).await?;
+    let file_len = file.metadata().await?.len();
+    let range_end = min(position + len, file_len);
+    file.seek(SeekFrom::Start(position)).await?;
+
+    let mut corrupted = vec![];
+    let mut pos = position;
+    let mut next_offset = start_offset;
+    let mut preamble = [0u8; BATCH_PREAMBLE_SIZE];
+    while pos + BATCH_PREAMBLE_SIZE as u64 <= range_end {
+        file.read_exact(&mut preamble).await?;
+        let mut batch_base_offset: Offset = 0;
+        let mut batch_len: i32 = 0;
+        let mut cursor = Cursor::new(&preamble);
+        batch_base_offset.decode(&mut cursor, 0)?;
+        batch_len.decode(&mut cursor, 0)?;
+
+        // length is checked before it is used as size, garbage can make it negative
+        let valid_len = batch_len >= BATCH_HEADER_SIZE as i32;
+        let batch_end = pos + BATCH_PREAMBLE_SIZE as u64 + batch_len.max(0) as u64;
+        if valid_len && batch_end > range_end && range_end < file_len {
+            trace!("batch at pos: {} continues after range", pos);
+            break;
+        }
+
+        if !valid_len || batch_end > file_len {
+            warn!(
+                "batch at pos: {} has invalid len: {}, log: {}",
+                pos,
+                batch_len,
+                file_path.display()
+            );
+            corrupted.push(CorruptBatch {
+                position: pos,
+                base_offset: next_offset,
+                last_offset: end_offset - 1,
+            });
+            break;
+        }
+
+        let mut bytes = vec![0u8; batch_len as usize];
+        file.read_exact(&mut bytes).await?;
+        let header = BatchHeader::decode_from(&mut Cursor::new(&bytes), 0)?;
+        let last_offset = batch_base_offset + header.last_offset_delta.max(0) as Offset;
+        if !batch_crc_matches(&bytes, &header) {
+            warn!(
+                "batch at pos: {} with offsets: {}..={} has crc mismatch, log: {}",
+                pos,
+                batch_base_offset,
+                last_offset,
+                file_path.display()
+            );
+            corrupted.push(CorruptBatch {
+                position: pos,
+                base_offset: batch_base_offset,
+                last_offset,
+            });
+        }
+
+        next_offset = last_offset + 1;
+        pos = batch_end;
+    }
+
+    Ok(corrupted)
+}
+
+/// result of log recovery
+#[derive(Debug, Default, PartialEq)]
+pub struct LogRecovery {
+    /// offset after last good batch
+    pub end_offset: Offset,
+    /// length of log up to end of last good batch
+    pub valid_len: u64,
+    /// bytes after last good batch which were truncated
+    pub discarded_bytes: u64,
+}
+
+/// scan batches of log and truncate it after last good batch
+///
+/// batch is good if it is completely written, its crc matches and its offsets
+/// follow previous batch. log can end with partially written batch if writer
+/// crashed, that batch and anything after it is discarded.
+pub async fn recover<P>(path: P) -> Result<LogRecovery, LogValidationError>
+where
+    P: AsRef<Path>,
+{
+    let file_path = path.as_ref();
+    let base_offset = log_path_get_offset(file_path)?;
+    let mut file = file_util::open(file_path).await?;
+    let file_len = file.metadata().await?.len();
+
+    let mut pos: u64 = 0;
+    let mut end_offset: Offset = base_offset;
+    let mut preamble = [0u8; BATCH_PREAMBLE_SIZE];
+    while pos + BATCH_PREAMBLE_SIZE as u64 <= file_len {
+        file.read_exact(&mut preamble).await?;
+        let mut batch_base_offset: Offset = 0;
+        let mut batch_len: i32 = 0;
+        let mut cursor = Cursor::new(&preamble);
+        batch_base_offset.decode(&mut cursor, 0)?;
+        batch_len.decode(&mut cursor, 0)?;
+
+        // length is checked before it is used as size, garbage can make it negative
+        if batch_len < BATCH_HEADER_SIZE as i32
+            || pos + BATCH_PREAMBLE_SIZE as u64 + batch_len as u64 > file_len
+        {
+            warn!(
+                "batch at pos: {} with len: {} is not completely written, log: {}",
+                pos,
+                batch_len,
+                file_path.display()
+            );
+            break;
+        }
+
+        let mut bytes = vec![0u8; batch_len as usize];
+        file.read_exact(&mut bytes).await?;
+        let header = BatchHeader::decode_from(&mut Cursor::new(&bytes), 0)?;
+        if !batch_crc_matches(&bytes, &header) {
+            warn!(
+                "batch at pos: {} has crc mismatch, log: {}",
+                pos,
+                file_path.display()
+            );
+            break;
+        }
+
+        if batch_base_offset < end_offset || header.last_offset_delta < 0 {
+            warn!(
+                "batch at pos: {} has base offset: {} before end offset: {}, log: {}",
+                pos,
+                batch_base_offset,
+                end_offset,
+                file_path.display()
+            );
+            break;
+        }
+
+        end_offset = batch_base_offset + header.last_offset_delta as Offset + 1;
+        pos += (BATCH_PREAMBLE_SIZE + batch_len as usize) as u64;
+    }
+    drop(file);
+
+    let discarded_bytes = file_len - pos;
+    if discarded_bytes > 0 {
+        warn!(
+            "truncating log: {} to: {} bytes, discarding: {} bytes",
+            file_path.display(),
+            pos,
+            discarded_bytes
+        );
+        let file = OpenOptions::new().write(true).open(file_path).await?;
+        file.set_len(pos).await?;
+        file.sync_all().await?;
+    }
+
+    Ok(LogRecovery {
+        end_offset,
+        valid_len: pos,
+        discarded_bytes,
+    })
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -132,6 +340,9 @@ mod tests {
     use crate::ConfigOption;
 
     use super::validate;
+    use super::recover;
+    use super::find_corrupt_batches;
+    use super::CorruptBatch;
     use crate::StorageError;
 
     const PRODUCER: i64 = 33;
@@ -255,4 +466,200 @@ mod tests {
 
         Ok(())
     }
+
+    const TEST_FILE_RECOVER: &str = "00000000000000000701.log";
+
+    #[test_async]
+    async fn test_recover_torn_batch() -> Result<(), StorageError> {
+        let test_file = temp_dir().join(TEST_FILE_RECOVER);
+        ensure_clean_file(&test_file);
+
+        let options = ConfigOption {
+            base_dir: temp_dir(),
+            segment_max_bytes: 1000,
+            ..Default::default()
+        };
+
+        let mut msg_sink = MutFileRecords::create(701, &options).await?;
+        msg_sink.send(create_batch(701, 2)).await?;
+        let valid_len = msg_sink.get_pos() as u64;
+        msg_sink.send(create_batch(703, 3)).await?;
+        let full_len = msg_sink.get_pos() as u64;
+        drop(msg_sink);
+
+        // second batch is partially written
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&test_file)
+            .expect("open log");
+        file.set_len(full_len - 5).expect("truncate");
+        drop(file);
+
+        let recovery = recover(&test_file).await?;
+        assert_eq!(recovery.end_offset, 703);
+        assert_eq!(recovery.valid_len, valid_len);
+        assert_eq!(recovery.discarded_bytes, full_len - 5 - valid_len);
+        assert_eq!(std::fs::metadata(&test_file)?.len(), valid_len);
+        assert_eq!(validate(&test_file).await?, 703);
+
+        // nothing to recover
+        let recovery = recover(&test_file).await?;
+        assert_eq!(recovery.discarded_bytes, 0);
+
+        Ok(())
+    }
+
+    const TEST_FILE_RECOVER_NEGATIVE: &str = "00000000000000000751.log";
+
+    #[test_async]
+    async fn test_recover_negative_batch_len() -> Result<(), StorageError> {
+        use std::io::{Seek, SeekFrom, Write};
+
+        let test_file = temp_dir().join(TEST_FILE_RECOVER_NEGATIVE);
+        ensure_clean_file(&test_file);
+
+        let options = ConfigOption {
+            base_dir: temp_dir(),
+            segment_max_bytes: 1000,
+            ..Default::default()
+        };
+
+        let mut msg_sink = MutFileRecords::create(751, &options).await?;
+        msg_sink.send(create_batch(751, 2)).await?;
+        let valid_len = msg_sink.get_pos() as u64;
+        msg_sink.send(create_batch(753, 3)).await?;
+        drop(msg_sink);
+
+        // length of second batch is overwritten with -1
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&test_file)
+            .expect("open log");
+        file.seek(SeekFrom::Start(valid_len + 8))?;
+        file.write_all(&[0xff; 4])?;
+        drop(file);
+
+        let recovery = recover(&test_file).await?;
+        assert_eq!(recovery.end_offset, 753);
+        assert_eq!(recovery.valid_len, valid_len);
+        assert_eq!(std::fs::metadata(&test_file)?.len(), valid_len);
+
+        Ok(())
+    }
+
+    const TEST_FILE_RECOVER_CRC: &str = "00000000000000000801.log";
+
+    #[test_async]
+    async fn test_recover_crc_mismatch() -> Result<(), StorageError> {
+        use std::io::{Seek, SeekFrom, Write};
+
+        let test_file = temp_dir().join(TEST_FILE_RECOVER_CRC);
+        ensure_clean_file(&test_file);
+
+        let options = ConfigOption {
+            base_dir: temp_dir(),
+            segment_max_bytes: 1000,
+            ..Default::default()
+        };
+
+        let mut msg_sink = MutFileRecords::create(801, &options).await?;
+        msg_sink.send(create_batch(801, 2)).await?;
+        let valid_len = msg_sink.get_pos() as u64;
+        msg_sink.send(create_batch(803, 3)).await?;
+        let full_len = msg_sink.get_pos() as u64;
+        drop(msg_sink);
+
+        // corrupt last byte of second batch
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&test_file)
+            .expect("open log");
+        file.seek(SeekFrom::Start(full_len - 1))?;
+        file.write_all(&[0xff])?;
+        drop(file);
+
+        let recovery = recover(&test_file).await?;
+        assert_eq!(recovery.end_offset, 803);
+        assert_eq!(recovery.discarded_bytes, full_len - valid_len);
+
+        Ok(())
+    }
+
+    const TEST_FILE_CORRUPT: &str = "00000000000000000901.log";
+
+    #[test_async]
+    async fn test_find_corrupt_batches() -> Result<(), StorageError> {
+        use std::io::{Seek, SeekFrom, Write};
+
+        let test_file = temp_dir().join(TEST_FILE_CORRUPT);
+        ensure_clean_file(&test_file);
+
+        let options = ConfigOption {
+            base_dir: temp_dir(),
+            segment_max_bytes: 1000,
+            ..Default::default()
+        };
+
+        let mut msg_sink = MutFileRecords::create(901, &options).await?;
+        msg_sink.send(create_batch(901, 2)).await?;
+        let first_len = msg_sink.get_pos() as u64;
+        msg_sink.send(create_batch(903, 3)).await?;
+        let second_end = msg_sink.get_pos() as u64;
+        msg_sink.send(create_batch(906, 1)).await?;
+        let full_len = msg_sink.get_pos() as u64;
+        drop(msg_sink);
+
+        assert!(find_corrupt_batches(&test_file, 0, full_len, 901, 907)
+            .await?
+            .is_empty());
+
+        // corrupt last byte of second batch
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&test_file)
+            .expect("open log");
+        file.seek(SeekFrom::Start(second_end - 1))?;
+        file.write_all(&[0xff])?;
+        drop(file);
+
+        let corrupted = find_corrupt_batches(&test_file, 0, full_len, 901, 907).await?;
+        assert_eq!(
+            corrupted,
+            vec![CorruptBatch {
+                position: first_len,
+                base_offset: 903,
+                last_offset: 905,
+            }]
+        );
+
+        // second batch is not completely in range
+        assert!(
+            find_corrupt_batches(&test_file, 0, second_end - 1, 901, 907)
+                .await?
+                .is_empty()
+        );
+
+        // length of second batch is overwritten with -1, rest of log can't be scanned
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&test_file)
+            .expect("open log");
+        file.seek(SeekFrom::Start(first_len + 8))?;
+        file.write_all(&[0xff; 4])?;
+        drop(file);
+
+        let corrupted = find_corrupt_batches(&test_file, 0, full_len, 901, 907).await?;
+        assert_eq!(
+            corrupted,
+            vec![CorruptBatch {
+                position: first_len,
+                base_offset: 903,
+                last_offset: 906,
+            }]
+        );
+        let corrupted = find_corrupt_batches(&test_file, 0, second_end - 1, 901, 907).await?;
+        assert_eq!(corrupted.len(), 1);
+
+        Ok(())
+    }
 }
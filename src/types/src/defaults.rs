@@ -29,6 +29,13 @@ pub const SPU_LOG_SIZE: &str = "1Gi";
 pub const SPU_LOG_INDEX_MAX_BYTES: u32 = 10485760;
 pub const SPU_LOG_INDEX_MAX_INTERVAL_BYTES: u32 = 4096;
 pub const SPU_LOG_SEGMENT_MAX_BYTES: u32 = 1073741824;
+pub const SPU_RETENTION_CHECK_INTERVAL_SEC: u64 = 300; // 5 min
+pub const SPU_LOG_DELETE_RETENTION_SEC: u32 = 86400; // 1 day
+
+// Consumer group defaults
+pub const CONSUMER_OFFSETS_TOPIC: &str = "__consumer_offsets";
+pub const CONSUMER_OFFSETS_PARTITIONS: i32 = 10;
+pub const CONSUMER_OFFSETS_REPLICATION: i32 = 3;
 
 // CLI config
 pub const CLI_PROFILES_DIR: &str = "profiles";
@@ -54,6 +61,8 @@ pub const FLV_LOG_SIZE: &str = "FLV_LOG_SIZE";
 pub const FLV_LOG_INDEX_MAX_BYTES: &str = "FLV_LOG_INDEX_MAX_BYTES";
 pub const FLV_LOG_INDEX_MAX_INTERVAL_BYTES: &str = "FLV_LOG_INDEX_MAX_INTERVAL_BYTES";
 pub const FLV_LOG_SEGMENT_MAX_BYTES: &str = "FLV_LOG_SEGMENT_MAX_BYTES";
+pub const FLV_LOG_RETENTION_SECONDS: &str = "FLV_LOG_RETENTION_SECONDS";
+pub const FLV_LOG_RETENTION_BYTES: &str = "FLV_LOG_RETENTION_BYTES";
 
 // Health Checks
 pub const HC_SPU_TRIGGER_INTERVAL_SEC: u64 = 60 * 5;
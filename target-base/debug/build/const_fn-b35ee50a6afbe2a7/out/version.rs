@@ -0,0 +1 @@
+Version { minor: 95, nightly: false }